@@ -0,0 +1,577 @@
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+use actix_web::{http::header::{ContentDisposition, DispositionParam, DispositionType}, web, HttpResponse, Responder};
+use bytes::Bytes;
+use mongodb::{bson::{doc, oid::ObjectId, Document}, Client};
+use futures::stream::TryStreamExt;
+use chrono::{Duration, Utc};
+use serde::{de::DeserializeOwned, Serialize};
+use crate::controllers::task_controller::check_description;
+use crate::models::account_model::{
+    AccountArchive, AccountExport, AccountExportResponse, AccountImportReport, ArchiveProfile, ExportStatus,
+    ARCHIVE_VERSION,
+};
+use crate::models::attachment_model::{Attachment, AttachmentResponse};
+use crate::models::comment_model::Comment;
+use crate::models::project_model::Project;
+use crate::models::task_model::Task;
+use crate::models::user_model::User;
+use crate::models::workflow_model::{Workflow, DEFAULT_WORKFLOW_ID};
+use crate::storage::{Storage, StorageError};
+use crate::utils::auth::AuthenticatedUser;
+use crate::utils::markdown::linked_task_ids;
+use crate::utils::rank::rank_sequence;
+
+async fn find_all<T>(client: &Client, collection: &str, filter: Document) -> Result<Vec<T>, String>
+where
+    T: DeserializeOwned + Send + Sync,
+{
+    client
+        .database("rust_backend")
+        .collection::<T>(collection)
+        .find(filter)
+        .sort(doc! { "created_at": 1 })
+        .await
+        .map_err(|e| e.to_string())?
+        .try_collect()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+async fn build_archive(client: &Client, user_id: &str) -> Result<AccountArchive, String> {
+    let oid = ObjectId::parse_str(user_id).map_err(|e| e.to_string())?;
+    let user = client
+        .database("rust_backend")
+        .collection::<User>("users")
+        .find_one(doc! { "_id": oid })
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or("User not found")?;
+
+    let projects: Vec<Project> = find_all(client, "projects", doc! { "user_id": user_id }).await?;
+    // Trashed tasks are part of the account too.
+    let tasks: Vec<Task> = find_all(client, "tasks", doc! { "user_id": user_id }).await?;
+    // Workflows shared by other workspace members come along when tasks use them.
+    let used: Vec<ObjectId> = tasks.iter().filter_map(|t| ObjectId::parse_str(&t.workflow_id).ok()).collect();
+    let workflows: Vec<Workflow> =
+        find_all(client, "workflows", doc! { "$or": [{ "user_id": user_id }, { "_id": { "$in": used } }] }).await?;
+    let task_ids: Vec<&String> = tasks.iter().filter_map(|t| t.id.as_ref()).collect();
+    let comments: Vec<Comment> = find_all(client, "comments", doc! { "task_id": { "$in": &task_ids } }).await?;
+    let attachments: Vec<Attachment> = find_all(client, "attachments", doc! { "task_id": { "$in": &task_ids } }).await?;
+    let labels: BTreeSet<&String> = tasks.iter().flat_map(|t| &t.labels).collect();
+
+    Ok(AccountArchive {
+        version: ARCHIVE_VERSION,
+        exported_at: Utc::now(),
+        profile: ArchiveProfile { id: user_id.to_string(), name: user.name, email: user.email },
+        labels: labels.into_iter().cloned().collect(),
+        workflows,
+        projects,
+        tasks,
+        comments,
+        attachments: attachments.into_iter().map(AttachmentResponse::from).collect(),
+    })
+}
+
+/// Builds the archive and stores it, then marks the export ready or failed.
+async fn run_export(client: Client, storage: Arc<dyn Storage>, export_id: ObjectId, user_id: String, storage_key: String) {
+    let result = match build_archive(&client, &user_id).await {
+        Ok(archive) => match serde_json::to_vec(&archive) {
+            Ok(data) => {
+                let size = data.len() as i64;
+                storage
+                    .put(&storage_key, Bytes::from(data), "application/json")
+                    .await
+                    .map(|_| size)
+                    .map_err(|e| e.to_string())
+            }
+            Err(err) => Err(err.to_string()),
+        },
+        Err(err) => Err(err),
+    };
+
+    let update = match result {
+        Ok(size) => doc! { "$set": { "status": "ready", "size": size, "completed_at": Utc::now().to_string() } },
+        Err(err) => {
+            eprintln!("❌ account export {} failed: {}", export_id.to_hex(), err);
+            doc! { "$set": { "status": "failed", "error": err, "completed_at": Utc::now().to_string() } }
+        }
+    };
+
+    let collection = client.database("rust_backend").collection::<AccountExport>("account_exports");
+    if let Err(err) = collection.update_one(doc! { "_id": export_id }, update).await {
+        eprintln!("❌ failed to update account export {}: {}", export_id.to_hex(), err);
+    }
+}
+
+/// Starts exporting the caller's account. The archive is built in the background and
+/// replaces the previous one; poll the export until it is `ready`.
+pub async fn create_export(
+    auth: AuthenticatedUser,
+    client: web::Data<Client>,
+    storage: web::Data<dyn Storage>,
+) -> impl Responder {
+    let user_id = auth.0;
+    let collection = client.database("rust_backend").collection::<AccountExport>("account_exports");
+
+    // An export still pending after an hour was cut short by a restart.
+    let stale = (Utc::now() - Duration::hours(1)).to_string();
+    match collection.find_one(doc! { "user_id": &user_id, "status": "pending", "created_at": { "$gt": stale } }).await {
+        Ok(Some(_)) => return HttpResponse::Conflict().body("An export is already being prepared"),
+        Ok(None) => {}
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    }
+
+    let previous: Vec<AccountExport> = match find_all(&client, "account_exports", doc! { "user_id": &user_id }).await {
+        Ok(p) => p,
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    };
+    for export in &previous {
+        match storage.delete(&export.storage_key).await {
+            Ok(()) | Err(StorageError::NotFound) => {}
+            Err(err) => return HttpResponse::InternalServerError().body(format!("Error deleting previous export: {}", err)),
+        }
+    }
+    if let Err(err) = collection.delete_many(doc! { "user_id": &user_id }).await {
+        return HttpResponse::InternalServerError().body(format!("Error deleting previous export: {}", err));
+    }
+
+    let export_id = ObjectId::new();
+    let mut export = AccountExport {
+        id: None,
+        user_id: user_id.clone(),
+        status: ExportStatus::Pending,
+        storage_key: format!("exports/{}/{}.json", user_id, export_id.to_hex()),
+        size: None,
+        error: None,
+        created_at: Utc::now(),
+        completed_at: None,
+    };
+    let mut document = match mongodb::bson::to_document(&export) {
+        Ok(d) => d,
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Serialization error: {}", err)),
+    };
+    document.insert("_id", export_id);
+
+    if let Err(err) = client.database("rust_backend").collection::<Document>("account_exports").insert_one(document).await {
+        return HttpResponse::InternalServerError().body(format!("Error creating export: {}", err));
+    }
+    export.id = Some(export_id.to_hex());
+
+    actix_web::rt::spawn(run_export(
+        client.get_ref().clone(),
+        storage.into_inner(),
+        export_id,
+        user_id,
+        export.storage_key.clone(),
+    ));
+
+    HttpResponse::Accepted().json(AccountExportResponse::from(export))
+}
+
+async fn find_export(client: &Client, user_id: &str, export_id: &str) -> Result<Option<AccountExport>, HttpResponse> {
+    let oid = match ObjectId::parse_str(export_id) {
+        Ok(o) => o,
+        Err(_) => return Ok(None),
+    };
+
+    client
+        .database("rust_backend")
+        .collection::<AccountExport>("account_exports")
+        .find_one(doc! { "_id": oid, "user_id": user_id })
+        .await
+        .map_err(|err| HttpResponse::InternalServerError().body(format!("Database error: {}", err)))
+}
+
+pub async fn get_export(
+    auth: AuthenticatedUser,
+    export_id: web::Path<String>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    match find_export(&client, &auth.0, &export_id).await {
+        Ok(Some(export)) => HttpResponse::Ok().json(AccountExportResponse::from(export)),
+        Ok(None) => HttpResponse::NotFound().body("Export not found"),
+        Err(response) => response,
+    }
+}
+
+pub async fn download_export(
+    auth: AuthenticatedUser,
+    export_id: web::Path<String>,
+    client: web::Data<Client>,
+    storage: web::Data<dyn Storage>,
+) -> impl Responder {
+    let export = match find_export(&client, &auth.0, &export_id).await {
+        Ok(Some(e)) => e,
+        Ok(None) => return HttpResponse::NotFound().body("Export not found"),
+        Err(response) => return response,
+    };
+    if export.status != ExportStatus::Ready {
+        return HttpResponse::Conflict().body("Export is not ready");
+    }
+
+    match storage.get(&export.storage_key).await {
+        Ok(stream) => HttpResponse::Ok()
+            .content_type("application/json")
+            .insert_header(ContentDisposition {
+                disposition: DispositionType::Attachment,
+                parameters: vec![DispositionParam::Filename(format!(
+                    "account-export-{}.json",
+                    export.created_at.format("%Y-%m-%d")
+                ))],
+            })
+            .streaming(stream),
+        Err(StorageError::NotFound) => HttpResponse::NotFound().body("Export content not found"),
+        Err(err) => HttpResponse::InternalServerError().body(format!("Error reading export: {}", err)),
+    }
+}
+
+/// Serializes `record` with a new `_id`.
+fn with_id<T: Serialize>(record: &T, oid: ObjectId) -> Result<Document, String> {
+    let mut document = mongodb::bson::to_document(record).map_err(|e| e.to_string())?;
+    document.insert("_id", oid);
+    Ok(document)
+}
+
+async fn insert_all(client: &Client, collection: &str, documents: Vec<Document>) -> Result<(), String> {
+    if documents.is_empty() {
+        return Ok(());
+    }
+    client
+        .database("rust_backend")
+        .collection::<Document>(collection)
+        .insert_many(documents)
+        .await
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+/// Undoes a failed import. The account was empty before, so everything it owns goes.
+async fn rollback_import(client: &Client, user_id: &str, comment_ids: Vec<ObjectId>) {
+    let database = client.database("rust_backend");
+    for collection in ["workflows", "projects", "tasks"] {
+        if let Err(err) = database.collection::<Document>(collection).delete_many(doc! { "user_id": user_id }).await {
+            eprintln!("❌ failed to roll back imported {}: {}", collection, err);
+        }
+    }
+    if let Err(err) = database.collection::<Document>("comments").delete_many(doc! { "_id": { "$in": comment_ids } }).await {
+        eprintln!("❌ failed to roll back imported comments: {}", err);
+    }
+}
+
+/// The records of `archive` ready to insert for `user_id`, with new ids and every
+/// reference between them remapped. References to records outside the archive are
+/// dropped and every record, comments included, belongs to `user_id`.
+#[derive(Debug)]
+struct ImportPlan {
+    workflows: Vec<Document>,
+    projects: Vec<Document>,
+    tasks: Vec<Document>,
+    comments: Vec<Document>,
+    comment_ids: Vec<ObjectId>,
+}
+
+fn plan_import(archive: AccountArchive, user_id: &str) -> Result<ImportPlan, String> {
+    let old_ids = archive
+        .workflows
+        .iter()
+        .filter_map(|w| w.id.clone())
+        .chain(archive.projects.iter().filter_map(|p| p.id.clone()))
+        .chain(archive.tasks.iter().filter_map(|t| t.id.clone()))
+        .chain(archive.comments.iter().filter_map(|c| c.id.clone()));
+    let ids: HashMap<String, ObjectId> = old_ids.map(|id| (id, ObjectId::new())).collect();
+    let new_id = |id: &Option<String>| id.as_ref().and_then(|id| ids.get(id)).copied();
+    let remap = |id: &String| ids.get(id).map(|oid| oid.to_hex());
+
+    let mut plan = ImportPlan { workflows: Vec::new(), projects: Vec::new(), tasks: Vec::new(), comments: Vec::new(), comment_ids: Vec::new() };
+
+    let mut workflows: HashMap<String, Workflow> = HashMap::new();
+    for mut workflow in archive.workflows {
+        let Some(oid) = new_id(&workflow.id) else { continue };
+        workflow.validate().map_err(|err| format!("workflow {}: {}", workflow.name, err))?;
+        workflow.id = None;
+        workflow.user_id = user_id.to_string();
+        workflow.workspace_id = None;
+        plan.workflows.push(with_id(&workflow, oid)?);
+        workflows.insert(oid.to_hex(), workflow);
+    }
+    for mut project in archive.projects {
+        let Some(oid) = new_id(&project.id) else { continue };
+        project.id = None;
+        project.user_id = user_id.to_string();
+        project.workspace_id = None;
+        plan.projects.push(with_id(&project, oid)?);
+    }
+
+    let builtin = Workflow::builtin_default();
+    let mut tasks = Vec::new();
+    for mut task in archive.tasks {
+        let Some(oid) = new_id(&task.id) else { continue };
+        let archived_id = task.id.clone().unwrap_or_default();
+        if task.title.trim().is_empty() {
+            return Err(format!("task {}: Title is required", archived_id));
+        }
+        check_description(task.description.as_deref()).map_err(|err| format!("task {}: {}", archived_id, err.message))?;
+
+        task.id = None;
+        task.user_id = user_id.to_string();
+        task.creator_id = user_id.to_string();
+        task.assignee_ids = vec![user_id.to_string()];
+        task.workspace_id = None;
+        task.version = 1;
+        // Built-in workflows keep their id.
+        task.workflow_id = remap(&task.workflow_id).unwrap_or(task.workflow_id);
+        let workflow = match workflows.get(&task.workflow_id) {
+            Some(workflow) => workflow,
+            None if task.workflow_id == DEFAULT_WORKFLOW_ID => &builtin,
+            None => return Err(format!("task {}: Workflow not found", archived_id)),
+        };
+        if workflow.status(&task.status).is_none() {
+            return Err(format!("task {}: Unknown status: {}", archived_id, task.status));
+        }
+        task.project_id = task.project_id.as_ref().and_then(remap);
+        task.parent_id = task.parent_id.as_ref().and_then(remap);
+        if let Some(description) = &mut task.description {
+            for linked in linked_task_ids(description) {
+                if let Some(new) = remap(&linked) {
+                    *description = description.replace(&linked, &new);
+                }
+            }
+        }
+        tasks.push((oid, task));
+    }
+
+    // Ranks are handed out again per column, keeping the archived order.
+    let mut columns: HashMap<(String, String), Vec<usize>> = HashMap::new();
+    for (index, (_, task)) in tasks.iter().enumerate() {
+        columns.entry((task.workflow_id.clone(), task.status.clone())).or_default().push(index);
+    }
+    for mut column in columns.into_values() {
+        column.sort_by(|&a, &b| (&tasks[a].1.rank, tasks[a].1.created_at).cmp(&(&tasks[b].1.rank, tasks[b].1.created_at)));
+        for (index, rank) in column.iter().zip(rank_sequence(column.len())) {
+            tasks[*index].1.rank = rank;
+        }
+    }
+    for (oid, task) in &tasks {
+        plan.tasks.push(with_id(task, *oid)?);
+    }
+
+    for mut comment in archive.comments {
+        let (Some(oid), Some(task_id)) = (new_id(&comment.id), remap(&comment.task_id)) else { continue };
+        comment.id = None;
+        comment.task_id = task_id;
+        // Any author id in the archive is unverified, so every comment becomes the importer's.
+        comment.author_id = user_id.to_string();
+        comment.parent_id = comment.parent_id.as_ref().and_then(remap);
+        comment.ancestors = comment.ancestors.iter().filter_map(remap).collect();
+        plan.comments.push(with_id(&comment, oid)?);
+        plan.comment_ids.push(oid);
+    }
+    Ok(plan)
+}
+
+/// Restores an archive from `POST /account/exports` into the caller's account, which
+/// must not have any workflows, projects or tasks yet. Every record gets a new id.
+pub async fn import_account(
+    auth: AuthenticatedUser,
+    archive: web::Json<AccountArchive>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let user_id = auth.0;
+    let archive = archive.into_inner();
+    if archive.version == 0 || archive.version > ARCHIVE_VERSION {
+        return HttpResponse::BadRequest().body(format!("Unsupported archive version: {}", archive.version));
+    }
+
+    let database = client.database("rust_backend");
+    for collection in ["workflows", "projects", "tasks"] {
+        match database.collection::<Document>(collection).count_documents(doc! { "user_id": &user_id }).await {
+            Ok(0) => {}
+            Ok(_) => return HttpResponse::Conflict().body("Archives can only be imported into an account without workflows, projects or tasks"),
+            Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+        }
+    }
+
+    let attachments_skipped = archive.attachments.len();
+    let ImportPlan { workflows, projects, tasks, comments, comment_ids } = match plan_import(archive, &user_id) {
+        Ok(p) => p,
+        Err(err) => return HttpResponse::BadRequest().body(format!("Invalid archive: {}", err)),
+    };
+    let report = AccountImportReport {
+        workflows: workflows.len(),
+        projects: projects.len(),
+        tasks: tasks.len(),
+        comments: comments.len(),
+        attachments_skipped,
+    };
+
+    let inserted = async {
+        insert_all(&client, "workflows", workflows).await?;
+        insert_all(&client, "projects", projects).await?;
+        insert_all(&client, "tasks", tasks).await?;
+        insert_all(&client, "comments", comments).await
+    };
+    if let Err(err) = inserted.await {
+        rollback_import(&client, &user_id, comment_ids).await;
+        return HttpResponse::InternalServerError().body(format!("Error importing archive: {}", err));
+    }
+
+    HttpResponse::Ok().json(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mongodb::bson::Bson;
+    use serde_json::json;
+
+    const OWNER: &str = "650000000000000000000001";
+    const IMPORTER: &str = "660000000000000000000001";
+    const PROJECT: &str = "650000000000000000000010";
+    const FIRST: &str = "650000000000000000000020";
+    const SECOND: &str = "650000000000000000000021";
+    const OUTSIDE: &str = "650000000000000000000099";
+    const COMMENT: &str = "650000000000000000000030";
+
+    fn task(id: &str, rank: &str, extra: serde_json::Value) -> serde_json::Value {
+        let mut task = json!({
+            "_id": id,
+            "title": format!("Task {}", id),
+            "description": null,
+            "status": "Pending",
+            "workflow_id": "default",
+            "rank": rank,
+            "user_id": OWNER,
+            "creator_id": OWNER,
+            "assignee_ids": [OWNER, OUTSIDE],
+            "workspace_id": "650000000000000000000077",
+            "due_at": null,
+            "version": 7,
+            "created_at": "2024-01-01 00:00:00 UTC",
+            "updated_at": "2024-01-01 00:00:00 UTC",
+        });
+        task.as_object_mut().unwrap().extend(extra.as_object().unwrap().clone());
+        task
+    }
+
+    fn archive() -> AccountArchive {
+        serde_json::from_value(json!({
+            "version": 1,
+            "exported_at": "2024-01-02 00:00:00 UTC",
+            "profile": { "id": OWNER, "name": "Owner", "email": "owner@example.com" },
+            "labels": [],
+            "workflows": [],
+            "projects": [{
+                "_id": PROJECT,
+                "user_id": OWNER,
+                "workspace_id": "650000000000000000000077",
+                "name": "Home",
+                "description": null,
+                "color": null,
+                "archived": false,
+                "created_at": "2024-01-01 00:00:00 UTC",
+                "updated_at": "2024-01-01 00:00:00 UTC",
+            }],
+            "tasks": [
+                task(FIRST, "m", json!({
+                    "project_id": PROJECT,
+                    "description": format!("Before [second](/tasks/{}) and [gone](/tasks/{})", SECOND, OUTSIDE),
+                })),
+                task(SECOND, "c", json!({ "parent_id": FIRST, "project_id": OUTSIDE })),
+            ],
+            "comments": [
+                {
+                    "_id": COMMENT,
+                    "task_id": FIRST,
+                    "author_id": OUTSIDE,
+                    "parent_id": null,
+                    "ancestors": [],
+                    "body": "Looks good",
+                    "edited": false,
+                    "created_at": "2024-01-01 00:00:00 UTC",
+                    "updated_at": "2024-01-01 00:00:00 UTC",
+                },
+                {
+                    "_id": "650000000000000000000031",
+                    "task_id": OUTSIDE,
+                    "author_id": OWNER,
+                    "parent_id": null,
+                    "ancestors": [],
+                    "body": "On a task that isn't in the archive",
+                    "edited": false,
+                    "created_at": "2024-01-01 00:00:00 UTC",
+                    "updated_at": "2024-01-01 00:00:00 UTC",
+                },
+            ],
+            "attachments": [],
+        }))
+        .unwrap()
+    }
+
+    fn id_of(document: &Document) -> String {
+        document.get_object_id("_id").unwrap().to_hex()
+    }
+
+    #[test]
+    fn gives_every_record_a_new_id_and_remaps_references() {
+        let plan = plan_import(archive(), IMPORTER).unwrap();
+
+        let project = &plan.projects[0];
+        let project_id = id_of(project);
+        assert_ne!(project_id, PROJECT);
+        assert_eq!(project.get_str("user_id").unwrap(), IMPORTER);
+        assert_eq!(project.get("workspace_id"), Some(&Bson::Null));
+
+        let (first, second) = (&plan.tasks[0], &plan.tasks[1]);
+        let (first_id, second_id) = (id_of(first), id_of(second));
+        assert!(first_id != FIRST && second_id != SECOND);
+        for task in [first, second] {
+            assert_eq!(task.get_str("user_id").unwrap(), IMPORTER);
+            assert_eq!(task.get_str("creator_id").unwrap(), IMPORTER);
+            assert_eq!(task.get_array("assignee_ids").unwrap(), &vec![Bson::String(IMPORTER.to_string())]);
+            assert_eq!(task.get("workspace_id"), Some(&Bson::Null));
+            assert_eq!(task.get_i64("version").unwrap(), 1);
+        }
+        assert_eq!(first.get_str("project_id").unwrap(), project_id);
+        assert_eq!(second.get_str("parent_id").unwrap(), first_id);
+        assert_eq!(
+            first.get_str("description").unwrap(),
+            format!("Before [second](/tasks/{}) and [gone](/tasks/{})", second_id, OUTSIDE)
+        );
+
+        assert_eq!(plan.comments.len(), 1);
+        let comment = &plan.comments[0];
+        assert_eq!(comment.get_str("task_id").unwrap(), first_id);
+        assert_eq!(comment.get_str("author_id").unwrap(), IMPORTER);
+        assert_eq!(plan.comment_ids, vec![comment.get_object_id("_id").unwrap()]);
+    }
+
+    #[test]
+    fn drops_references_to_records_outside_the_archive() {
+        let plan = plan_import(archive(), IMPORTER).unwrap();
+        let second = &plan.tasks[1];
+        assert_eq!(second.get("project_id"), Some(&Bson::Null));
+        assert!(plan.comments.iter().all(|c| c.get_str("body").unwrap() != "On a task that isn't in the archive"));
+    }
+
+    #[test]
+    fn ranks_each_column_again_in_archived_order() {
+        let plan = plan_import(archive(), IMPORTER).unwrap();
+        let rank = |index: usize| plan.tasks[index].get_str("rank").unwrap().to_string();
+        // SECOND was ranked before FIRST in the archive, and still is.
+        assert!(rank(1) < rank(0));
+        assert_eq!(rank(0).len(), rank(1).len());
+        assert!(!["m", "c"].contains(&rank(0).as_str()));
+    }
+
+    #[test]
+    fn rejects_unknown_statuses_and_workflows() {
+        let mut archive = archive();
+        archive.tasks[0].status = "Nope".to_string();
+        assert!(plan_import(archive, IMPORTER).unwrap_err().contains("Unknown status"));
+
+        let mut archive = self::archive();
+        archive.tasks[1].workflow_id = OUTSIDE.to_string();
+        assert!(plan_import(archive, IMPORTER).unwrap_err().contains("Workflow not found"));
+    }
+}
@@ -0,0 +1,243 @@
+use std::env;
+use actix_multipart::Multipart;
+use actix_web::{http::header::{ContentDisposition, DispositionParam, DispositionType}, web, HttpResponse, Responder};
+use bytes::BytesMut;
+use mongodb::{bson::{doc, Bson}, Client};
+use mongodb::bson::oid::ObjectId;
+use futures::stream::TryStreamExt;
+use chrono::Utc;
+use crate::controllers::task_controller::find_task;
+use crate::models::attachment_model::{Attachment, AttachmentResponse};
+use crate::storage::{Storage, StorageError};
+use crate::utils::auth::AuthenticatedUser;
+
+lazy_static::lazy_static! {
+    static ref MAX_ATTACHMENT_SIZE: usize = env::var("MAX_ATTACHMENT_SIZE")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(10 * 1024 * 1024);
+    static ref ALLOWED_ATTACHMENT_TYPES: Vec<String> = env::var("ALLOWED_ATTACHMENT_TYPES")
+        .unwrap_or_else(|_| "image/png,image/jpeg,image/gif,application/pdf,text/plain,text/csv,application/zip".to_string())
+        .split(',')
+        .map(|t| t.trim().to_ascii_lowercase())
+        .filter(|t| !t.is_empty())
+        .collect();
+}
+
+pub async fn upload_attachment(
+    auth: AuthenticatedUser,
+    task_id: web::Path<String>,
+    mut payload: Multipart,
+    client: web::Data<Client>,
+    storage: web::Data<dyn Storage>,
+) -> impl Responder {
+    let task_id = task_id.into_inner();
+    match find_task(&client, &auth.0, &task_id).await {
+        Ok(Some(_)) => {}
+        Ok(None) => return HttpResponse::NotFound().body("Task not found"),
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    }
+
+    let mut field = loop {
+        match payload.try_next().await {
+            Ok(Some(field)) if field.name() == Some("file") => break field,
+            Ok(Some(_)) => continue,
+            Ok(None) => return HttpResponse::BadRequest().body("Multipart field \"file\" is required"),
+            Err(err) => return HttpResponse::BadRequest().body(format!("Invalid multipart body: {}", err)),
+        }
+    };
+
+    let filename = field
+        .content_disposition()
+        .and_then(|cd| cd.get_filename())
+        .map(|name| name.to_string())
+        .unwrap_or_else(|| "attachment".to_string());
+
+    let content_type = match field.content_type() {
+        Some(mime) => mime.essence_str().to_ascii_lowercase(),
+        None => return HttpResponse::BadRequest().body("File content type is required"),
+    };
+    if !ALLOWED_ATTACHMENT_TYPES.contains(&content_type) {
+        return HttpResponse::UnsupportedMediaType().body(format!("Content type {} is not allowed", content_type));
+    }
+
+    let mut data = BytesMut::new();
+    loop {
+        match field.try_next().await {
+            Ok(Some(chunk)) => {
+                if data.len() + chunk.len() > *MAX_ATTACHMENT_SIZE {
+                    return HttpResponse::PayloadTooLarge()
+                        .body(format!("Attachments are limited to {} bytes", *MAX_ATTACHMENT_SIZE));
+                }
+                data.extend_from_slice(&chunk);
+            }
+            Ok(None) => break,
+            Err(err) => return HttpResponse::BadRequest().body(format!("Invalid multipart body: {}", err)),
+        }
+    }
+
+    let size = data.len() as i64;
+    let storage_key = format!("{}/{}", task_id, ObjectId::new().to_hex());
+
+    if let Err(err) = storage.put(&storage_key, data.freeze(), &content_type).await {
+        return HttpResponse::InternalServerError().body(format!("Error storing attachment: {}", err));
+    }
+
+    let database = client.database("rust_backend");
+    let collection = database.collection::<Attachment>("attachments");
+
+    let mut attachment = Attachment {
+        id: None,
+        task_id,
+        user_id: auth.0,
+        filename,
+        content_type,
+        size,
+        storage_key,
+        created_at: Utc::now(),
+    };
+
+    match collection.insert_one(&attachment).await {
+        Ok(insert_result) => {
+            if let Some(oid) = insert_result.inserted_id.as_object_id() {
+                attachment.id = Some(oid.to_hex());
+                HttpResponse::Ok().json(AttachmentResponse::from(attachment))
+            } else {
+                HttpResponse::InternalServerError().body("Failed to get inserted ID")
+            }
+        }
+        Err(err) => {
+            let _ = storage.delete(&attachment.storage_key).await;
+            HttpResponse::InternalServerError().body(format!("Error inserting attachment: {}", err))
+        }
+    }
+}
+
+pub async fn get_attachments(
+    auth: AuthenticatedUser,
+    task_id: web::Path<String>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let database = client.database("rust_backend");
+    let collection = database.collection::<Attachment>("attachments");
+
+    let cursor = match collection.find(doc! { "task_id": task_id.as_str(), "user_id": &auth.0 }).await {
+        Ok(c) => c,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    let attachments: Vec<AttachmentResponse> = match cursor.try_collect::<Vec<_>>().await {
+        Ok(docs) => docs.into_iter().map(AttachmentResponse::from).collect(),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error reading attachments: {}", e)),
+    };
+
+    HttpResponse::Ok().json(attachments)
+}
+
+async fn find_attachment(
+    client: &Client,
+    user_id: &str,
+    task_id: &str,
+    attachment_id: &str,
+) -> Result<Option<Attachment>, HttpResponse> {
+    let oid = match ObjectId::parse_str(attachment_id) {
+        Ok(o) => o,
+        Err(_) => return Err(HttpResponse::BadRequest().body("Invalid attachment ID")),
+    };
+
+    client
+        .database("rust_backend")
+        .collection::<Attachment>("attachments")
+        .find_one(doc! { "_id": Bson::ObjectId(oid), "task_id": task_id, "user_id": user_id })
+        .await
+        .map_err(|err| HttpResponse::InternalServerError().body(format!("Database error: {}", err)))
+}
+
+pub async fn download_attachment(
+    auth: AuthenticatedUser,
+    path: web::Path<(String, String)>,
+    client: web::Data<Client>,
+    storage: web::Data<dyn Storage>,
+) -> impl Responder {
+    let (task_id, attachment_id) = path.into_inner();
+
+    let attachment = match find_attachment(&client, &auth.0, &task_id, &attachment_id).await {
+        Ok(Some(a)) => a,
+        Ok(None) => return HttpResponse::NotFound().body("Attachment not found"),
+        Err(response) => return response,
+    };
+
+    match storage.get(&attachment.storage_key).await {
+        Ok(stream) => HttpResponse::Ok()
+            .content_type(attachment.content_type.as_str())
+            .insert_header(ContentDisposition {
+                disposition: DispositionType::Attachment,
+                parameters: vec![DispositionParam::Filename(attachment.filename)],
+            })
+            .insert_header(("X-Content-Type-Options", "nosniff"))
+            .no_chunking(attachment.size as u64)
+            .streaming(stream),
+        Err(StorageError::NotFound) => HttpResponse::NotFound().body("Attachment content not found"),
+        Err(err) => HttpResponse::InternalServerError().body(format!("Error reading attachment: {}", err)),
+    }
+}
+
+pub async fn delete_attachment(
+    auth: AuthenticatedUser,
+    path: web::Path<(String, String)>,
+    client: web::Data<Client>,
+    storage: web::Data<dyn Storage>,
+) -> impl Responder {
+    let (task_id, attachment_id) = path.into_inner();
+
+    let attachment = match find_attachment(&client, &auth.0, &task_id, &attachment_id).await {
+        Ok(Some(a)) => a,
+        Ok(None) => return HttpResponse::NotFound().body("Attachment not found"),
+        Err(response) => return response,
+    };
+
+    if let Err(err) = storage.delete(&attachment.storage_key).await {
+        return HttpResponse::InternalServerError().body(format!("Error deleting attachment content: {}", err));
+    }
+
+    let database = client.database("rust_backend");
+    let collection = database.collection::<Attachment>("attachments");
+
+    let oid = match ObjectId::parse_str(&attachment_id) {
+        Ok(o) => o,
+        Err(_) => return HttpResponse::BadRequest().body("Invalid attachment ID"),
+    };
+
+    match collection.delete_one(doc! { "_id": Bson::ObjectId(oid) }).await {
+        Ok(_) => HttpResponse::Ok().body("Attachment deleted successfully"),
+        Err(err) => HttpResponse::InternalServerError().body(format!("Error deleting attachment: {}", err)),
+    }
+}
+
+/// Removes all attachments of a task, blobs first so no orphaned content is left behind.
+pub async fn delete_task_attachments(
+    client: &Client,
+    storage: &dyn Storage,
+    task_id: &str,
+) -> Result<(), String> {
+    let collection = client.database("rust_backend").collection::<Attachment>("attachments");
+
+    let attachments: Vec<Attachment> = collection
+        .find(doc! { "task_id": task_id })
+        .await
+        .map_err(|e| e.to_string())?
+        .try_collect()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    for attachment in &attachments {
+        storage.delete(&attachment.storage_key).await.map_err(|e| e.to_string())?;
+    }
+
+    collection
+        .delete_many(doc! { "task_id": task_id })
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
@@ -0,0 +1,432 @@
+use std::collections::{HashMap, HashSet};
+use actix_web::{http::StatusCode, web, HttpResponse, Responder};
+use mongodb::{bson::{doc, to_bson, Bson, Document}, Client, ClientSession, Collection};
+use mongodb::bson::oid::ObjectId;
+use futures::stream::TryStreamExt;
+use chrono::Utc;
+use crate::controllers::board_controller::{end_of_column_rank, write_within_wip_limit, ColumnEnds, WipCheck};
+use crate::controllers::custom_field_controller::{check_custom_values, retain_defined_fields};
+use crate::controllers::project_controller::find_project;
+use crate::controllers::task_controller::{
+    apply_task_update, check_assignees, check_transition, track_completion, find_task, log_history, prepare_new_task, prepare_task_update, TaskError,
+};
+use crate::controllers::workflow_controller::find_workflow;
+use crate::models::batch_model::{
+    BatchItemResult, BatchOperation, BatchRequest, BatchResponse, BulkUpdateRequest, BulkUpdateResponse,
+};
+use crate::models::history_model::{diff_fields, FieldChange, HistoryAction};
+use crate::models::task_model::Task;
+use crate::utils::auth::AuthenticatedUser;
+
+pub const MAX_BATCH_OPERATIONS: usize = 500;
+
+/// A validated operation, ready to be written.
+enum PlannedOp {
+    Create(Task),
+    Update { oid: ObjectId, set: Document, before: Box<Task>, after: Box<Task>, wip: Option<WipCheck> },
+    Delete { oid: ObjectId, task: Task },
+}
+
+async fn load_task(client: &Client, user_id: &str, task_id: &str) -> Result<(ObjectId, Task), TaskError> {
+    let oid = ObjectId::parse_str(task_id).map_err(|_| TaskError::bad_request("Invalid task ID"))?;
+    let task = find_task(client, user_id, task_id)
+        .await
+        .map_err(|err| TaskError::internal(format!("Database error: {}", err)))?
+        .ok_or_else(TaskError::not_found)?;
+    Ok((oid, task))
+}
+
+async fn plan(client: &Client, user_id: &str, op: &BatchOperation) -> Result<PlannedOp, TaskError> {
+    match op {
+        BatchOperation::Create { task } => Ok(PlannedOp::Create(prepare_new_task(client, user_id, task).await?)),
+        BatchOperation::Update { id, changes } => {
+            let (oid, before) = load_task(client, user_id, id).await?;
+            let (set, after) = prepare_task_update(client, user_id, &before, changes).await?;
+            let wip = if after.status != before.status { WipCheck::load(client, &after).await? } else { None };
+            Ok(PlannedOp::Update { oid, set, before: Box::new(before), after: Box::new(after), wip })
+        }
+        BatchOperation::Delete { id } => {
+            let (oid, task) = load_task(client, user_id, id).await?;
+            Ok(PlannedOp::Delete { oid, task })
+        }
+    }
+}
+
+/// The write of an update or delete that matched nothing: the task changed or went away
+/// since it was planned.
+fn concurrent_write() -> TaskError {
+    TaskError { status: StatusCode::CONFLICT, message: "Task was modified concurrently".to_string() }
+}
+
+async fn execute(
+    collection: &Collection<Task>,
+    op: &mut PlannedOp,
+    session: Option<&mut ClientSession>,
+) -> Result<(), TaskError> {
+    let database_error = |err: mongodb::error::Error| TaskError::internal(format!("Database error: {}", err));
+    match op {
+        PlannedOp::Create(task) => {
+            let insert = collection.insert_one(&*task);
+            let result = match session {
+                Some(s) => insert.session(s).await,
+                None => insert.await,
+            }
+            .map_err(database_error)?;
+            task.id = result.inserted_id.as_object_id().map(|oid| oid.to_hex());
+        }
+        PlannedOp::Update { oid, set, before, wip, .. } => {
+            let filter =
+                doc! { "_id": Bson::ObjectId(*oid), "user_id": &before.user_id, "deleted_at": Bson::Null, "version": before.version };
+            let Some(session) = session else {
+                let written = write_within_wip_limit(collection, filter, before, set.clone(), wip.as_ref()).await?;
+                return if written { Ok(()) } else { Err(concurrent_write()) };
+            };
+
+            // Inside the transaction a write that overfills a column is rolled back with
+            // the rest, so the column is only counted again.
+            if let Some(wip) = wip
+                && wip.is_full(collection, Some(&mut *session)).await?
+            {
+                return Err(wip.error());
+            }
+            let result = collection
+                .update_one(filter, doc! { "$set": set.clone(), "$inc": { "version": 1 } })
+                .session(&mut *session)
+                .await
+                .map_err(database_error)?;
+            if result.matched_count == 0 {
+                return Err(concurrent_write());
+            }
+            if let Some(wip) = wip
+                && wip.is_full(collection, Some(session)).await?
+            {
+                return Err(wip.error());
+            }
+        }
+        PlannedOp::Delete { oid, task } => {
+            let now = Utc::now();
+            let update = collection.update_one(
+                doc! { "_id": Bson::ObjectId(*oid), "user_id": &task.user_id, "deleted_at": Bson::Null, "version": task.version },
+                doc! { "$set": { "deleted_at": now.to_string() }, "$inc": { "version": 1 } },
+            );
+            let result = match session {
+                Some(s) => update.session(s).await,
+                None => update.await,
+            }
+            .map_err(database_error)?;
+            if result.matched_count == 0 {
+                return Err(concurrent_write());
+            }
+            task.deleted_at = Some(now);
+            task.version += 1;
+        }
+    }
+    Ok(())
+}
+
+async fn log_planned(client: &Client, user_id: &str, op: &PlannedOp) {
+    match op {
+        PlannedOp::Create(task) => {
+            log_history(client, task, user_id, HistoryAction::Created, diff_fields(&[], &task.tracked_fields())).await
+        }
+        PlannedOp::Update { before, after, .. } => {
+            let changes = diff_fields(&before.tracked_fields(), &after.tracked_fields());
+            if !changes.is_empty() {
+                log_history(client, after, user_id, HistoryAction::Updated, changes).await
+            }
+        }
+        PlannedOp::Delete { task, .. } => {
+            let changes = vec![FieldChange {
+                field: "deleted_at".to_string(),
+                from: serde_json::Value::Null,
+                to: serde_json::json!(task.deleted_at),
+            }];
+            log_history(client, task, user_id, HistoryAction::Trashed, changes).await
+        }
+    }
+}
+
+fn planned_id(op: &PlannedOp) -> Option<String> {
+    match op {
+        PlannedOp::Create(task) => task.id.clone(),
+        PlannedOp::Update { oid, .. } | PlannedOp::Delete { oid, .. } => Some(oid.to_hex()),
+    }
+}
+
+fn success(index: usize, op: &PlannedOp) -> BatchItemResult {
+    BatchItemResult { index, status: StatusCode::OK.as_u16(), id: planned_id(op), error: None }
+}
+
+fn failure(index: usize, status: StatusCode, error: String) -> BatchItemResult {
+    BatchItemResult { index, status: status.as_u16(), id: None, error: Some(error) }
+}
+
+/// Runs up to `MAX_BATCH_OPERATIONS` creates, updates and deletes with a result per item.
+/// In atomic mode the writes share one transaction, which needs a replica set.
+pub async fn batch_tasks(
+    auth: AuthenticatedUser,
+    request: web::Json<BatchRequest>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let user_id = auth.0;
+    let request = request.into_inner();
+
+    if request.operations.is_empty() {
+        return HttpResponse::BadRequest().body("At least one operation is required");
+    }
+    if request.operations.len() > MAX_BATCH_OPERATIONS {
+        return HttpResponse::BadRequest()
+            .body(format!("A batch can contain at most {} operations", MAX_BATCH_OPERATIONS));
+    }
+
+    let collection = client.database("rust_backend").collection::<Task>("tasks");
+
+    if !request.atomic {
+        let mut results = Vec::with_capacity(request.operations.len());
+        for (index, op) in request.operations.iter().enumerate() {
+            let mut planned = match plan(&client, &user_id, op).await {
+                Ok(p) => p,
+                Err(err) => {
+                    results.push(failure(index, err.status, err.message));
+                    continue;
+                }
+            };
+            match execute(&collection, &mut planned, None).await {
+                Ok(()) => {
+                    log_planned(&client, &user_id, &planned).await;
+                    results.push(success(index, &planned));
+                }
+                Err(err) => results.push(failure(index, err.status, err.message)),
+            }
+        }
+        return HttpResponse::Ok().json(BatchResponse { committed: true, results });
+    }
+
+    // Atomic mode: validate everything first, then write inside a transaction.
+    let mut outcomes = Vec::with_capacity(request.operations.len());
+    let mut column_ends = ColumnEnds::default();
+    for op in &request.operations {
+        let outcome = match plan(&client, &user_id, op).await {
+            Ok(PlannedOp::Create(mut task)) => column_ends.place(&mut task).map(|()| PlannedOp::Create(task)),
+            Ok(PlannedOp::Update { oid, mut set, before, mut after, wip })
+                if after.status != before.status || after.workflow_id != before.workflow_id =>
+            {
+                column_ends.place(&mut after).map(|()| {
+                    set.insert("rank", &after.rank);
+                    PlannedOp::Update { oid, set, before, after, wip }
+                })
+            }
+            outcome => outcome,
+        };
+        outcomes.push(outcome);
+    }
+
+    if outcomes.iter().any(Result::is_err) {
+        let results = outcomes
+            .into_iter()
+            .enumerate()
+            .map(|(index, outcome)| match outcome {
+                Err(err) => failure(index, err.status, err.message),
+                Ok(_) => failure(index, StatusCode::FAILED_DEPENDENCY, "Not executed because another operation failed".to_string()),
+            })
+            .collect();
+        return HttpResponse::BadRequest().json(BatchResponse { committed: false, results });
+    }
+
+    let mut planned: Vec<PlannedOp> = outcomes.into_iter().flatten().collect();
+
+    let mut session = match client.start_session().await {
+        Ok(s) => s,
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    };
+    if let Err(err) = session.start_transaction().await {
+        return HttpResponse::InternalServerError().body(format!("Error starting transaction: {}", err));
+    }
+
+    for op in planned.iter_mut() {
+        if let Err(err) = execute(&collection, op, Some(&mut session)).await {
+            let _ = session.abort_transaction().await;
+            return HttpResponse::build(err.status).body(format!("Batch rolled back: {}", err.message));
+        }
+    }
+
+    if let Err(err) = session.commit_transaction().await {
+        return HttpResponse::InternalServerError().body(format!("Error committing batch: {}", err));
+    }
+
+    let mut results = Vec::with_capacity(planned.len());
+    for (index, op) in planned.iter().enumerate() {
+        log_planned(&client, &user_id, op).await;
+        results.push(success(index, op));
+    }
+
+    HttpResponse::Ok().json(BatchResponse { committed: true, results })
+}
+
+/// Applies the same changes to every task matching a filter, e.g. completing all
+/// `Pending` tasks with a given label.
+pub async fn bulk_update_tasks(
+    auth: AuthenticatedUser,
+    request: web::Json<BulkUpdateRequest>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let user_id = auth.0;
+    let BulkUpdateRequest { filter, changes } = request.into_inner();
+
+    let mut query = doc! { "user_id": &user_id, "deleted_at": Bson::Null };
+    if let Some(status) = &filter.status {
+        query.insert("status", status);
+    }
+    if let Some(workflow_id) = &filter.workflow_id {
+        query.insert("workflow_id", workflow_id);
+    }
+    if let Some(labels) = &filter.labels {
+        query.insert("labels", doc! { "$all": labels });
+    }
+    if let Some(project_id) = &filter.project_id {
+        query.insert("project_id", project_id);
+    }
+
+    let target_project = match &changes.project_id {
+        Some(Some(project_id)) => match find_project(&client, &user_id, project_id).await {
+            Ok(Some(p)) => Some(p),
+            Ok(None) => return HttpResponse::BadRequest().body("Project not found"),
+            Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+        },
+        _ => None,
+    };
+
+    let collection = client.database("rust_backend").collection::<Task>("tasks");
+
+    // One more than allowed is enough to tell the filter matches too many tasks.
+    let tasks: Vec<Task> = match collection.find(query).limit(MAX_BATCH_OPERATIONS as i64 + 1).await {
+        Ok(cursor) => match cursor.try_collect().await {
+            Ok(docs) => docs,
+            Err(e) => return HttpResponse::InternalServerError().body(format!("Error reading tasks: {}", e)),
+        },
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+    if tasks.len() > MAX_BATCH_OPERATIONS {
+        return HttpResponse::BadRequest()
+            .body(format!("A bulk update can change at most {} tasks; narrow the filter", MAX_BATCH_OPERATIONS));
+    }
+
+    if tasks.is_empty() || changes.is_empty() {
+        return HttpResponse::Ok().json(BulkUpdateResponse { matched: tasks.len() as u64, modified: 0, conflicts: Vec::new() });
+    }
+
+    let mut workflows = HashMap::new();
+    if let Some(status) = &changes.status {
+        for task in &tasks {
+            if !workflows.contains_key(&task.workflow_id) {
+                match find_workflow(&client, &user_id, &task.workflow_id).await {
+                    Ok(Some(w)) => { workflows.insert(task.workflow_id.clone(), w); }
+                    Ok(None) => return HttpResponse::InternalServerError().body("Task workflow not found"),
+                    Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+                }
+            }
+            if let Err(err) = check_transition(&workflows[&task.workflow_id], &task.status, status) {
+                return HttpResponse::BadRequest().body(format!("Task {}: {}", task.id.as_deref().unwrap_or_default(), err.message));
+            }
+        }
+    }
+
+    if let Some(assignee_ids) = &changes.assignee_ids {
+        let mut checked = HashSet::new();
+        for task in &tasks {
+            if checked.insert(task.workspace_id.clone())
+                && let Err(err) = check_assignees(&client, &user_id, task.workspace_id.as_deref(), assignee_ids).await
+            {
+                return HttpResponse::build(err.status)
+                    .body(format!("Task {}: {}", task.id.as_deref().unwrap_or_default(), err.message));
+            }
+        }
+    }
+
+    // Custom field values are checked against each project the tasks end up in.
+    if let Some(values) = &changes.custom_fields {
+        let mut checked = HashSet::new();
+        for task in &tasks {
+            let project_id = match &changes.project_id {
+                Some(project_id) => project_id.clone(),
+                None => task.project_id.clone(),
+            };
+            if !checked.insert(project_id.clone()) {
+                continue;
+            }
+            let project = match (&target_project, project_id) {
+                (Some(target), _) => Some(target.clone()),
+                (None, Some(project_id)) => match find_project(&client, &user_id, &project_id).await {
+                    Ok(p) => p,
+                    Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+                },
+                (None, None) => None,
+            };
+            if let Err(err) = check_custom_values(&client, project.as_ref(), values).await {
+                return HttpResponse::build(err.status)
+                    .body(format!("Task {}: {}", task.id.as_deref().unwrap_or_default(), err.message));
+            }
+        }
+    }
+
+    let now = Utc::now();
+    let mut modified = 0;
+    let mut conflicts = Vec::new();
+    for task in &tasks {
+        let (mut set, mut updated) = match apply_task_update(task, &changes, now) {
+            Ok(v) => v,
+            Err(err) => return err.into(),
+        };
+        let Some(oid) = task.id.as_deref().and_then(|id| ObjectId::parse_str(id).ok()) else { continue };
+
+        if changes.project_id.is_some() && retain_defined_fields(&mut updated, target_project.as_ref()) {
+            match to_bson(&updated.custom_fields) {
+                Ok(values) => set.insert("custom_fields", values),
+                Err(err) => return HttpResponse::InternalServerError().body(format!("Serialization error: {}", err)),
+            };
+        }
+        if let Some(workflow) = workflows.get(&task.workflow_id) {
+            track_completion(workflow, &mut set, &mut updated);
+        }
+        // Tasks are written one by one, so each moved task ranks after the one before it.
+        if updated.status != task.status {
+            updated.rank = match end_of_column_rank(&client, &user_id, &updated.workflow_id, &updated.status).await {
+                Ok(rank) => rank,
+                Err(err) => return err.into(),
+            };
+            set.insert("rank", &updated.rank);
+        }
+
+        // Each task is written only if it is still the version that was checked above.
+        let wip = if updated.status != task.status {
+            match WipCheck::load(&client, &updated).await {
+                Ok(wip) => wip,
+                Err(err) => return err.into(),
+            }
+        } else {
+            None
+        };
+        let filter = doc! { "_id": Bson::ObjectId(oid), "user_id": &task.user_id, "deleted_at": Bson::Null, "version": task.version };
+        match write_within_wip_limit(&collection, filter, task, set, wip.as_ref()).await {
+            Ok(true) => {}
+            // Changed since it was read, or its new column is full.
+            Ok(false) => {
+                conflicts.push(oid.to_hex());
+                continue;
+            }
+            Err(err) if err.status == StatusCode::CONFLICT => {
+                conflicts.push(oid.to_hex());
+                continue;
+            }
+            Err(err) => return err.into(),
+        }
+        modified += 1;
+
+        let field_changes = diff_fields(&task.tracked_fields(), &updated.tracked_fields());
+        if !field_changes.is_empty() {
+            log_history(&client, &updated, &user_id, HistoryAction::Updated, field_changes).await;
+        }
+    }
+
+    HttpResponse::Ok().json(BulkUpdateResponse { matched: tasks.len() as u64, modified, conflicts })
+}
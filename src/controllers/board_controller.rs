@@ -0,0 +1,336 @@
+use std::collections::HashMap;
+use actix_web::{http::{header::IfMatch, StatusCode}, web, HttpResponse, Responder};
+use mongodb::{bson::{doc, to_document, Bson, Document}, Client, ClientSession, Collection};
+use mongodb::bson::oid::ObjectId;
+use futures::stream::TryStreamExt;
+use crate::controllers::project_controller::archived_project_ids;
+use crate::controllers::task_controller::{
+    concurrent_write_failed, find_task, if_match_satisfied, log_history, precondition_failed, prepare_task_update,
+    task_etag, TaskError,
+};
+use crate::controllers::workflow_controller::{default_workflow, find_workflow};
+use crate::models::board_model::{BoardColumn, BoardQuery, BoardResponse, MoveTask};
+use crate::models::history_model::{diff_fields, HistoryAction};
+use crate::models::task_model::{Task, TaskResponse, UpdateTask};
+use crate::utils::auth::AuthenticatedUser;
+use crate::utils::rank::{rank_between, rank_sequence};
+
+/// The rank that puts a task at the end of a board column.
+pub async fn end_of_column_rank(
+    client: &Client,
+    user_id: &str,
+    workflow_id: &str,
+    status: &str,
+) -> Result<String, TaskError> {
+    let collection = client.database("rust_backend").collection::<Task>("tasks");
+    let last = last_rank(
+        &collection,
+        doc! { "user_id": user_id, "workflow_id": workflow_id, "status": status, "deleted_at": Bson::Null },
+    )
+    .await
+    .map_err(|err| TaskError::internal(format!("Database error: {}", err)))?;
+
+    rank_between(last.as_deref(), None).map_err(TaskError::internal)
+}
+
+/// The column ends of new tasks prepared together before any is written. Each got the
+/// rank after the stored end of its column, so the ones after the first in a column are
+/// moved behind the previous one to keep their order.
+#[derive(Debug, Default)]
+pub struct ColumnEnds(HashMap<(String, String), String>);
+
+impl ColumnEnds {
+    pub fn place(&mut self, task: &mut Task) -> Result<(), TaskError> {
+        let column = (task.workflow_id.clone(), task.status.clone());
+        if let Some(last) = self.0.get(&column) {
+            task.rank = rank_between(Some(last), None).map_err(TaskError::internal)?;
+        }
+        self.0.insert(column, task.rank.clone());
+        Ok(())
+    }
+}
+
+/// A write that brings a task into a board column with a WIP limit. The column is
+/// counted before the write and again after it, since a concurrent write may have filled
+/// it in between; a write that overfilled it is undone.
+pub struct WipCheck {
+    /// The other tasks in the column.
+    column: Document,
+    status: String,
+    limit: u32,
+}
+
+impl WipCheck {
+    /// The check for writing `task` into its column, or `None` when the column has no limit.
+    pub async fn load(client: &Client, task: &Task) -> Result<Option<Self>, TaskError> {
+        let workflow = find_workflow(client, &task.user_id, &task.workflow_id)
+            .await
+            .map_err(|err| TaskError::internal(format!("Database error: {}", err)))?;
+        let Some(limit) = workflow.as_ref().and_then(|w| w.status(&task.status)).and_then(|s| s.wip_limit) else {
+            return Ok(None);
+        };
+        let mut column = doc! {
+            "user_id": &task.user_id,
+            "workflow_id": &task.workflow_id,
+            "status": &task.status,
+            "deleted_at": Bson::Null,
+        };
+        if let Some(oid) = task.id.as_deref().and_then(|id| ObjectId::parse_str(id).ok()) {
+            column.insert("_id", doc! { "$ne": oid });
+        }
+        Ok(Some(WipCheck { column, status: task.status.clone(), limit }))
+    }
+
+    /// Whether the other tasks already fill the column.
+    pub async fn is_full(&self, collection: &Collection<Task>, session: Option<&mut ClientSession>) -> Result<bool, TaskError> {
+        let count = collection.count_documents(self.column.clone());
+        let count = match session {
+            Some(s) => count.session(s).await,
+            None => count.await,
+        }
+        .map_err(|err| TaskError::internal(format!("Database error: {}", err)))?;
+        Ok(count >= self.limit as u64)
+    }
+
+    pub fn error(&self) -> TaskError {
+        TaskError {
+            status: StatusCode::CONFLICT,
+            message: format!("Column {} has reached its WIP limit of {}", self.status, self.limit),
+        }
+    }
+}
+
+/// Writes `set` to the task `filter` matches, which must be `before`, keeping to the
+/// column's WIP limit if `wip` is given. Returns whether `filter` matched.
+pub async fn write_within_wip_limit(
+    collection: &Collection<Task>,
+    filter: Document,
+    before: &Task,
+    set: Document,
+    wip: Option<&WipCheck>,
+) -> Result<bool, TaskError> {
+    let database_error = |err: mongodb::error::Error| TaskError::internal(format!("Database error: {}", err));
+    let Some(wip) = wip else {
+        let result = collection.update_one(filter, doc! { "$set": set, "$inc": { "version": 1 } }).await.map_err(database_error)?;
+        return Ok(result.matched_count > 0);
+    };
+    if wip.is_full(collection, None).await? {
+        return Err(wip.error());
+    }
+
+    // The values the write replaces, to put back if the column turns out to be full.
+    let stored = to_document(before).map_err(|err| TaskError::internal(format!("Serialization error: {}", err)))?;
+    let previous: Document = set.keys().map(|key| (key.clone(), stored.get(key).cloned().unwrap_or(Bson::Null))).collect();
+
+    let result = collection.update_one(filter, doc! { "$set": set, "$inc": { "version": 1 } }).await.map_err(database_error)?;
+    if result.matched_count == 0 {
+        return Ok(false);
+    }
+    if wip.is_full(collection, None).await? {
+        let oid = before.id.as_deref().and_then(|id| ObjectId::parse_str(id).ok());
+        let reverted = collection
+            .update_one(doc! { "_id": oid, "version": before.version + 1 }, doc! { "$set": previous, "$inc": { "version": 1 } })
+            .await
+            .map_err(database_error)?;
+        // Otherwise the task changed again in the meantime and that write stands.
+        if reverted.matched_count > 0 {
+            return Err(wip.error());
+        }
+    }
+    Ok(true)
+}
+
+/// Returns the tasks of one workflow grouped into a column per status, each in rank order.
+pub async fn get_board(
+    auth: AuthenticatedUser,
+    query: web::Query<BoardQuery>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let user_id = auth.0;
+
+    let workflow = match &query.workflow_id {
+        Some(workflow_id) => match find_workflow(&client, &user_id, workflow_id).await {
+            Ok(Some(w)) => w,
+            Ok(None) => return HttpResponse::NotFound().body("Workflow not found"),
+            Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+        },
+        None => match default_workflow(&client, &user_id).await {
+            Ok(w) => w,
+            Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+        },
+    };
+    let workflow_id = workflow.id.clone().unwrap_or_default();
+
+    let mut filter = doc! { "user_id": &user_id, "workflow_id": &workflow_id, "deleted_at": Bson::Null };
+    if let Some(project_id) = &query.project_id {
+        filter.insert("project_id", project_id);
+    } else {
+        let archived = match archived_project_ids(&client, &user_id).await {
+            Ok(ids) => ids,
+            Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+        };
+        if !archived.is_empty() {
+            filter.insert("project_id", doc! { "$nin": archived });
+        }
+    }
+
+    let collection = client.database("rust_backend").collection::<Task>("tasks");
+    let cursor = match collection.find(filter).sort(doc! { "rank": 1, "_id": 1 }).await {
+        Ok(c) => c,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+    let tasks: Vec<Task> = match cursor.try_collect().await {
+        Ok(docs) => docs,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error reading tasks: {}", e)),
+    };
+
+    let mut statuses = workflow.statuses.clone();
+    statuses.sort_by_key(|s| s.order);
+
+    let mut columns: Vec<BoardColumn> = statuses
+        .into_iter()
+        .map(|s| BoardColumn { status: s.name, category: s.category, wip_limit: s.wip_limit, tasks: Vec::new() })
+        .collect();
+    for task in tasks {
+        if let Some(column) = columns.iter_mut().find(|c| c.status == task.status) {
+            column.tasks.push(TaskResponse::from(task));
+        }
+    }
+
+    HttpResponse::Ok().json(BoardResponse { workflow_id, columns })
+}
+
+/// Moves a task to a position in a board column, changing its status and rank in a
+/// single write. Moving into a column that has reached its WIP limit is refused.
+pub async fn move_task(
+    auth: AuthenticatedUser,
+    task_id: web::Path<String>,
+    if_match: Option<web::Header<IfMatch>>,
+    request: web::Json<MoveTask>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let user_id = auth.0;
+    let task_id = task_id.into_inner();
+    let request = request.into_inner();
+
+    let oid = match ObjectId::parse_str(&task_id) {
+        Ok(o) => o,
+        Err(_) => return HttpResponse::BadRequest().body("Invalid task ID"),
+    };
+
+    let task = match find_task(&client, &user_id, &task_id).await {
+        Ok(Some(t)) => t,
+        Ok(None) => return HttpResponse::NotFound().body("Task not found"),
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    };
+
+    if !if_match_satisfied(&if_match, &task) {
+        return precondition_failed(task);
+    }
+
+    let status = request.status.unwrap_or_else(|| task.status.clone());
+    let update = UpdateTask { status: Some(status.clone()), ..Default::default() };
+    let (mut set, mut updated) = match prepare_task_update(&client, &user_id, &task, &update).await {
+        Ok(v) => v,
+        Err(err) => return err.into(),
+    };
+
+    let collection = client.database("rust_backend").collection::<Task>("tasks");
+    let column_filter = doc! {
+        "user_id": &user_id,
+        "workflow_id": &task.workflow_id,
+        "status": &status,
+        "deleted_at": Bson::Null,
+        "_id": { "$ne": Bson::ObjectId(oid) },
+    };
+
+    let wip = if status != task.status {
+        match WipCheck::load(&client, &updated).await {
+            Ok(wip) => wip,
+            Err(err) => return err.into(),
+        }
+    } else {
+        None
+    };
+
+    let rank = match rank_in_column(&collection, column_filter, request.position).await {
+        Ok(rank) => rank,
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    };
+    set.insert("rank", &rank);
+    updated.rank = rank;
+
+    let filter = doc! { "_id": Bson::ObjectId(oid), "user_id": &user_id, "deleted_at": Bson::Null, "version": task.version };
+    match write_within_wip_limit(&collection, filter, &task, set, wip.as_ref()).await {
+        Ok(true) => {
+            let changes = diff_fields(&task.tracked_fields(), &updated.tracked_fields());
+            if !changes.is_empty() {
+                log_history(&client, &updated, &user_id, HistoryAction::Updated, changes).await;
+            }
+            HttpResponse::Ok().insert_header(task_etag(&updated)).json(TaskResponse::from(updated))
+        }
+        Ok(false) => concurrent_write_failed(&client, &user_id, &task_id, if_match.is_some()).await,
+        Err(err) => err.into(),
+    }
+}
+
+/// Ranks a task for `position` in a column (the end if `None`). Concurrent moves can
+/// leave two tasks with the same rank and no room between them; the column is then
+/// re-ranked once.
+async fn rank_in_column(
+    collection: &Collection<Task>,
+    column: Document,
+    position: Option<usize>,
+) -> mongodb::error::Result<String> {
+    for _ in 0..2 {
+        let (before, after) = match position {
+            Some(position) => column_neighbours(collection, column.clone(), position).await?,
+            None => (last_rank(collection, column.clone()).await?, None),
+        };
+        if let Ok(rank) = rank_between(before.as_deref(), after.as_deref()) {
+            return Ok(rank);
+        }
+        rebalance_column(collection, column.clone()).await?;
+    }
+    Err(mongodb::error::Error::custom("could not rank task within its column"))
+}
+
+async fn rebalance_column(collection: &Collection<Task>, column: Document) -> mongodb::error::Result<()> {
+    let tasks: Vec<Task> = collection.find(column).sort(doc! { "rank": 1, "_id": 1 }).await?.try_collect().await?;
+
+    for (task, rank) in tasks.iter().zip(rank_sequence(tasks.len())) {
+        if let Some(oid) = task.id.as_deref().and_then(|id| ObjectId::parse_str(id).ok()) {
+            collection.update_one(doc! { "_id": oid }, doc! { "$set": { "rank": rank } }).await?;
+        }
+    }
+    Ok(())
+}
+
+async fn last_rank(collection: &Collection<Task>, column: Document) -> mongodb::error::Result<Option<String>> {
+    let last = collection.find_one(column).sort(doc! { "rank": -1, "_id": -1 }).await?;
+    Ok(last.map(|t| t.rank).filter(|rank| !rank.is_empty()))
+}
+
+/// Ranks of the tasks that will sit directly above and below `position` in a column.
+async fn column_neighbours(
+    collection: &Collection<Task>,
+    column: Document,
+    position: usize,
+) -> mongodb::error::Result<(Option<String>, Option<String>)> {
+    let neighbours: Vec<Task> = collection
+        .find(column.clone())
+        .sort(doc! { "rank": 1, "_id": 1 })
+        .skip(position.saturating_sub(1) as u64)
+        .limit(if position == 0 { 1 } else { 2 })
+        .await?
+        .try_collect()
+        .await?;
+
+    let mut ranks = neighbours.into_iter().map(|t| Some(t.rank).filter(|rank| !rank.is_empty()));
+    Ok(match (position, ranks.next(), ranks.next()) {
+        (0, next, _) => (None, next.flatten()),
+        (_, Some(prev), next) => (prev, next.flatten()),
+        // Past the end of the column.
+        (_, None, _) => (last_rank(collection, column).await?, None),
+    })
+}
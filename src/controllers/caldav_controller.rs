@@ -0,0 +1,539 @@
+use std::collections::HashMap;
+use actix_web::{http::header::{IfMatch, IF_NONE_MATCH, LOCATION}, web, HttpRequest, HttpResponse, Responder};
+use mongodb::{bson::{doc, oid::ObjectId, Bson}, Client};
+use futures::stream::TryStreamExt;
+use sha2::{Digest, Sha256};
+use crate::controllers::calendar_controller::{begin_calendar, ical_status, task_workflows, write_vtodo};
+use crate::controllers::task_controller::{
+    if_match_satisfied, log_history, precondition_failed, prepare_new_task, save_task_update, task_etag, trash_task,
+};
+use crate::controllers::workflow_controller::{default_workflow, find_workflow};
+use crate::models::history_model::{diff_fields, HistoryAction};
+use crate::models::task_model::{NewTask, Priority, Task, UpdateTask};
+use crate::models::workflow_model::Workflow;
+use crate::utils::auth::AuthenticatedUser;
+use crate::utils::dav::{parse_request, DavRequest, Multistatus, PropName, CALDAV, CALENDARSERVER, DAV};
+use crate::utils::ical::{parse_component, Property};
+
+const ROOT: &str = "/caldav/";
+const PRINCIPAL: &str = "/caldav/principal/";
+const HOME: &str = "/caldav/calendars/";
+const COLLECTION: &str = "/caldav/calendars/tasks/";
+
+/// Something a CalDAV client can ask for the properties of. Every user has a single
+/// task calendar holding the tasks they own.
+enum Resource<'a> {
+    Root,
+    Principal,
+    Home,
+    Collection { ctag: String },
+    Task { task: &'a Task, workflow: Option<&'a Workflow> },
+}
+
+impl Resource<'_> {
+    fn href(&self) -> String {
+        match self {
+            Resource::Root => ROOT.to_string(),
+            Resource::Principal => PRINCIPAL.to_string(),
+            Resource::Home => HOME.to_string(),
+            Resource::Collection { .. } => COLLECTION.to_string(),
+            Resource::Task { task, .. } => format!("{}{}", COLLECTION, resource_name(task)),
+        }
+    }
+
+    /// The value of `prop` as XML, or `None` if the resource doesn't have it.
+    fn prop(&self, prop: &PropName) -> Option<String> {
+        let principal_href = format!("<D:href>{}</D:href>", PRINCIPAL);
+        match (prop.namespace.as_str(), prop.name.as_str(), self) {
+            (DAV, "resourcetype", Resource::Principal) => Some("<D:principal/>".to_string()),
+            (DAV, "resourcetype", Resource::Collection { .. }) => Some("<D:collection/><C:calendar/>".to_string()),
+            (DAV, "resourcetype", Resource::Task { .. }) => Some(String::new()),
+            (DAV, "resourcetype", _) => Some("<D:collection/>".to_string()),
+            (DAV, "displayname", Resource::Collection { .. }) => Some("Tasks".to_string()),
+            (DAV, "current-user-principal", _) => Some(principal_href),
+            (DAV, "principal-URL", Resource::Principal) => Some(principal_href),
+            (CALDAV, "calendar-home-set", Resource::Root | Resource::Principal) => {
+                Some(format!("<D:href>{}</D:href>", HOME))
+            }
+            (CALDAV, "supported-calendar-component-set", Resource::Collection { .. }) => {
+                Some("<C:comp name=\"VTODO\"/>".to_string())
+            }
+            (CALENDARSERVER, "getctag", Resource::Collection { ctag }) => Some(ctag.clone()),
+            (DAV, "getetag", Resource::Task { task, .. }) => Some(format!("\"{}\"", task.version)),
+            (DAV, "getcontenttype", Resource::Task { .. }) => Some("text/calendar; charset=utf-8".to_string()),
+            (CALDAV, "calendar-data", Resource::Task { task, workflow }) => {
+                Some(quick_xml::escape::escape(render_task(task, *workflow)).into_owned())
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Properties returned for `allprop`; calendar data is only sent when asked for.
+fn all_props() -> Vec<PropName> {
+    vec![
+        PropName::new(DAV, "resourcetype"),
+        PropName::new(DAV, "displayname"),
+        PropName::new(DAV, "current-user-principal"),
+        PropName::new(DAV, "principal-URL"),
+        PropName::new(CALDAV, "calendar-home-set"),
+        PropName::new(CALDAV, "supported-calendar-component-set"),
+        PropName::new(CALENDARSERVER, "getctag"),
+        PropName::new(DAV, "getetag"),
+        PropName::new(DAV, "getcontenttype"),
+    ]
+}
+
+fn add_response(multistatus: &mut Multistatus, resource: &Resource, request: &DavRequest) {
+    let (found, missing) = if request.props.is_empty() {
+        let found = all_props().into_iter().filter_map(|p| resource.prop(&p).map(|v| (p, v))).collect();
+        (found, Vec::new())
+    } else {
+        let mut found = Vec::new();
+        let mut missing = Vec::new();
+        for prop in &request.props {
+            match resource.prop(prop) {
+                Some(value) => found.push((prop.clone(), value)),
+                None => missing.push(prop.clone()),
+            }
+        }
+        (found, missing)
+    };
+    multistatus.response(&resource.href(), &found, &missing);
+}
+
+fn multistatus_response(multistatus: Multistatus) -> HttpResponse {
+    HttpResponse::MultiStatus()
+        .content_type("application/xml; charset=utf-8")
+        .body(multistatus.finish())
+}
+
+fn parse_body(body: &str) -> Result<DavRequest, HttpResponse> {
+    parse_request(body).map_err(|err| HttpResponse::BadRequest().body(format!("Invalid XML: {}", err)))
+}
+
+/// `Depth: 0` asks about the resource alone; anything else also covers its members.
+fn depth_zero(req: &HttpRequest) -> bool {
+    req.headers().get("Depth").is_some_and(|d| d.as_bytes() == b"0")
+}
+
+pub fn resource_name(task: &Task) -> String {
+    task.caldav_name
+        .clone()
+        .unwrap_or_else(|| format!("{}.ics", task.id.as_deref().unwrap_or_default()))
+}
+
+fn render_task(task: &Task, workflow: Option<&Workflow>) -> String {
+    let mut calendar = begin_calendar();
+    write_vtodo(&mut calendar, task, workflow);
+    calendar.raw("END", "VCALENDAR");
+    calendar.finish()
+}
+
+/// Changes whenever a task is added, changed or removed, so clients know to sync: a
+/// digest of the listed tasks' ids and versions, since every write bumps the version.
+fn ctag(tasks: &[Task]) -> String {
+    let mut digest = Sha256::new();
+    for task in tasks {
+        digest.update(format!("{}:{};", task.id.as_deref().unwrap_or_default(), task.version).as_bytes());
+    }
+    hex::encode(digest.finalize())
+}
+
+async fn owned_tasks(client: &Client, user_id: &str) -> mongodb::error::Result<Vec<Task>> {
+    client
+        .database("rust_backend")
+        .collection::<Task>("tasks")
+        .find(doc! { "user_id": user_id, "deleted_at": Bson::Null })
+        .sort(doc! { "created_at": 1 })
+        .await?
+        .try_collect()
+        .await
+}
+
+/// Looks up a task by its resource name: the name a client created it under, or
+/// `{id}.ics`.
+async fn find_resource(client: &Client, user_id: &str, name: &str) -> mongodb::error::Result<Option<Task>> {
+    let mut names = vec![doc! { "caldav_name": name }];
+    if let Some(id) = name.strip_suffix(".ics")
+        && let Ok(oid) = ObjectId::parse_str(id)
+    {
+        names.push(doc! { "_id": oid, "caldav_name": Bson::Null });
+    }
+
+    client
+        .database("rust_backend")
+        .collection::<Task>("tasks")
+        .find_one(doc! { "user_id": user_id, "deleted_at": Bson::Null, "$or": names })
+        .await
+}
+
+pub async fn propfind_root(body: String) -> impl Responder {
+    let request = match parse_body(&body) {
+        Ok(r) => r,
+        Err(response) => return response,
+    };
+    let mut multistatus = Multistatus::default();
+    add_response(&mut multistatus, &Resource::Root, &request);
+    multistatus_response(multistatus)
+}
+
+pub async fn propfind_principal(body: String) -> impl Responder {
+    let request = match parse_body(&body) {
+        Ok(r) => r,
+        Err(response) => return response,
+    };
+    let mut multistatus = Multistatus::default();
+    add_response(&mut multistatus, &Resource::Principal, &request);
+    multistatus_response(multistatus)
+}
+
+pub async fn propfind_home(
+    auth: AuthenticatedUser,
+    req: HttpRequest,
+    body: String,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let request = match parse_body(&body) {
+        Ok(r) => r,
+        Err(response) => return response,
+    };
+    let mut multistatus = Multistatus::default();
+    add_response(&mut multistatus, &Resource::Home, &request);
+
+    if !depth_zero(&req) {
+        let tasks = match owned_tasks(&client, &auth.0).await {
+            Ok(t) => t,
+            Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+        };
+        add_response(&mut multistatus, &Resource::Collection { ctag: ctag(&tasks) }, &request);
+    }
+    multistatus_response(multistatus)
+}
+
+pub async fn propfind_collection(
+    auth: AuthenticatedUser,
+    req: HttpRequest,
+    body: String,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let request = match parse_body(&body) {
+        Ok(r) => r,
+        Err(response) => return response,
+    };
+    let tasks = match owned_tasks(&client, &auth.0).await {
+        Ok(t) => t,
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    };
+
+    let mut multistatus = Multistatus::default();
+    add_response(&mut multistatus, &Resource::Collection { ctag: ctag(&tasks) }, &request);
+    if !depth_zero(&req) {
+        let workflows = match task_workflows(&client, &tasks).await {
+            Ok(w) => w,
+            Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+        };
+        for task in &tasks {
+            let workflow = workflows.get(&(task.user_id.clone(), task.workflow_id.clone()));
+            add_response(&mut multistatus, &Resource::Task { task, workflow }, &request);
+        }
+    }
+    multistatus_response(multistatus)
+}
+
+/// Answers `calendar-multiget` and `calendar-query` reports. Queries can only narrow
+/// by component: the calendar holds nothing but VTODOs, so a query for another
+/// component matches nothing and any other filter is ignored.
+pub async fn report_collection(
+    auth: AuthenticatedUser,
+    body: String,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let request = match parse_body(&body) {
+        Ok(r) => r,
+        Err(response) => return response,
+    };
+    let tasks = match owned_tasks(&client, &auth.0).await {
+        Ok(t) => t,
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    };
+    let workflows = match task_workflows(&client, &tasks).await {
+        Ok(w) => w,
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    };
+    let resource = |task| Resource::Task { task, workflow: workflows.get(&(task.user_id.clone(), task.workflow_id.clone())) };
+
+    let mut multistatus = Multistatus::default();
+    match request.root.as_str() {
+        "calendar-multiget" => {
+            let by_name: HashMap<String, &Task> = tasks.iter().map(|t| (resource_name(t), t)).collect();
+            for href in &request.hrefs {
+                let name = href.split_once(COLLECTION).map(|(_, name)| name).unwrap_or_default();
+                match by_name.get(name) {
+                    Some(task) => add_response(&mut multistatus, &resource(task), &request),
+                    None => {
+                        multistatus.not_found(href);
+                    }
+                }
+            }
+        }
+        "calendar-query" => {
+            let todos = request.components.iter().all(|c| c.eq_ignore_ascii_case("VCALENDAR"))
+                || request.components.iter().any(|c| c.eq_ignore_ascii_case("VTODO"));
+            if todos {
+                for task in &tasks {
+                    add_response(&mut multistatus, &resource(task), &request);
+                }
+            }
+        }
+        other => return HttpResponse::Forbidden().body(format!("Unsupported report: {}", other)),
+    }
+    multistatus_response(multistatus)
+}
+
+pub async fn propfind_task(
+    auth: AuthenticatedUser,
+    name: web::Path<String>,
+    body: String,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let request = match parse_body(&body) {
+        Ok(r) => r,
+        Err(response) => return response,
+    };
+    let task = match find_resource(&client, &auth.0, &name).await {
+        Ok(Some(t)) => t,
+        Ok(None) => return HttpResponse::NotFound().body("Task not found"),
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    };
+    let workflow = match find_workflow(&client, &auth.0, &task.workflow_id).await {
+        Ok(w) => w,
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    };
+
+    let mut multistatus = Multistatus::default();
+    add_response(&mut multistatus, &Resource::Task { task: &task, workflow: workflow.as_ref() }, &request);
+    multistatus_response(multistatus)
+}
+
+pub async fn get_task_resource(
+    auth: AuthenticatedUser,
+    name: web::Path<String>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let task = match find_resource(&client, &auth.0, &name).await {
+        Ok(Some(t)) => t,
+        Ok(None) => return HttpResponse::NotFound().body("Task not found"),
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    };
+    let workflow = match find_workflow(&client, &auth.0, &task.workflow_id).await {
+        Ok(w) => w,
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    };
+
+    HttpResponse::Ok()
+        .content_type("text/calendar; charset=utf-8")
+        .insert_header(task_etag(&task))
+        .body(render_task(&task, workflow.as_ref()))
+}
+
+/// Maps an iCalendar PRIORITY (1 highest to 9 lowest, 0 undefined) to a task priority.
+fn task_priority(priority: u8) -> Priority {
+    match priority {
+        1..=2 => Priority::Urgent,
+        3..=4 => Priority::High,
+        6..=9 => Priority::Low,
+        _ => Priority::Medium,
+    }
+}
+
+/// The workflow status for an iCalendar STATUS. `current` is kept when it already has
+/// that meaning, so a client marking an in-progress task in progress changes nothing.
+fn workflow_status(workflow: &Workflow, status: &str, current: Option<&str>) -> Option<String> {
+    let wanted = match status {
+        "COMPLETED" | "CANCELLED" => "COMPLETED",
+        "IN-PROCESS" => "IN-PROCESS",
+        _ => "NEEDS-ACTION",
+    };
+    if let Some(current) = current
+        && ical_status(Some(workflow), current) == wanted
+    {
+        return Some(current.to_string());
+    }
+
+    let mut statuses: Vec<_> = workflow.statuses.iter().collect();
+    statuses.sort_by_key(|s| s.order);
+    statuses
+        .into_iter()
+        .find(|s| ical_status(Some(workflow), &s.name) == wanted)
+        .map(|s| s.name.clone())
+}
+
+/// The task fields a VTODO sets.
+struct TodoFields {
+    uid: Option<String>,
+    title: String,
+    description: Option<String>,
+    status: Option<String>,
+    labels: Vec<String>,
+    priority: Option<Priority>,
+    due_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+fn todo_fields(properties: &[Property]) -> Result<TodoFields, String> {
+    let find = |name: &str| properties.iter().find(|p| p.name == name);
+
+    let due_at = match find("DUE") {
+        Some(due) => Some(due.date_time().map_err(|err| format!("Invalid DUE: {}", err))?),
+        None => None,
+    };
+    let priority = match find("PRIORITY") {
+        Some(p) => Some(task_priority(p.value.trim().parse().map_err(|_| format!("Invalid PRIORITY: {}", p.value))?)),
+        None => None,
+    };
+
+    Ok(TodoFields {
+        uid: find("UID").map(Property::text),
+        title: find("SUMMARY").map(Property::text).unwrap_or_default(),
+        description: find("DESCRIPTION").map(Property::text).filter(|d| !d.is_empty()),
+        status: find("STATUS").map(|s| s.value.trim().to_ascii_uppercase()),
+        labels: properties.iter().filter(|p| p.name == "CATEGORIES").flat_map(Property::texts).collect(),
+        priority,
+        due_at,
+    })
+}
+
+/// Creates or replaces a task from a VTODO. The request replaces the whole task, so a
+/// missing DESCRIPTION, DUE or CATEGORIES clears it; a missing STATUS or PRIORITY
+/// leaves it alone.
+pub async fn put_task_resource(
+    auth: AuthenticatedUser,
+    req: HttpRequest,
+    name: web::Path<String>,
+    if_match: Option<web::Header<IfMatch>>,
+    body: String,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let user_id = auth.0;
+    let name = name.into_inner();
+
+    let Some(properties) = parse_component(&body, "VTODO") else {
+        return HttpResponse::Forbidden().body("Only VTODO components are supported");
+    };
+    let fields = match todo_fields(&properties) {
+        Ok(f) => f,
+        Err(message) => return HttpResponse::BadRequest().body(message),
+    };
+    let create_only = req.headers().get(IF_NONE_MATCH).is_some_and(|v| v.as_bytes() == b"*");
+
+    let existing = match find_resource(&client, &user_id, &name).await {
+        Ok(t) => t,
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    };
+
+    if let Some(task) = existing {
+        if create_only || !if_match_satisfied(&if_match, &task) {
+            return precondition_failed(task);
+        }
+        let workflow = match find_workflow(&client, &user_id, &task.workflow_id).await {
+            Ok(w) => w,
+            Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+        };
+        let status = match (&workflow, &fields.status) {
+            (Some(workflow), Some(status)) => workflow_status(workflow, status, Some(&task.status)),
+            _ => None,
+        };
+
+        let update = UpdateTask {
+            title: (fields.title != task.title).then_some(fields.title),
+            description: (fields.description != task.description).then_some(fields.description),
+            status: status.filter(|s| *s != task.status),
+            labels: (fields.labels != task.labels).then_some(fields.labels),
+            priority: fields.priority.filter(|p| *p != task.priority),
+            project_id: None,
+            assignee_ids: None,
+            due_at: (fields.due_at != task.due_at).then_some(fields.due_at),
+            custom_fields: None,
+        };
+
+        return match save_task_update(&client, &user_id, &task, &update, if_match.is_some()).await {
+            Ok(updated) => HttpResponse::NoContent().insert_header(task_etag(&updated)).finish(),
+            Err(response) => response,
+        };
+    }
+
+    if if_match.is_some() {
+        return HttpResponse::PreconditionFailed().body("Task not found");
+    }
+    let status = match (&fields.status, default_workflow(&client, &user_id).await) {
+        (Some(status), Ok(workflow)) => workflow_status(&workflow, status, None),
+        (None, Ok(_)) => None,
+        (_, Err(err)) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    };
+
+    let new_task = NewTask {
+        title: fields.title,
+        description: fields.description,
+        status,
+        workflow_id: None,
+        labels: Some(fields.labels),
+        priority: fields.priority,
+        project_id: None,
+        parent_id: None,
+        workspace_id: None,
+        owner_id: None,
+        assignee_ids: None,
+        due_at: fields.due_at,
+        custom_fields: None,
+    };
+    let mut task = match prepare_new_task(&client, &user_id, &new_task).await {
+        Ok(t) => t,
+        Err(err) => return err.into(),
+    };
+    task.ical_uid = fields.uid;
+    task.caldav_name = Some(name);
+
+    let collection = client.database("rust_backend").collection::<Task>("tasks");
+    match collection.insert_one(&task).await {
+        Ok(insert_result) => {
+            task.id = insert_result.inserted_id.as_object_id().map(|oid| oid.to_hex());
+            log_history(&client, &task, &user_id, HistoryAction::Created, diff_fields(&[], &task.tracked_fields())).await;
+            HttpResponse::Created().insert_header(task_etag(&task)).finish()
+        }
+        Err(err) => HttpResponse::InternalServerError().body(format!("Error inserting task: {}", err)),
+    }
+}
+
+/// Moves the task to the trash, like `DELETE /tasks/{id}`.
+pub async fn delete_task_resource(
+    auth: AuthenticatedUser,
+    name: web::Path<String>,
+    if_match: Option<web::Header<IfMatch>>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let task = match find_resource(&client, &auth.0, &name).await {
+        Ok(Some(t)) => t,
+        Ok(None) => return HttpResponse::NotFound().body("Task not found"),
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    };
+    if !if_match_satisfied(&if_match, &task) {
+        return precondition_failed(task);
+    }
+
+    match trash_task(&client, &auth.0, &task, if_match.is_some()).await {
+        Ok(_) => HttpResponse::NoContent().finish(),
+        Err(response) => response,
+    }
+}
+
+pub async fn dav_options() -> impl Responder {
+    HttpResponse::Ok()
+        .insert_header(("DAV", "1, calendar-access"))
+        .insert_header(("Allow", "OPTIONS, GET, PUT, DELETE, PROPFIND, REPORT"))
+        .finish()
+}
+
+/// Service discovery (RFC 6764): points clients at the CalDAV root.
+pub async fn well_known_caldav() -> impl Responder {
+    HttpResponse::MovedPermanently().insert_header((LOCATION, ROOT)).finish()
+}
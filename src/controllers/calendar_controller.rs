@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+use actix_web::{web, HttpResponse, Responder};
+use mongodb::{bson::{doc, Bson}, Client};
+use futures::stream::TryStreamExt;
+use chrono::{Days, Utc};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+use crate::controllers::workflow_controller::find_workflow;
+use crate::models::calendar_model::{CalendarFeed, CalendarFeedResponse, CalendarQuery};
+use crate::models::task_model::{Priority, Task};
+use crate::models::workflow_model::{StatusCategory, Workflow};
+use crate::utils::auth::AuthenticatedUser;
+use crate::utils::ical::{format_date, format_date_time, Calendar};
+
+fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+/// The feed URL, absolute when `PUBLIC_URL` is set.
+fn feed_url(token: &str) -> String {
+    let base = std::env::var("PUBLIC_URL").unwrap_or_default();
+    format!("{}/calendar/{}.ics", base.trim_end_matches('/'), token)
+}
+
+/// The iCalendar STATUS of a workflow status: `Done` statuses are completed, the
+/// workflow's initial status has not been started, and any other status is in progress.
+pub fn ical_status(workflow: Option<&Workflow>, status: &str) -> &'static str {
+    let Some(workflow) = workflow else { return "NEEDS-ACTION" };
+    match workflow.status(status) {
+        Some(s) if s.category == StatusCategory::Done => "COMPLETED",
+        _ if workflow.initial_status().is_some_and(|s| s.name == status) => "NEEDS-ACTION",
+        Some(_) => "IN-PROCESS",
+        None => "NEEDS-ACTION",
+    }
+}
+
+/// iCalendar priorities run from 1 (highest) to 9 (lowest).
+fn ical_priority(priority: Priority) -> &'static str {
+    match priority {
+        Priority::Urgent => "1",
+        Priority::High => "3",
+        Priority::Medium => "5",
+        Priority::Low => "9",
+    }
+}
+
+/// The UID a calendar client gave the task, or one derived from its id.
+pub fn ical_uid(task: &Task) -> String {
+    task.ical_uid.clone().unwrap_or_else(|| format!("{}@tasks", task.id.as_deref().unwrap_or_default()))
+}
+
+/// Starts a VCALENDAR; the caller adds its components and ends it.
+pub fn begin_calendar() -> Calendar {
+    let mut calendar = Calendar::default();
+    calendar
+        .raw("BEGIN", "VCALENDAR")
+        .raw("VERSION", "2.0")
+        .raw("PRODID", "-//rust_backend//Tasks//EN")
+        .raw("CALSCALE", "GREGORIAN");
+    calendar
+}
+
+pub fn write_vtodo(calendar: &mut Calendar, task: &Task, workflow: Option<&Workflow>) {
+    calendar
+        .raw("BEGIN", "VTODO")
+        .text("UID", &ical_uid(task))
+        .raw("DTSTAMP", &format_date_time(&task.updated_at))
+        .raw("CREATED", &format_date_time(&task.created_at))
+        .raw("LAST-MODIFIED", &format_date_time(&task.updated_at))
+        .text("SUMMARY", &task.title)
+        .raw("STATUS", ical_status(workflow, &task.status))
+        .raw("PRIORITY", ical_priority(task.priority));
+    if let Some(due_at) = &task.due_at {
+        calendar.raw("DUE", &format_date_time(due_at));
+    }
+    if let Some(description) = &task.description {
+        calendar.text("DESCRIPTION", description);
+    }
+    if !task.labels.is_empty() {
+        calendar.list("CATEGORIES", &task.labels);
+    }
+    if let Some(completed_at) = &task.completed_at {
+        calendar.raw("COMPLETED", &format_date_time(completed_at));
+    }
+    calendar.raw("END", "VTODO");
+}
+
+/// The workflows of `tasks`, keyed by owner and workflow id. Workflows belong to the
+/// task's owner, who may be someone else for assigned tasks.
+pub async fn task_workflows(client: &Client, tasks: &[Task]) -> mongodb::error::Result<HashMap<(String, String), Workflow>> {
+    let mut workflows = HashMap::new();
+    for task in tasks {
+        let key = (task.user_id.clone(), task.workflow_id.clone());
+        if workflows.contains_key(&key) {
+            continue;
+        }
+        if let Some(workflow) = find_workflow(client, &task.user_id, &task.workflow_id).await? {
+            workflows.insert(key, workflow);
+        }
+    }
+    Ok(workflows)
+}
+
+fn render_feed(tasks: &[Task], workflows: &HashMap<(String, String), Workflow>, events: bool) -> String {
+    let mut calendar = begin_calendar();
+    calendar.text("X-WR-CALNAME", "Tasks");
+
+    for task in tasks {
+        let (Some(id), Some(due_at)) = (&task.id, &task.due_at) else { continue };
+        write_vtodo(&mut calendar, task, workflows.get(&(task.user_id.clone(), task.workflow_id.clone())));
+
+        if events {
+            let day = due_at.date_naive();
+            calendar
+                .raw("BEGIN", "VEVENT")
+                .text("UID", &format!("{}-due@tasks", id))
+                .raw("DTSTAMP", &format_date_time(&task.updated_at))
+                .raw("DTSTART;VALUE=DATE", &format_date(&day))
+                .raw("DTEND;VALUE=DATE", &format_date(&day.checked_add_days(Days::new(1)).unwrap_or(day)))
+                .text("SUMMARY", &task.title)
+                .raw("TRANSP", "TRANSPARENT")
+                .raw("END", "VEVENT");
+        }
+    }
+
+    calendar.raw("END", "VCALENDAR");
+    calendar.finish()
+}
+
+/// Creates the caller's feed token, replacing any previous one so its URL stops working.
+pub async fn regenerate_feed_token(
+    auth: AuthenticatedUser,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let token = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+    let feed = CalendarFeed { id: None, user_id: auth.0.clone(), token_hash: hash_token(&token), created_at: Utc::now() };
+
+    let collection = client.database("rust_backend").collection::<CalendarFeed>("calendar_feeds");
+
+    match collection.replace_one(doc! { "user_id": &auth.0 }, &feed).upsert(true).await {
+        Ok(_) => HttpResponse::Ok().json(CalendarFeedResponse { url: feed_url(&token), token, created_at: feed.created_at }),
+        Err(err) => HttpResponse::InternalServerError().body(format!("Error saving calendar feed: {}", err)),
+    }
+}
+
+pub async fn delete_feed_token(
+    auth: AuthenticatedUser,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let collection = client.database("rust_backend").collection::<CalendarFeed>("calendar_feeds");
+
+    match collection.delete_one(doc! { "user_id": &auth.0 }).await {
+        Ok(delete_result) => {
+            if delete_result.deleted_count > 0 {
+                HttpResponse::Ok().body("Calendar feed disabled")
+            } else {
+                HttpResponse::NotFound().body("Calendar feed not found")
+            }
+        }
+        Err(err) => HttpResponse::InternalServerError().body(format!("Error deleting calendar feed: {}", err)),
+    }
+}
+
+/// Serves the tasks with a due date assigned to the token's user. The token in the URL
+/// is the only credential, since calendar apps can't send an access token.
+pub async fn get_calendar_feed(
+    token: web::Path<String>,
+    query: web::Query<CalendarQuery>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let database = client.database("rust_backend");
+
+    let feed = match database
+        .collection::<CalendarFeed>("calendar_feeds")
+        .find_one(doc! { "token_hash": hash_token(&token) })
+        .await
+    {
+        Ok(Some(f)) => f,
+        Ok(None) => return HttpResponse::NotFound().body("Calendar feed not found"),
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    };
+
+    let tasks: Vec<Task> = match database
+        .collection::<Task>("tasks")
+        .find(doc! { "assignee_ids": &feed.user_id, "deleted_at": Bson::Null, "due_at": { "$type": "string" } })
+        .sort(doc! { "due_at": 1 })
+        .await
+    {
+        Ok(cursor) => match cursor.try_collect().await {
+            Ok(docs) => docs,
+            Err(e) => return HttpResponse::InternalServerError().body(format!("Error reading tasks: {}", e)),
+        },
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    let workflows = match task_workflows(&client, &tasks).await {
+        Ok(w) => w,
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    };
+
+    HttpResponse::Ok()
+        .content_type("text/calendar; charset=utf-8")
+        .body(render_feed(&tasks, &workflows, query.events.unwrap_or(false)))
+}
@@ -0,0 +1,237 @@
+use actix_web::{web, HttpResponse, Responder};
+use mongodb::{bson::{doc, Bson}, Client};
+use mongodb::bson::oid::ObjectId;
+use futures::stream::TryStreamExt;
+use chrono::Utc;
+use crate::controllers::notification_controller::{notify, task_watchers};
+use crate::controllers::task_controller::find_task;
+use crate::models::comment_model::{Comment, CommentResponse, CommentThread, NewComment, UpdateComment};
+use crate::models::notification_model::NotificationKind;
+use crate::utils::auth::AuthenticatedUser;
+use crate::utils::pagination::{Page, PageQuery};
+
+pub async fn create_comment(
+    auth: AuthenticatedUser,
+    task_id: web::Path<String>,
+    comment: web::Json<NewComment>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    if comment.body.trim().is_empty() {
+        return HttpResponse::BadRequest().body("Comment body is required");
+    }
+
+    let task_id = task_id.into_inner();
+    let task = match find_task(&client, &auth.0, &task_id).await {
+        Ok(Some(t)) => t,
+        Ok(None) => return HttpResponse::NotFound().body("Task not found"),
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    };
+
+    let database = client.database("rust_backend");
+    let collection = database.collection::<Comment>("comments");
+
+    let ancestors = match &comment.parent_id {
+        Some(parent_id) => {
+            let parent_oid = match ObjectId::parse_str(parent_id) {
+                Ok(o) => o,
+                Err(_) => return HttpResponse::BadRequest().body("Invalid parent comment ID"),
+            };
+            match collection.find_one(doc! { "_id": Bson::ObjectId(parent_oid), "task_id": &task_id }).await {
+                Ok(Some(parent)) => {
+                    let mut ancestors = parent.ancestors;
+                    ancestors.push(parent_id.clone());
+                    ancestors
+                }
+                Ok(None) => return HttpResponse::BadRequest().body("Parent comment not found"),
+                Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+            }
+        }
+        None => Vec::new(),
+    };
+
+    let now = Utc::now();
+    let mut new_comment = Comment {
+        id: None,
+        task_id,
+        author_id: auth.0,
+        parent_id: comment.parent_id.clone(),
+        ancestors,
+        body: comment.body.clone(),
+        edited: false,
+        created_at: now,
+        updated_at: now,
+    };
+
+    match collection.insert_one(&new_comment).await {
+        Ok(insert_result) => {
+            if let Some(oid) = insert_result.inserted_id.as_object_id() {
+                new_comment.id = Some(oid.to_hex());
+                let message = format!("New comment on \"{}\"", task.title);
+                notify(&client, &task_watchers(&task), NotificationKind::Comment, &task, Some(&new_comment.author_id), message)
+                    .await;
+                HttpResponse::Ok().json(CommentResponse::from(new_comment))
+            } else {
+                HttpResponse::InternalServerError().body("Failed to get inserted ID")
+            }
+        }
+        Err(err) => HttpResponse::InternalServerError().body(format!("Error inserting comment: {}", err)),
+    }
+}
+
+/// Lists top-level comments page by page, each with its full reply thread.
+pub async fn get_comments(
+    auth: AuthenticatedUser,
+    task_id: web::Path<String>,
+    query: web::Query<PageQuery>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let task_id = task_id.into_inner();
+    match find_task(&client, &auth.0, &task_id).await {
+        Ok(Some(_)) => {}
+        Ok(None) => return HttpResponse::NotFound().body("Task not found"),
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    }
+
+    let database = client.database("rust_backend");
+    let collection = database.collection::<Comment>("comments");
+
+    let root_filter = doc! { "task_id": &task_id, "parent_id": Bson::Null };
+
+    let total = match collection.count_documents(root_filter.clone()).await {
+        Ok(n) => n,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    let cursor = match collection
+        .find(root_filter)
+        .sort(doc! { "created_at": 1, "_id": 1 })
+        .skip(query.skip())
+        .limit(query.limit() as i64)
+        .await
+    {
+        Ok(c) => c,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    let roots: Vec<Comment> = match cursor.try_collect().await {
+        Ok(docs) => docs,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error reading comments: {}", e)),
+    };
+
+    let root_ids: Vec<String> = roots.iter().filter_map(|c| c.id.clone()).collect();
+
+    let cursor = match collection
+        .find(doc! { "task_id": &task_id, "ancestors.0": { "$in": &root_ids } })
+        .sort(doc! { "created_at": 1, "_id": 1 })
+        .await
+    {
+        Ok(c) => c,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    let mut replies: Vec<Comment> = match cursor.try_collect().await {
+        Ok(docs) => docs,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error reading comments: {}", e)),
+    };
+
+    let items = roots
+        .into_iter()
+        .map(|root| {
+            let (thread, rest): (Vec<_>, Vec<_>) = replies
+                .drain(..)
+                .partition(|reply| reply.ancestors.first() == root.id.as_ref());
+            replies = rest;
+            CommentThread {
+                comment: CommentResponse::from(root),
+                replies: thread.into_iter().map(CommentResponse::from).collect(),
+            }
+        })
+        .collect();
+
+    HttpResponse::Ok().json(Page {
+        items,
+        page: query.page(),
+        limit: query.limit(),
+        total,
+    })
+}
+
+pub async fn update_comment(
+    auth: AuthenticatedUser,
+    path: web::Path<(String, String)>,
+    update: web::Json<UpdateComment>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let (task_id, comment_id) = path.into_inner();
+
+    if update.body.trim().is_empty() {
+        return HttpResponse::BadRequest().body("Comment body is required");
+    }
+
+    let oid = match ObjectId::parse_str(&comment_id) {
+        Ok(o) => o,
+        Err(_) => return HttpResponse::BadRequest().body("Invalid comment ID"),
+    };
+
+    match find_task(&client, &auth.0, &task_id).await {
+        Ok(Some(_)) => {}
+        Ok(None) => return HttpResponse::NotFound().body("Task not found"),
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    }
+
+    let database = client.database("rust_backend");
+    let collection = database.collection::<Comment>("comments");
+
+    let filter = doc! { "_id": Bson::ObjectId(oid), "task_id": &task_id, "author_id": &auth.0 };
+    let update_doc = doc! { "$set": {
+        "body": &update.body,
+        "edited": true,
+        "updated_at": Utc::now().to_string(),
+    } };
+
+    match collection.find_one_and_update(filter, update_doc)
+        .return_document(mongodb::options::ReturnDocument::After)
+        .await
+    {
+        Ok(Some(comment)) => HttpResponse::Ok().json(CommentResponse::from(comment)),
+        Ok(None) => HttpResponse::NotFound().body("Comment not found"),
+        Err(err) => HttpResponse::InternalServerError().body(format!("Error updating comment: {}", err)),
+    }
+}
+
+/// Deletes a comment and every reply below it.
+pub async fn delete_comment(
+    auth: AuthenticatedUser,
+    path: web::Path<(String, String)>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let (task_id, comment_id) = path.into_inner();
+
+    let oid = match ObjectId::parse_str(&comment_id) {
+        Ok(o) => o,
+        Err(_) => return HttpResponse::BadRequest().body("Invalid comment ID"),
+    };
+
+    match find_task(&client, &auth.0, &task_id).await {
+        Ok(Some(_)) => {}
+        Ok(None) => return HttpResponse::NotFound().body("Task not found"),
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    }
+
+    let database = client.database("rust_backend");
+    let collection = database.collection::<Comment>("comments");
+
+    match collection.delete_one(doc! { "_id": Bson::ObjectId(oid), "task_id": &task_id, "author_id": &auth.0 }).await {
+        Ok(delete_result) => {
+            if delete_result.deleted_count == 0 {
+                return HttpResponse::NotFound().body("Comment not found");
+            }
+        }
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Error deleting comment: {}", err)),
+    }
+
+    match collection.delete_many(doc! { "task_id": &task_id, "ancestors": &comment_id }).await {
+        Ok(_) => HttpResponse::Ok().body("Comment deleted successfully"),
+        Err(err) => HttpResponse::InternalServerError().body(format!("Error deleting replies: {}", err)),
+    }
+}
@@ -0,0 +1,418 @@
+use std::collections::HashSet;
+use actix_web::{http::header::{ContentDisposition, DispositionParam, DispositionType}, web, HttpResponse, Responder};
+use bytes::Bytes;
+use mongodb::{bson::{doc, Bson}, Client};
+use futures::{future, stream, StreamExt, TryStreamExt};
+use chrono::{DateTime, SecondsFormat, Utc};
+use crate::controllers::board_controller::ColumnEnds;
+use crate::controllers::task_controller::{log_history, prepare_new_task, scope_filter};
+use crate::models::csv_model::{CsvColumn, ExportQuery, ImportReport, ImportRequest, ImportRowError};
+use crate::models::history_model::{diff_fields, HistoryAction};
+use crate::models::task_model::{NewTask, Priority, Task};
+use crate::utils::auth::AuthenticatedUser;
+
+pub const MAX_IMPORT_ROWS: usize = 5000;
+
+fn format_date(date: &DateTime<Utc>) -> String {
+    date.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+}
+
+/// Characters that make spreadsheets read a cell as a formula.
+const FORMULA_PREFIXES: [char; 6] = ['=', '+', '-', '@', '\t', '\r'];
+
+/// Prefixes text a spreadsheet would run as a formula with `'`, which shows it as text.
+fn escape_formula(value: String) -> String {
+    if value.starts_with(FORMULA_PREFIXES) { format!("'{}", value) } else { value }
+}
+
+/// Undoes `escape_formula`, so exported files import as they were.
+fn unescape_formula(value: &str) -> &str {
+    match value.strip_prefix('\'') {
+        Some(rest) if rest.starts_with(FORMULA_PREFIXES) => rest,
+        _ => value,
+    }
+}
+
+fn cell(task: &Task, column: CsvColumn) -> String {
+    escape_formula(raw_cell(task, column))
+}
+
+fn raw_cell(task: &Task, column: CsvColumn) -> String {
+    match column {
+        CsvColumn::Id => task.id.clone().unwrap_or_default(),
+        CsvColumn::Title => task.title.clone(),
+        CsvColumn::Description => task.description.clone().unwrap_or_default(),
+        CsvColumn::Status => task.status.clone(),
+        CsvColumn::WorkflowId => task.workflow_id.clone(),
+        CsvColumn::Labels => task.labels.join(","),
+        CsvColumn::Priority => serde_json::json!(task.priority).as_str().unwrap_or_default().to_string(),
+        CsvColumn::ProjectId => task.project_id.clone().unwrap_or_default(),
+        CsvColumn::ParentId => task.parent_id.clone().unwrap_or_default(),
+        CsvColumn::WorkspaceId => task.workspace_id.clone().unwrap_or_default(),
+        CsvColumn::AssigneeIds => task.assignee_ids.join(","),
+        CsvColumn::DueAt => task.due_at.as_ref().map(format_date).unwrap_or_default(),
+        CsvColumn::CreatedAt => format_date(&task.created_at),
+        CsvColumn::UpdatedAt => format_date(&task.updated_at),
+        CsvColumn::CompletedAt => task.completed_at.as_ref().map(format_date).unwrap_or_default(),
+    }
+}
+
+fn csv_line<I: IntoIterator<Item = String>>(values: I) -> Result<Bytes, csv::Error> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(values)?;
+    writer.into_inner().map(Bytes::from).map_err(|err| err.into_error().into())
+}
+
+/// Streams the caller's tasks as CSV, oldest first.
+pub async fn export_tasks(
+    auth: AuthenticatedUser,
+    query: web::Query<ExportQuery>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let columns: Vec<CsvColumn> = match &query.columns {
+        Some(names) => {
+            let mut columns = Vec::new();
+            for name in names.split(',').filter(|n| !n.trim().is_empty()) {
+                match CsvColumn::from_name(name) {
+                    Some(column) => columns.push(column),
+                    None => return HttpResponse::BadRequest().body(format!("Unknown column: {}", name.trim())),
+                }
+            }
+            columns
+        }
+        None => CsvColumn::ALL.to_vec(),
+    };
+    if columns.is_empty() {
+        return HttpResponse::BadRequest().body("At least one column is required");
+    }
+
+    let mut filter = scope_filter(query.scope, &auth.0);
+    filter.insert("deleted_at", Bson::Null);
+    if let Some(project_id) = &query.project_id {
+        filter.insert("project_id", project_id);
+    }
+    if let Some(status) = &query.status {
+        filter.insert("status", status);
+    }
+    if let Some(label) = &query.label {
+        filter.insert("labels", label);
+    }
+
+    let header = match csv_line(columns.iter().map(|c| c.name().to_string())) {
+        Ok(h) => h,
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Error writing CSV: {}", err)),
+    };
+
+    let cursor = match client
+        .database("rust_backend")
+        .collection::<Task>("tasks")
+        .find(filter)
+        .sort(doc! { "created_at": 1, "_id": 1 })
+        .await
+    {
+        Ok(c) => c,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    let rows = cursor.map(move |task| {
+        let task = task.map_err(actix_web::error::ErrorInternalServerError)?;
+        csv_line(columns.iter().map(|c| cell(&task, *c))).map_err(actix_web::error::ErrorInternalServerError)
+    });
+
+    HttpResponse::Ok()
+        .content_type("text/csv; charset=utf-8")
+        .insert_header(ContentDisposition {
+            disposition: DispositionType::Attachment,
+            parameters: vec![DispositionParam::Filename("tasks.csv".to_string())],
+        })
+        .streaming(stream::once(future::ok::<_, actix_web::Error>(header)).chain(rows))
+}
+
+fn optional(value: &str) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+fn list(value: &str) -> Vec<String> {
+    value.split(',').map(str::trim).filter(|v| !v.is_empty()).map(str::to_string).collect()
+}
+
+/// Builds the task a CSV row describes; read-only columns are ignored.
+fn row_to_task(columns: &[Option<CsvColumn>], record: &csv::StringRecord) -> Result<NewTask, String> {
+    let mut task = NewTask {
+        title: String::new(),
+        description: None,
+        status: None,
+        workflow_id: None,
+        labels: None,
+        priority: None,
+        project_id: None,
+        parent_id: None,
+        workspace_id: None,
+        owner_id: None,
+        assignee_ids: None,
+        due_at: None,
+        custom_fields: None,
+    };
+
+    for (column, value) in columns.iter().zip(record.iter()) {
+        let Some(column) = column else { continue };
+        let value = unescape_formula(value);
+        match column {
+            CsvColumn::Title => task.title = value.trim().to_string(),
+            CsvColumn::Description => task.description = (!value.is_empty()).then(|| value.to_string()),
+            CsvColumn::Status => task.status = optional(value),
+            CsvColumn::WorkflowId => task.workflow_id = optional(value),
+            CsvColumn::Labels => task.labels = Some(list(value)),
+            CsvColumn::Priority => {
+                task.priority = match optional(value) {
+                    Some(priority) => Some(
+                        serde_json::from_value::<Priority>(serde_json::Value::String(priority.clone()))
+                            .map_err(|_| format!("Unknown priority: {}", priority))?,
+                    ),
+                    None => None,
+                }
+            }
+            CsvColumn::ProjectId => task.project_id = optional(value),
+            CsvColumn::ParentId => task.parent_id = optional(value),
+            CsvColumn::WorkspaceId => task.workspace_id = optional(value),
+            CsvColumn::AssigneeIds => task.assignee_ids = optional(value).map(|v| list(&v)),
+            CsvColumn::DueAt => {
+                task.due_at = match optional(value) {
+                    Some(due_at) => Some(
+                        DateTime::parse_from_rfc3339(&due_at)
+                            .map_err(|_| format!("due_at is not an RFC 3339 date: {}", due_at))?
+                            .with_timezone(&Utc),
+                    ),
+                    None => None,
+                }
+            }
+            CsvColumn::Id | CsvColumn::CreatedAt | CsvColumn::UpdatedAt | CsvColumn::CompletedAt => {}
+        }
+    }
+    Ok(task)
+}
+
+/// What makes two tasks duplicates of each other.
+fn duplicate_key(title: &str, project_id: &Option<String>, due_at: &Option<DateTime<Utc>>) -> (String, Option<String>, Option<i64>) {
+    (title.trim().to_lowercase(), project_id.clone(), due_at.map(|d| d.timestamp_millis()))
+}
+
+/// Imports tasks from CSV. Every row is validated like a new task; rows duplicating an
+/// existing task or an earlier row are skipped, and any invalid row fails the import.
+pub async fn import_tasks(
+    auth: AuthenticatedUser,
+    request: web::Json<ImportRequest>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let user_id = auth.0;
+    let request = request.into_inner();
+
+    let mut reader = csv::ReaderBuilder::new().flexible(true).from_reader(request.csv.as_bytes());
+    let headers = match reader.headers() {
+        Ok(h) => h.clone(),
+        Err(err) => return HttpResponse::BadRequest().body(format!("Invalid CSV header: {}", err)),
+    };
+    let columns: Vec<Option<CsvColumn>> = headers
+        .iter()
+        .map(|h| request.mapping.get(h).copied().or_else(|| CsvColumn::from_name(h)))
+        .map(|c| c.filter(|c| !c.is_read_only()))
+        .collect();
+    if !columns.contains(&Some(CsvColumn::Title)) {
+        return HttpResponse::BadRequest().body("No column is mapped to title");
+    }
+
+    let collection = client.database("rust_backend").collection::<Task>("tasks");
+    let existing: Vec<Task> = match collection.find(doc! { "user_id": &user_id, "deleted_at": Bson::Null }).await {
+        Ok(cursor) => match cursor.try_collect().await {
+            Ok(docs) => docs,
+            Err(e) => return HttpResponse::InternalServerError().body(format!("Error reading tasks: {}", e)),
+        },
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+    let mut seen: HashSet<_> = existing.iter().map(|t| duplicate_key(&t.title, &t.project_id, &t.due_at)).collect();
+
+    let mut report = ImportReport {
+        dry_run: request.dry_run,
+        imported: 0,
+        duplicates: Vec::new(),
+        errors: Vec::new(),
+        task_ids: Vec::new(),
+    };
+    let mut tasks = Vec::new();
+    let mut column_ends = ColumnEnds::default();
+    for (index, record) in reader.records().enumerate() {
+        if index >= MAX_IMPORT_ROWS {
+            return HttpResponse::BadRequest().body(format!("At most {} rows can be imported at once", MAX_IMPORT_ROWS));
+        }
+        let row = index as u64 + 2;
+        let record = match record {
+            Ok(r) => r,
+            Err(err) => {
+                report.errors.push(ImportRowError { row, message: format!("Invalid CSV: {}", err) });
+                continue;
+            }
+        };
+        let row = record.position().map(|p| p.line()).unwrap_or(row);
+
+        let new_task = match row_to_task(&columns, &record) {
+            Ok(t) => t,
+            Err(message) => {
+                report.errors.push(ImportRowError { row, message });
+                continue;
+            }
+        };
+        if !seen.insert(duplicate_key(&new_task.title, &new_task.project_id, &new_task.due_at)) {
+            report.duplicates.push(row);
+            continue;
+        }
+
+        let prepared = match prepare_new_task(&client, &user_id, &new_task).await {
+            Ok(mut task) => column_ends.place(&mut task).map(|()| task),
+            Err(err) => Err(err),
+        };
+        match prepared {
+            Ok(task) => tasks.push(task),
+            Err(err) => report.errors.push(ImportRowError { row, message: err.message }),
+        }
+    }
+
+    if request.dry_run {
+        report.imported = tasks.len();
+        return HttpResponse::Ok().json(report);
+    }
+    if !report.errors.is_empty() {
+        return HttpResponse::UnprocessableEntity().json(report);
+    }
+    if tasks.is_empty() {
+        return HttpResponse::Ok().json(report);
+    }
+    report.imported = tasks.len();
+
+    let result = match collection.insert_many(&tasks).await {
+        Ok(r) => r,
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Error inserting tasks: {}", err)),
+    };
+    for (index, task) in tasks.iter_mut().enumerate() {
+        task.id = result.inserted_ids.get(&index).and_then(|id| id.as_object_id()).map(|oid| oid.to_hex());
+        log_history(&client, task, &user_id, HistoryAction::Created, diff_fields(&[], &task.tracked_fields())).await;
+    }
+    report.task_ids = tasks.into_iter().filter_map(|t| t.id).collect();
+
+    HttpResponse::Ok().json(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn task() -> Task {
+        serde_json::from_value(serde_json::json!({
+            "_id": "650000000000000000000020",
+            "title": "Write report",
+            "description": "First line\nsecond, with a comma",
+            "status": "InProgress",
+            "workflow_id": "default",
+            "labels": ["work", "q4"],
+            "priority": "High",
+            "project_id": "650000000000000000000010",
+            "parent_id": "650000000000000000000021",
+            "user_id": "650000000000000000000001",
+            "assignee_ids": ["650000000000000000000001", "650000000000000000000002"],
+            "workspace_id": "650000000000000000000077",
+            "due_at": "2026-10-31 17:00:00.250 UTC",
+            "created_at": "2026-10-01 08:00:00 UTC",
+            "updated_at": "2026-10-02 09:30:00 UTC",
+            "completed_at": null,
+            "deleted_at": null,
+        }))
+        .unwrap()
+    }
+
+    /// Writes `task` as a CSV file with `columns` and reads its one row back.
+    fn round_trip(task: &Task, columns: &[CsvColumn]) -> Result<NewTask, String> {
+        let mut file = csv_line(columns.iter().map(|c| c.name().to_string())).unwrap().to_vec();
+        file.extend_from_slice(&csv_line(columns.iter().map(|c| cell(task, *c))).unwrap());
+
+        let mut reader = csv::Reader::from_reader(file.as_slice());
+        let headers: Vec<Option<CsvColumn>> = reader.headers().unwrap().iter().map(CsvColumn::from_name).collect();
+        let record = reader.records().next().unwrap().unwrap();
+        row_to_task(&headers, &record)
+    }
+
+    #[test]
+    fn writable_columns_round_trip() {
+        let task = task();
+        let columns: Vec<CsvColumn> = CsvColumn::ALL.into_iter().filter(|c| !c.is_read_only()).collect();
+        let imported = round_trip(&task, &columns).unwrap();
+
+        assert_eq!(imported.title, task.title);
+        assert_eq!(imported.description, task.description);
+        assert_eq!(imported.status.as_ref(), Some(&task.status));
+        assert_eq!(imported.workflow_id.as_ref(), Some(&task.workflow_id));
+        assert_eq!(imported.labels.as_ref(), Some(&task.labels));
+        assert_eq!(imported.priority, Some(Priority::High));
+        assert_eq!(imported.project_id, task.project_id);
+        assert_eq!(imported.parent_id, task.parent_id);
+        assert_eq!(imported.workspace_id, task.workspace_id);
+        assert_eq!(imported.assignee_ids.as_ref(), Some(&task.assignee_ids));
+        assert_eq!(imported.due_at, task.due_at);
+    }
+
+    #[test]
+    fn dates_are_rfc_3339() {
+        let task = task();
+        assert_eq!(cell(&task, CsvColumn::DueAt), "2026-10-31T17:00:00.250Z");
+        assert_eq!(cell(&task, CsvColumn::CreatedAt), "2026-10-01T08:00:00Z");
+        assert_eq!(cell(&task, CsvColumn::CompletedAt), "");
+
+        let columns = [Some(CsvColumn::Title), Some(CsvColumn::DueAt)];
+        let record = csv::StringRecord::from(vec!["Call", "2026-10-31T19:00:00+02:00"]);
+        let imported = row_to_task(&columns, &record).unwrap();
+        assert_eq!(imported.due_at, Some(Utc.with_ymd_and_hms(2026, 10, 31, 17, 0, 0).unwrap()));
+    }
+
+    #[test]
+    fn read_only_and_unknown_columns_are_ignored() {
+        let columns = [Some(CsvColumn::Title), Some(CsvColumn::CreatedAt), None];
+        let record = csv::StringRecord::from(vec!["Call", "not a date", "whatever"]);
+        assert_eq!(row_to_task(&columns, &record).unwrap().title, "Call");
+    }
+
+    #[test]
+    fn reports_bad_priorities_and_dates() {
+        let columns = [Some(CsvColumn::Title), Some(CsvColumn::Priority), Some(CsvColumn::DueAt)];
+        let bad_priority = csv::StringRecord::from(vec!["Call", "Critical", ""]);
+        assert_eq!(row_to_task(&columns, &bad_priority).unwrap_err(), "Unknown priority: Critical");
+
+        let bad_date = csv::StringRecord::from(vec!["Call", "", "31/10/2026"]);
+        assert_eq!(row_to_task(&columns, &bad_date).unwrap_err(), "due_at is not an RFC 3339 date: 31/10/2026");
+    }
+
+    #[test]
+    fn duplicates_ignore_case_and_surrounding_space() {
+        let due = Some(Utc.with_ymd_and_hms(2026, 10, 31, 17, 0, 0).unwrap());
+        let project = Some("p".to_string());
+        assert_eq!(duplicate_key("Write report", &project, &due), duplicate_key("  write REPORT ", &project, &due));
+        assert_ne!(duplicate_key("Write report", &project, &due), duplicate_key("Write report", &None, &due));
+        assert_ne!(duplicate_key("Write report", &project, &due), duplicate_key("Write report", &project, &None));
+    }
+
+    #[test]
+    fn escapes_formulas_and_imports_them_back() {
+        let mut task = task();
+        for title in ["=HYPERLINK(\"http://evil\")", "+1", "-2", "@SUM(A1)", "'quoted"] {
+            task.title = title.to_string();
+            let exported = cell(&task, CsvColumn::Title);
+            if title.starts_with('\'') {
+                assert_eq!(exported, title);
+            } else {
+                assert_eq!(exported, format!("'{}", title));
+            }
+            assert_eq!(round_trip(&task, &[CsvColumn::Title]).unwrap().title, title);
+        }
+        task.labels = vec!["-urgent".to_string(), "later".to_string()];
+        assert_eq!(round_trip(&task, &[CsvColumn::Title, CsvColumn::Labels]).unwrap().labels, Some(task.labels.clone()));
+    }
+}
@@ -0,0 +1,452 @@
+use std::collections::BTreeMap;
+use actix_web::{web, HttpResponse, Responder};
+use mongodb::{bson::{doc, to_bson, Bson}, Client};
+use mongodb::bson::oid::ObjectId;
+use futures::stream::TryStreamExt;
+use chrono::{NaiveDate, Utc};
+use crate::controllers::project_controller::find_project;
+use crate::controllers::task_controller::{log_history, normalize_list, TaskError};
+use crate::models::custom_field_model::{CustomField, FieldType, FieldValue, NewCustomField, UpdateCustomField};
+use crate::models::history_model::{diff_fields, HistoryAction};
+use crate::models::project_model::Project;
+use crate::models::task_model::Task;
+use crate::models::user_model::User;
+use crate::utils::auth::AuthenticatedUser;
+
+fn is_date(value: &str) -> bool {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").is_ok()
+}
+
+fn check_definition(project: &Project, field: &CustomField) -> Result<(), HttpResponse> {
+    if field.name.is_empty() {
+        return Err(HttpResponse::BadRequest().body("Field name is required"));
+    }
+    if project.custom_fields.iter().any(|f| f.id != field.id && f.name.eq_ignore_ascii_case(&field.name)) {
+        return Err(HttpResponse::Conflict().body(format!("The project already has a field named {}", field.name)));
+    }
+    if field.field_type.has_options() && field.options.is_empty() {
+        return Err(HttpResponse::BadRequest().body("Select fields need at least one option"));
+    }
+    Ok(())
+}
+
+/// Whether `value` is valid for `field`, leaving aside whether a referenced user exists.
+fn value_matches(field: &CustomField, value: &FieldValue) -> bool {
+    match (field.field_type, value) {
+        (FieldType::Text, FieldValue::Text(_)) => true,
+        (FieldType::Number, FieldValue::Number(n)) => n.is_finite(),
+        (FieldType::Date, FieldValue::Text(day)) => is_date(day),
+        (FieldType::SingleSelect, FieldValue::Text(option)) => field.options.contains(option),
+        (FieldType::MultiSelect, FieldValue::List(options)) => options.iter().all(|o| field.options.contains(o)),
+        (FieldType::Checkbox, FieldValue::Bool(_)) => true,
+        (FieldType::User, FieldValue::Text(user_id)) => ObjectId::parse_str(user_id).is_ok(),
+        _ => false,
+    }
+}
+
+/// Validates custom field values against the definitions of the task's project.
+/// `None` values remove a field and are always accepted.
+pub async fn check_custom_values(
+    client: &Client,
+    project: Option<&Project>,
+    values: &BTreeMap<String, Option<FieldValue>>,
+) -> Result<(), TaskError> {
+    let mut user_ids = Vec::new();
+    for (field_id, value) in values {
+        let Some(value) = value else { continue };
+        let field = project
+            .and_then(|p| p.custom_field(field_id))
+            .ok_or_else(|| TaskError::bad_request(format!("Unknown custom field: {}", field_id)))?;
+        if !value_matches(field, value) {
+            return Err(TaskError::bad_request(format!("Invalid value for custom field {}", field.name)));
+        }
+        if let (FieldType::User, FieldValue::Text(user_id)) = (field.field_type, value)
+            && let Ok(oid) = ObjectId::parse_str(user_id)
+            && !user_ids.contains(&oid)
+        {
+            user_ids.push(oid);
+        }
+    }
+
+    if !user_ids.is_empty() {
+        let found = client
+            .database("rust_backend")
+            .collection::<User>("users")
+            .count_documents(doc! { "_id": { "$in": &user_ids } })
+            .await
+            .map_err(|err| TaskError::internal(format!("Database error: {}", err)))?;
+        if found != user_ids.len() as u64 {
+            return Err(TaskError::bad_request("Custom field references an unknown user"));
+        }
+    }
+    Ok(())
+}
+
+/// Drops the values of fields `project` does not define, e.g. after the task moved to
+/// another project. Returns whether anything was dropped.
+pub fn retain_defined_fields(task: &mut Task, project: Option<&Project>) -> bool {
+    let before = task.custom_fields.len();
+    task.custom_fields.retain(|field_id, _| project.is_some_and(|p| p.custom_field(field_id).is_some()));
+    task.custom_fields.len() != before
+}
+
+/// Converts a value of a field of type `from` to the changed definition `to`, or
+/// `None` when it has no sensible equivalent.
+fn convert_value(value: &FieldValue, from: FieldType, to: &CustomField) -> Option<FieldValue> {
+    let converted = match (to.field_type, value) {
+        (FieldType::Text, FieldValue::Text(text)) => FieldValue::Text(text.clone()),
+        (FieldType::Text, FieldValue::Number(n)) => FieldValue::Text(n.to_string()),
+        (FieldType::Text, FieldValue::Bool(b)) => FieldValue::Text(b.to_string()),
+        (FieldType::Text, FieldValue::List(items)) => FieldValue::Text(items.join(", ")),
+        (FieldType::Number, FieldValue::Number(n)) => FieldValue::Number(*n),
+        (FieldType::Number, FieldValue::Text(text)) => FieldValue::Number(text.trim().parse().ok()?),
+        (FieldType::Date, FieldValue::Text(text)) => FieldValue::Text(text.trim().to_string()),
+        (FieldType::SingleSelect, FieldValue::Text(text)) => FieldValue::Text(text.clone()),
+        (FieldType::SingleSelect, FieldValue::List(items)) => {
+            FieldValue::Text(items.iter().find(|i| to.options.contains(i))?.clone())
+        }
+        (FieldType::MultiSelect, FieldValue::Text(text)) => FieldValue::List(vec![text.clone()]),
+        (FieldType::MultiSelect, FieldValue::List(items)) => {
+            let kept: Vec<String> = items.iter().filter(|i| to.options.contains(i)).cloned().collect();
+            if kept.is_empty() {
+                return None;
+            }
+            FieldValue::List(kept)
+        }
+        (FieldType::Checkbox, FieldValue::Bool(b)) => FieldValue::Bool(*b),
+        (FieldType::Checkbox, FieldValue::Text(text)) => FieldValue::Bool(text.trim().parse().ok()?),
+        // Any other text is not a user id.
+        (FieldType::User, FieldValue::Text(user_id)) if from == FieldType::User => FieldValue::Text(user_id.clone()),
+        _ => return None,
+    };
+    value_matches(to, &converted).then_some(converted)
+}
+
+/// The query value matching a custom field's stored values for a `?custom_value=`
+/// listing filter. Multi select fields match tasks with that option among theirs.
+pub fn custom_query_value(field: &CustomField, raw: &str) -> Result<Bson, String> {
+    match field.field_type {
+        FieldType::Number => raw
+            .trim()
+            .parse::<f64>()
+            .map(Bson::Double)
+            .map_err(|_| format!("{} is a number field", field.name)),
+        FieldType::Checkbox => raw
+            .trim()
+            .parse::<bool>()
+            .map(Bson::Boolean)
+            .map_err(|_| format!("{} is a checkbox field; use true or false", field.name)),
+        _ => Ok(Bson::String(raw.to_string())),
+    }
+}
+
+/// The tasks holding a value for a field, whoever owns them: in a workspace project they
+/// belong to several members. The caller's access to the project is checked beforehand.
+async fn tasks_with_value(client: &Client, project_id: &str, field_id: &str) -> Result<Vec<Task>, HttpResponse> {
+    let key = format!("custom_fields.{}", field_id);
+    match client
+        .database("rust_backend")
+        .collection::<Task>("tasks")
+        .find(doc! { "project_id": project_id, &key: { "$exists": true } })
+        .await
+    {
+        Ok(cursor) => cursor
+            .try_collect()
+            .await
+            .map_err(|e| HttpResponse::InternalServerError().body(format!("Error reading tasks: {}", e))),
+        Err(e) => Err(HttpResponse::InternalServerError().body(format!("Database error: {}", e))),
+    }
+}
+
+/// Replaces or removes the value a task holds for a field, recording the change in
+/// its history like any other task update.
+async fn set_task_value(
+    client: &Client,
+    actor_id: &str,
+    task: &Task,
+    field_id: &str,
+    value: Option<FieldValue>,
+) -> Result<(), HttpResponse> {
+    let Some(oid) = task.id.as_deref().and_then(|id| ObjectId::parse_str(id).ok()) else { return Ok(()) };
+    let key = format!("custom_fields.{}", field_id);
+    let now = Utc::now();
+
+    let mut updated = task.clone();
+    let change = match &value {
+        Some(value) => {
+            let converted = to_bson(value)
+                .map_err(|err| HttpResponse::InternalServerError().body(format!("Serialization error: {}", err)))?;
+            updated.custom_fields.insert(field_id.to_string(), value.clone());
+            doc! { "$set": { &key: converted, "updated_at": now.to_string() }, "$inc": { "version": 1 } }
+        }
+        None => {
+            updated.custom_fields.remove(field_id);
+            doc! { "$unset": { &key: "" }, "$set": { "updated_at": now.to_string() }, "$inc": { "version": 1 } }
+        }
+    };
+
+    client
+        .database("rust_backend")
+        .collection::<Task>("tasks")
+        .update_one(doc! { "_id": Bson::ObjectId(oid) }, change)
+        .await
+        .map_err(|err| HttpResponse::InternalServerError().body(format!("Error migrating field values: {}", err)))?;
+
+    updated.version += 1;
+    updated.updated_at = now;
+    let changes = diff_fields(&task.tracked_fields(), &updated.tracked_fields());
+    log_history(client, &updated, actor_id, HistoryAction::Updated, changes).await;
+    Ok(())
+}
+
+async fn owned_project(client: &Client, user_id: &str, project_id: &str) -> Result<Project, HttpResponse> {
+    match find_project(client, user_id, project_id).await {
+        Ok(Some(p)) => Ok(p),
+        Ok(None) => Err(HttpResponse::NotFound().body("Project not found")),
+        Err(err) => Err(HttpResponse::InternalServerError().body(format!("Database error: {}", err))),
+    }
+}
+
+async fn save_definitions(client: &Client, project: &Project) -> Result<(), HttpResponse> {
+    let oid = match project.id.as_deref().map(ObjectId::parse_str) {
+        Some(Ok(o)) => o,
+        _ => return Err(HttpResponse::BadRequest().body("Invalid project ID")),
+    };
+    let fields = to_bson(&project.custom_fields)
+        .map_err(|err| HttpResponse::InternalServerError().body(format!("Serialization error: {}", err)))?;
+
+    client
+        .database("rust_backend")
+        .collection::<Project>("projects")
+        .update_one(
+            doc! { "_id": Bson::ObjectId(oid), "user_id": &project.user_id },
+            doc! { "$set": { "custom_fields": fields, "updated_at": Utc::now().to_string() } },
+        )
+        .await
+        .map(|_| ())
+        .map_err(|err| HttpResponse::InternalServerError().body(format!("Error updating project: {}", err)))
+}
+
+pub async fn create_custom_field(
+    auth: AuthenticatedUser,
+    project_id: web::Path<String>,
+    field: web::Json<NewCustomField>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let mut project = match owned_project(&client, &auth.0, &project_id).await {
+        Ok(p) => p,
+        Err(response) => return response,
+    };
+
+    let field = field.into_inner();
+    let new_field = CustomField {
+        id: ObjectId::new().to_hex(),
+        name: field.name.trim().to_string(),
+        field_type: field.field_type,
+        options: if field.field_type.has_options() { normalize_list(&field.options) } else { Vec::new() },
+    };
+    if let Err(response) = check_definition(&project, &new_field) {
+        return response;
+    }
+
+    project.custom_fields.push(new_field.clone());
+    match save_definitions(&client, &project).await {
+        Ok(()) => HttpResponse::Ok().json(new_field),
+        Err(response) => response,
+    }
+}
+
+/// Changes a field definition and converts the values tasks already hold; values
+/// that cannot be converted are removed.
+pub async fn update_custom_field(
+    auth: AuthenticatedUser,
+    path: web::Path<(String, String)>,
+    update: web::Json<UpdateCustomField>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let (project_id, field_id) = path.into_inner();
+    let mut project = match owned_project(&client, &auth.0, &project_id).await {
+        Ok(p) => p,
+        Err(response) => return response,
+    };
+    let Some(index) = project.custom_fields.iter().position(|f| f.id == field_id) else {
+        return HttpResponse::NotFound().body("Custom field not found");
+    };
+
+    let previous = project.custom_fields[index].clone();
+    let mut field = previous.clone();
+    if let Some(name) = &update.name {
+        field.name = name.trim().to_string();
+    }
+    if let Some(field_type) = update.field_type {
+        field.field_type = field_type;
+    }
+    if let Some(options) = &update.options {
+        field.options = normalize_list(options);
+    }
+    if !field.field_type.has_options() {
+        field.options.clear();
+    }
+    if let Err(response) = check_definition(&project, &field) {
+        return response;
+    }
+
+    // Values are converted before anything is written, and the definition is saved
+    // last: if a write fails, the field keeps its previous definition and retrying the
+    // update converts the values left over.
+    let mut conversions = Vec::new();
+    if field.field_type != previous.field_type || field.options != previous.options {
+        let tasks = match tasks_with_value(&client, &project_id, &field.id).await {
+            Ok(tasks) => tasks,
+            Err(response) => return response,
+        };
+        for task in tasks {
+            let Some(value) = task.custom_fields.get(&field.id) else { continue };
+            match convert_value(value, previous.field_type, &field) {
+                Some(converted) if converted == *value => {}
+                converted => conversions.push((task, converted)),
+            }
+        }
+    }
+    for (task, converted) in conversions {
+        if let Err(response) = set_task_value(&client, &auth.0, &task, &field.id, converted).await {
+            return response;
+        }
+    }
+
+    project.custom_fields[index] = field.clone();
+    match save_definitions(&client, &project).await {
+        Ok(()) => HttpResponse::Ok().json(field),
+        Err(response) => response,
+    }
+}
+
+/// Deletes a field definition together with the values tasks hold for it.
+pub async fn delete_custom_field(
+    auth: AuthenticatedUser,
+    path: web::Path<(String, String)>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let (project_id, field_id) = path.into_inner();
+    let mut project = match owned_project(&client, &auth.0, &project_id).await {
+        Ok(p) => p,
+        Err(response) => return response,
+    };
+    if project.custom_field(&field_id).is_none() {
+        return HttpResponse::NotFound().body("Custom field not found");
+    }
+
+    // As with updates, the definition goes last so that a failed delete can be retried.
+    let tasks = match tasks_with_value(&client, &project_id, &field_id).await {
+        Ok(tasks) => tasks,
+        Err(response) => return response,
+    };
+    for task in tasks {
+        if let Err(response) = set_task_value(&client, &auth.0, &task, &field_id, None).await {
+            return response;
+        }
+    }
+
+    project.custom_fields.retain(|f| f.id != field_id);
+    match save_definitions(&client, &project).await {
+        Ok(()) => HttpResponse::Ok().body("Custom field deleted successfully"),
+        Err(response) => response,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(field_type: FieldType, options: &[&str]) -> CustomField {
+        CustomField {
+            id: "f1".to_string(),
+            name: "Field".to_string(),
+            field_type,
+            options: options.iter().map(|o| o.to_string()).collect(),
+        }
+    }
+
+    fn text(value: &str) -> FieldValue {
+        FieldValue::Text(value.to_string())
+    }
+
+    fn list(values: &[&str]) -> FieldValue {
+        FieldValue::List(values.iter().map(|v| v.to_string()).collect())
+    }
+
+    #[test]
+    fn values_must_match_the_field_type() {
+        assert!(value_matches(&field(FieldType::Text, &[]), &text("anything")));
+        assert!(!value_matches(&field(FieldType::Text, &[]), &FieldValue::Number(1.0)));
+        assert!(value_matches(&field(FieldType::Number, &[]), &FieldValue::Number(-2.5)));
+        assert!(!value_matches(&field(FieldType::Number, &[]), &FieldValue::Number(f64::NAN)));
+        assert!(!value_matches(&field(FieldType::Number, &[]), &FieldValue::Number(f64::INFINITY)));
+        assert!(value_matches(&field(FieldType::Date, &[]), &text("2025-02-28")));
+        assert!(!value_matches(&field(FieldType::Date, &[]), &text("2025-02-30")));
+        assert!(!value_matches(&field(FieldType::Date, &[]), &text("28/02/2025")));
+        assert!(value_matches(&field(FieldType::Checkbox, &[]), &FieldValue::Bool(false)));
+        assert!(!value_matches(&field(FieldType::Checkbox, &[]), &text("true")));
+        assert!(value_matches(&field(FieldType::User, &[]), &text("65a000000000000000000001")));
+        assert!(!value_matches(&field(FieldType::User, &[]), &text("alice")));
+    }
+
+    #[test]
+    fn select_values_must_be_options() {
+        let single = field(FieldType::SingleSelect, &["Low", "High"]);
+        assert!(value_matches(&single, &text("High")));
+        assert!(!value_matches(&single, &text("high")));
+        assert!(!value_matches(&single, &list(&["High"])));
+
+        let multi = field(FieldType::MultiSelect, &["a", "b"]);
+        assert!(value_matches(&multi, &list(&["a", "b"])));
+        assert!(value_matches(&multi, &list(&[])));
+        assert!(!value_matches(&multi, &list(&["a", "c"])));
+        assert!(!value_matches(&multi, &text("a")));
+    }
+
+    #[test]
+    fn converts_values_to_text() {
+        let to = field(FieldType::Text, &[]);
+        assert_eq!(convert_value(&FieldValue::Number(2.5), FieldType::Number, &to), Some(text("2.5")));
+        assert_eq!(convert_value(&FieldValue::Bool(true), FieldType::Checkbox, &to), Some(text("true")));
+        assert_eq!(convert_value(&list(&["a", "b"]), FieldType::MultiSelect, &to), Some(text("a, b")));
+    }
+
+    #[test]
+    fn converts_text_that_parses() {
+        let number = field(FieldType::Number, &[]);
+        assert_eq!(convert_value(&text(" 42 "), FieldType::Text, &number), Some(FieldValue::Number(42.0)));
+        assert_eq!(convert_value(&text("many"), FieldType::Text, &number), None);
+        assert_eq!(convert_value(&text("NaN"), FieldType::Text, &number), None);
+
+        let date = field(FieldType::Date, &[]);
+        assert_eq!(convert_value(&text(" 2025-01-02 "), FieldType::Text, &date), Some(text("2025-01-02")));
+        assert_eq!(convert_value(&text("soon"), FieldType::Text, &date), None);
+
+        let checkbox = field(FieldType::Checkbox, &[]);
+        assert_eq!(convert_value(&text("false"), FieldType::Text, &checkbox), Some(FieldValue::Bool(false)));
+        assert_eq!(convert_value(&text("yes"), FieldType::Text, &checkbox), None);
+    }
+
+    #[test]
+    fn keeps_only_remaining_options() {
+        let single = field(FieldType::SingleSelect, &["b", "c"]);
+        assert_eq!(convert_value(&list(&["a", "c", "b"]), FieldType::MultiSelect, &single), Some(text("c")));
+        assert_eq!(convert_value(&text("a"), FieldType::SingleSelect, &single), None);
+
+        let multi = field(FieldType::MultiSelect, &["b", "c"]);
+        assert_eq!(convert_value(&list(&["a", "b", "c"]), FieldType::MultiSelect, &multi), Some(list(&["b", "c"])));
+        assert_eq!(convert_value(&list(&["a"]), FieldType::MultiSelect, &multi), None);
+        assert_eq!(convert_value(&text("c"), FieldType::SingleSelect, &multi), Some(list(&["c"])));
+    }
+
+    #[test]
+    fn only_user_values_stay_users() {
+        let user = field(FieldType::User, &[]);
+        let id = text("65a000000000000000000001");
+        assert_eq!(convert_value(&id, FieldType::User, &user), Some(id.clone()));
+        assert_eq!(convert_value(&id, FieldType::Text, &user), None);
+        assert_eq!(convert_value(&FieldValue::Bool(true), FieldType::Checkbox, &user), None);
+    }
+}
@@ -0,0 +1,419 @@
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration as StdDuration;
+use actix_http::ws::{self, CloseCode, Codec, Frame, Message};
+use actix_web::{body::BodyStream, rt::time::interval, web, HttpRequest, HttpResponse, Responder};
+use bytes::{Bytes, BytesMut};
+use futures::{channel::mpsc::{self, Sender}, stream, StreamExt, TryStreamExt};
+use mongodb::{bson::{doc, Document}, options::ReturnDocument, Client};
+use chrono::{Duration, Utc};
+use tokio_util::codec::{Decoder, Encoder};
+use crate::controllers::workspace_controller::member_workspace_ids;
+use crate::models::event_model::{EventStreamQuery, TaskEvent, TaskEventKind, TaskEventResponse};
+use crate::models::history_model::{FieldChange, HistoryAction};
+use crate::models::task_model::{Task, TaskResponse};
+use crate::utils::auth::AuthenticatedUser;
+
+const POLL_INTERVAL: StdDuration = StdDuration::from_secs(1);
+/// Idle polls after which a keep-alive is sent, so proxies don't drop quiet streams.
+const KEEP_ALIVE_POLLS: u32 = 15;
+/// Polls after which the caller's workspaces are looked up again.
+const WORKSPACE_REFRESH_POLLS: u32 = 30;
+const BATCH_SIZE: i64 = 100;
+/// WebSocket frames waiting to be written. A client that lets this many pile up has
+/// stopped reading, so its socket is closed instead of buffering without bound.
+const SOCKET_BUFFER: usize = 4 * BATCH_SIZE as usize;
+/// How long after being numbered an event is assumed to be inserted.
+const SETTLE_SECONDS: i64 = 10;
+
+/// The stream event of a recorded task change. Purging a task from the trash sends
+/// nothing, since moving it there already sent `task.deleted`.
+fn event_kind(action: &HistoryAction) -> Option<TaskEventKind> {
+    match action {
+        HistoryAction::Created | HistoryAction::Restored => Some(TaskEventKind::Created),
+        HistoryAction::Updated => Some(TaskEventKind::Updated),
+        HistoryAction::Trashed => Some(TaskEventKind::Deleted),
+        HistoryAction::Deleted => None,
+    }
+}
+
+/// Stores a task change for the event streams. The change already succeeded, so
+/// failures are only logged.
+pub async fn record_task_event(
+    client: &Client,
+    task: &Task,
+    actor_id: &str,
+    action: &HistoryAction,
+    changes: &[FieldChange],
+) {
+    let Some(kind) = event_kind(action) else { return };
+
+    let seq = match next_seq(client).await {
+        Ok(seq) => seq,
+        Err(err) => {
+            eprintln!("❌ failed to number event for task {}: {}", task.id.as_deref().unwrap_or_default(), err);
+            return;
+        }
+    };
+    let event = TaskEvent {
+        id: None,
+        seq,
+        event: kind,
+        user_id: task.user_id.clone(),
+        workspace_id: task.workspace_id.clone(),
+        assignee_ids: task.assignee_ids.clone(),
+        actor_id: actor_id.to_string(),
+        task: TaskResponse::from(task.clone()),
+        changes: changes.to_vec(),
+        // Taken after the number, which `EventCursor` relies on.
+        created_at: Utc::now(),
+    };
+    if let Err(err) = client.database("rust_backend").collection::<TaskEvent>("task_events").insert_one(event).await {
+        eprintln!("❌ failed to record event for task {}: {}", task.id.as_deref().unwrap_or_default(), err);
+    }
+}
+
+/// Hands out the next event number from a counter document, so numbers only grow.
+async fn next_seq(client: &Client) -> mongodb::error::Result<i64> {
+    let counter = client
+        .database("rust_backend")
+        .collection::<Document>("counters")
+        .find_one_and_update(doc! { "_id": "task_events" }, doc! { "$inc": { "seq": 1_i64 } })
+        .upsert(true)
+        .return_document(ReturnDocument::After)
+        .await?;
+    Ok(counter.and_then(|c| c.get_i64("seq").ok()).unwrap_or_default())
+}
+
+/// Where a stream starts: just below `last_event_id` while that event is still kept,
+/// after the newest event otherwise. The flag is set when `last_event_id` was given but
+/// can't be resumed from, so the client knows it missed events and should reload its tasks.
+///
+/// A lower-numbered event may still have been on its way when `last_event_id` was sent,
+/// so a resumed stream goes back to the last event that was settled by then. Events the
+/// client already has are sent again; clients skip ids they have seen.
+async fn start_position(client: &Client, last_event_id: Option<&str>) -> mongodb::error::Result<(i64, bool)> {
+    let collection = client.database("rust_backend").collection::<TaskEvent>("task_events");
+
+    let last = match last_event_id.and_then(|id| id.trim().parse::<i64>().ok()) {
+        Some(seq) => collection.find_one(doc! { "seq": seq }).await?,
+        None => None,
+    };
+    if let Some(last) = last {
+        let settled = last.created_at - Duration::seconds(SETTLE_SECONDS);
+        let before = collection
+            .find_one(doc! { "created_at": { "$lt": settled.to_string() } })
+            .sort(doc! { "seq": -1 })
+            .await?;
+        return Ok((before.map(|e| e.seq.min(last.seq)).unwrap_or_default(), false));
+    }
+
+    let newest = collection.find_one(doc! {}).sort(doc! { "seq": -1 }).await?;
+    Ok((newest.map(|e| e.seq).unwrap_or_default(), last_event_id.is_some()))
+}
+
+/// Reads the events a user may see: those of tasks they own or are assigned to, and of
+/// tasks in their workspaces.
+///
+/// An event's number is taken before it is inserted, so a lower number can show up after
+/// a higher one. Events are therefore read again until they are `SETTLE_SECONDS` old,
+/// and the ones already sent are skipped.
+struct EventCursor {
+    client: Client,
+    user_id: String,
+    /// Every event up to this number has been sent or will never be visible.
+    floor: i64,
+    /// Events above `floor` that were already sent.
+    sent: BTreeSet<i64>,
+    workspace_ids: Vec<String>,
+    polls: u32,
+}
+
+impl EventCursor {
+    async fn next_batch(&mut self) -> mongodb::error::Result<Vec<TaskEvent>> {
+        if self.polls.is_multiple_of(WORKSPACE_REFRESH_POLLS) {
+            self.workspace_ids = member_workspace_ids(&self.client, &self.user_id).await?;
+        }
+        self.polls = self.polls.wrapping_add(1);
+
+        let collection = self.client.database("rust_backend").collection::<TaskEvent>("task_events");
+        let visible = doc! {
+            "$or": [
+                { "user_id": &self.user_id },
+                { "assignee_ids": &self.user_id },
+                { "workspace_id": { "$in": &self.workspace_ids } },
+            ],
+        };
+
+        // The unsettled events that were already sent are read again in full, so late
+        // lower numbers among them are found; the events after them come in batches.
+        let mut events: Vec<TaskEvent> = Vec::new();
+        let newest_sent = self.sent.last().copied().unwrap_or(self.floor);
+        if newest_sent > self.floor {
+            let mut filter = visible.clone();
+            filter.insert("seq", doc! { "$gt": self.floor, "$lte": newest_sent });
+            events = collection.find(filter).sort(doc! { "seq": 1 }).await?.try_collect().await?;
+        }
+        let mut filter = visible;
+        filter.insert("seq", doc! { "$gt": newest_sent });
+        let tail: Vec<TaskEvent> =
+            collection.find(filter).sort(doc! { "seq": 1 }).limit(BATCH_SIZE).await?.try_collect().await?;
+        events.extend(tail);
+
+        // Events are timestamped after they are numbered, so once one is settled every
+        // lower number is inserted and was read above.
+        let settled = Utc::now() - Duration::seconds(SETTLE_SECONDS);
+        let mut fresh = Vec::new();
+        for event in events {
+            if event.created_at < settled {
+                self.floor = self.floor.max(event.seq);
+            }
+            if self.sent.insert(event.seq) {
+                fresh.push(event);
+            }
+        }
+        let floor = self.floor;
+        self.sent.retain(|&seq| seq > floor);
+        Ok(fresh)
+    }
+}
+
+async fn open_cursor(client: &Client, user_id: String, last_event_id: Option<&str>) -> mongodb::error::Result<(EventCursor, bool)> {
+    let (floor, reset) = start_position(client, last_event_id).await?;
+    let cursor = EventCursor {
+        client: client.clone(),
+        user_id,
+        floor,
+        sent: BTreeSet::new(),
+        workspace_ids: Vec::new(),
+        polls: 0,
+    };
+    Ok((cursor, reset))
+}
+
+fn event_json(event: TaskEvent) -> String {
+    serde_json::to_string(&TaskEventResponse::from(event)).unwrap_or_default()
+}
+
+fn sse_message(event: TaskEvent) -> String {
+    let id = event.seq;
+    let kind = event.event.name();
+    format!("id: {}\nevent: {}\ndata: {}\n\n", id, kind, event_json(event))
+}
+
+/// Streams the caller's task events as Server-Sent Events. Reconnecting clients resume
+/// with the `Last-Event-ID` header or `?last_event_id=`.
+pub async fn stream_task_events(
+    auth: AuthenticatedUser,
+    req: HttpRequest,
+    query: web::Query<EventStreamQuery>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let last_event_id = req
+        .headers()
+        .get("Last-Event-ID")
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string)
+        .or_else(|| query.last_event_id.clone());
+
+    let (cursor, reset) = match open_cursor(&client, auth.0, last_event_id.as_deref()).await {
+        Ok(c) => c,
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    };
+
+    let mut opening = String::from("retry: 3000\n\n");
+    if reset {
+        opening.push_str("event: reset\ndata: {}\n\n");
+    }
+
+    let events = stream::unfold((cursor, interval(POLL_INTERVAL), 0u32), |(mut cursor, mut ticker, mut idle)| async move {
+        loop {
+            ticker.tick().await;
+            match cursor.next_batch().await {
+                Ok(events) if !events.is_empty() => {
+                    let chunk: String = events.into_iter().map(sse_message).collect();
+                    return Some((Bytes::from(chunk), (cursor, ticker, 0)));
+                }
+                Ok(_) => {
+                    idle += 1;
+                    if idle >= KEEP_ALIVE_POLLS {
+                        return Some((Bytes::from_static(b": keep-alive\n\n"), (cursor, ticker, 0)));
+                    }
+                }
+                // The client reconnects and resumes from the last event it got.
+                Err(err) => {
+                    eprintln!("❌ task event stream failed: {}", err);
+                    return None;
+                }
+            }
+        }
+    });
+
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(("Cache-Control", "no-cache"))
+        .insert_header(("X-Accel-Buffering", "no"))
+        .streaming(stream::once(async move { Bytes::from(opening) }).chain(events).map(Ok::<_, actix_web::Error>))
+}
+
+fn ws_frame(message: Message) -> Bytes {
+    let mut buffer = BytesMut::new();
+    if let Err(err) = Codec::new().encode(message, &mut buffer) {
+        eprintln!("❌ failed to encode WebSocket frame: {}", err);
+    }
+    buffer.freeze()
+}
+
+/// The frames queued for a WebSocket, shared by the tasks reading from and writing to it.
+#[derive(Clone)]
+struct Outgoing {
+    frames: Sender<Bytes>,
+    overflowed: Arc<AtomicBool>,
+}
+
+impl Outgoing {
+    /// Queues a message. Returns `false` once the socket is closing, which it starts
+    /// doing when the queue is full.
+    fn send(&mut self, message: Message) -> bool {
+        match self.frames.try_send(ws_frame(message)) {
+            Ok(()) => true,
+            Err(err) => {
+                if err.is_full() {
+                    self.overflowed.store(true, Ordering::Relaxed);
+                    self.frames.close_channel();
+                }
+                false
+            }
+        }
+    }
+
+    fn close(&mut self, code: Option<CloseCode>) {
+        self.send(Message::Close(code.map(Into::into)));
+        self.frames.close_channel();
+    }
+
+    fn is_closed(&self) -> bool {
+        self.frames.is_closed()
+    }
+}
+
+/// Answers pings and closes the connection when the client does; other messages are
+/// ignored.
+async fn read_frames(mut payload: web::Payload, mut outgoing: Outgoing) {
+    let mut codec = Codec::new();
+    let mut buffer = BytesMut::new();
+
+    while let Some(Ok(chunk)) = payload.next().await {
+        buffer.extend_from_slice(&chunk);
+        loop {
+            match codec.decode(&mut buffer) {
+                Ok(Some(Frame::Ping(data))) => {
+                    if !outgoing.send(Message::Pong(data)) {
+                        return;
+                    }
+                }
+                Ok(Some(Frame::Close(reason))) => {
+                    outgoing.send(Message::Close(reason));
+                    outgoing.frames.close_channel();
+                    return;
+                }
+                Ok(Some(_)) => {}
+                Ok(None) => break,
+                Err(_) => {
+                    outgoing.close(Some(CloseCode::Protocol));
+                    return;
+                }
+            }
+        }
+    }
+    outgoing.frames.close_channel();
+}
+
+async fn push_events(mut cursor: EventCursor, reset: bool, mut outgoing: Outgoing) {
+    if reset {
+        outgoing.send(Message::Text(r#"{"event":"reset"}"#.into()));
+    }
+
+    let mut ticker = interval(POLL_INTERVAL);
+    let mut idle = 0;
+    loop {
+        ticker.tick().await;
+        if outgoing.is_closed() {
+            return;
+        }
+        match cursor.next_batch().await {
+            Ok(events) if !events.is_empty() => {
+                idle = 0;
+                for event in events {
+                    if !outgoing.send(Message::Text(event_json(event).into())) {
+                        return;
+                    }
+                }
+            }
+            Ok(_) => {
+                idle += 1;
+                if idle >= KEEP_ALIVE_POLLS {
+                    idle = 0;
+                    outgoing.send(Message::Ping(Bytes::new()));
+                }
+            }
+            Err(err) => {
+                eprintln!("❌ task event socket failed: {}", err);
+                outgoing.close(Some(CloseCode::Error));
+                return;
+            }
+        }
+    }
+}
+
+/// Streams the caller's task events over a WebSocket, one JSON text message per event.
+/// Reconnecting clients resume with `?last_event_id=`, including those whose socket
+/// was closed with 1013 (try again later) for falling behind.
+pub async fn task_events_socket(
+    auth: AuthenticatedUser,
+    req: HttpRequest,
+    payload: web::Payload,
+    query: web::Query<EventStreamQuery>,
+    client: web::Data<Client>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let mut response = ws::handshake(req.head())?;
+    let (cursor, reset) = open_cursor(&client, auth.0, query.last_event_id.as_deref())
+        .await
+        .map_err(|err| actix_web::error::ErrorInternalServerError(format!("Database error: {}", err)))?;
+
+    let (frames, queued) = mpsc::channel::<Bytes>(SOCKET_BUFFER);
+    let overflowed = Arc::new(AtomicBool::new(false));
+    let outgoing = Outgoing { frames, overflowed: overflowed.clone() };
+    actix_web::rt::spawn(read_frames(payload, outgoing.clone()));
+    actix_web::rt::spawn(push_events(cursor, reset, outgoing));
+
+    // The close frame of a full queue goes out after the frames already in it.
+    let closing = stream::once(async move { overflowed.load(Ordering::Relaxed) })
+        .filter_map(|overflowed| async move { overflowed.then(|| ws_frame(Message::Close(Some(CloseCode::Again.into())))) });
+    let body = BodyStream::new(queued.chain(closing).map(Ok::<_, actix_web::Error>));
+    Ok(HttpResponse::from(response.body(body).map_into_boxed_body()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_full_queue_closes_the_socket() {
+        let (frames, mut queued) = mpsc::channel::<Bytes>(1);
+        let mut outgoing = Outgoing { frames, overflowed: Arc::new(AtomicBool::new(false)) };
+
+        // A channel holds its buffer plus one message per sender.
+        assert!(outgoing.send(Message::Text("1".into())));
+        assert!(outgoing.send(Message::Text("2".into())));
+        assert!(!outgoing.send(Message::Text("3".into())));
+        assert!(outgoing.overflowed.load(Ordering::Relaxed));
+        assert!(outgoing.is_closed());
+
+        // Frames queued before the overflow are still written.
+        assert!(queued.try_next().unwrap().is_some());
+        assert!(queued.try_next().unwrap().is_some());
+        assert_eq!(queued.try_next().unwrap(), None);
+    }
+}
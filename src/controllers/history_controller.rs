@@ -0,0 +1,68 @@
+use actix_web::{web, HttpResponse, Responder};
+use mongodb::{bson::doc, Client};
+use futures::stream::TryStreamExt;
+use chrono::Utc;
+use crate::controllers::task_controller::find_task;
+use crate::models::history_model::{FieldChange, HistoryAction, TaskHistory};
+use crate::utils::auth::AuthenticatedUser;
+
+pub async fn record_history(
+    client: &Client,
+    task_id: &str,
+    user_id: &str,
+    actor_id: &str,
+    action: HistoryAction,
+    changes: Vec<FieldChange>,
+) -> mongodb::error::Result<()> {
+    let entry = TaskHistory {
+        id: None,
+        task_id: task_id.to_string(),
+        user_id: user_id.to_string(),
+        actor_id: actor_id.to_string(),
+        action,
+        changes,
+        created_at: Utc::now(),
+    };
+
+    client
+        .database("rust_backend")
+        .collection::<TaskHistory>("task_history")
+        .insert_one(entry)
+        .await?;
+    Ok(())
+}
+
+pub async fn get_task_history(
+    auth: AuthenticatedUser,
+    task_id: web::Path<String>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let database = client.database("rust_backend");
+    let collection = database.collection::<TaskHistory>("task_history");
+
+    // Scoped by the stored owner rather than the task, so history stays readable after deletion.
+    let cursor = match collection
+        .find(doc! { "task_id": task_id.as_str(), "user_id": &auth.0 })
+        .sort(doc! { "created_at": 1, "_id": 1 })
+        .await
+    {
+        Ok(c) => c,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    let entries: Vec<TaskHistory> = match cursor.try_collect().await {
+        Ok(docs) => docs,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error reading history: {}", e)),
+    };
+
+    // Tasks created before history was recorded have none yet.
+    if entries.is_empty() {
+        match find_task(&client, &auth.0, &task_id).await {
+            Ok(Some(_)) => {}
+            Ok(None) => return HttpResponse::NotFound().body("Task not found"),
+            Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+        }
+    }
+
+    HttpResponse::Ok().json(entries)
+}
@@ -0,0 +1,270 @@
+use std::collections::{BTreeSet, HashMap};
+use actix_web::{web, HttpResponse, Responder};
+use mongodb::{bson::{doc, oid::ObjectId, Bson}, Client};
+use futures::stream::TryStreamExt;
+use chrono::Utc;
+use crate::controllers::board_controller::ColumnEnds;
+use crate::controllers::csv_controller::MAX_IMPORT_ROWS;
+use crate::controllers::project_controller::find_project;
+use crate::controllers::task_controller::{log_history, prepare_new_task};
+use crate::controllers::workflow_controller::{default_workflow, find_workflow};
+use crate::importers::{self, ImportedTask, Parsed};
+use crate::models::csv_model::ImportRowError;
+use crate::models::history_model::{diff_fields, HistoryAction};
+use crate::models::import_model::{ColumnMapping, FormatImportReport, FormatImportRequest, ImportFormat, ImportedTaskPreview};
+use crate::models::project_model::Project;
+use crate::models::task_model::{NewTask, Task};
+use crate::models::workflow_model::{StatusCategory, Workflow};
+use crate::utils::auth::AuthenticatedUser;
+
+/// Where an imported task ends up.
+enum Destination {
+    Existing(Project),
+    /// A project to create, by name.
+    New(String),
+}
+
+/// The status of `workflow` for an imported task: the one named like its column when
+/// columns map to statuses, a `Done` one for finished tasks, the initial one otherwise.
+fn task_status<'a>(workflow: &'a Workflow, task: &ImportedTask, columns: ColumnMapping, unknown: &mut BTreeSet<String>) -> Option<&'a str> {
+    let mut statuses: Vec<_> = workflow.statuses.iter().collect();
+    statuses.sort_by_key(|s| s.order);
+
+    let named = match (columns, &task.column) {
+        (ColumnMapping::Status, Some(column)) => {
+            let found = statuses.iter().find(|s| s.name.eq_ignore_ascii_case(column));
+            if found.is_none() {
+                unknown.insert(column.clone());
+            }
+            found
+        }
+        _ => None,
+    };
+    let done = statuses.iter().find(|s| s.category == StatusCategory::Done);
+
+    match named {
+        Some(status) if task.done && status.category != StatusCategory::Done => done.or(Some(status)),
+        Some(status) => Some(status),
+        None if task.done => done.or(statuses.first()),
+        None => statuses.first(),
+    }
+    .map(|s| s.name.as_str())
+}
+
+/// Imports tasks from another tool's export. With `preview`, nothing is created and the
+/// report lists the tasks as they would be.
+pub async fn import_from(
+    auth: AuthenticatedUser,
+    format: web::Path<ImportFormat>,
+    request: web::Json<FormatImportRequest>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let user_id = auth.0;
+    let request = request.into_inner();
+
+    let Parsed { tasks: imported, mut warnings } = match importers::parse(*format, &request.data) {
+        Ok(p) => p,
+        Err(message) => return HttpResponse::BadRequest().body(message),
+    };
+    if imported.len() > MAX_IMPORT_ROWS {
+        return HttpResponse::BadRequest().body(format!("At most {} tasks can be imported at once", MAX_IMPORT_ROWS));
+    }
+
+    let workflow = match &request.workflow_id {
+        Some(workflow_id) => match find_workflow(&client, &user_id, workflow_id).await {
+            Ok(Some(w)) => w,
+            Ok(None) => return HttpResponse::BadRequest().body("Workflow not found"),
+            Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+        },
+        None => match default_workflow(&client, &user_id).await {
+            Ok(w) => w,
+            Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+        },
+    };
+    let fallback_project = match &request.project_id {
+        Some(project_id) => match find_project(&client, &user_id, project_id).await {
+            Ok(Some(p)) => Some(p),
+            Ok(None) => return HttpResponse::BadRequest().body("Project not found"),
+            Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+        },
+        None => None,
+    };
+
+    let projects_collection = client.database("rust_backend").collection::<Project>("projects");
+    let existing_projects: Vec<Project> = match projects_collection.find(doc! { "user_id": &user_id }).await {
+        Ok(cursor) => match cursor.try_collect().await {
+            Ok(docs) => docs,
+            Err(e) => return HttpResponse::InternalServerError().body(format!("Error reading projects: {}", e)),
+        },
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+    let projects_by_name: HashMap<String, &Project> =
+        existing_projects.iter().map(|p| (p.name.to_lowercase(), p)).collect();
+
+    let mut report = FormatImportReport {
+        preview: request.preview,
+        tasks: Vec::new(),
+        new_projects: Vec::new(),
+        warnings: Vec::new(),
+        errors: Vec::new(),
+        task_ids: Vec::new(),
+    };
+    let mut unknown_columns = BTreeSet::new();
+    let mut prepared: Vec<(Task, Option<Destination>)> = Vec::new();
+    let mut column_ends = ColumnEnds::default();
+
+    for (index, task) in imported.iter().enumerate() {
+        let project_name = match request.columns {
+            ColumnMapping::Project => task.column.as_ref().or(task.project.as_ref()),
+            ColumnMapping::Status => task.project.as_ref(),
+        };
+        let destination = match project_name {
+            Some(name) => match projects_by_name.get(&name.to_lowercase()) {
+                Some(project) => Some(Destination::Existing((*project).clone())),
+                None => {
+                    if !report.new_projects.iter().any(|p| p.eq_ignore_ascii_case(name)) {
+                        report.new_projects.push(name.clone());
+                    }
+                    Some(Destination::New(name.clone()))
+                }
+            },
+            None => fallback_project.clone().map(Destination::Existing),
+        };
+        let status = task_status(&workflow, task, request.columns, &mut unknown_columns).map(str::to_string);
+
+        let new_task = NewTask {
+            title: task.title.clone(),
+            description: task.full_description(),
+            status,
+            workflow_id: request.workflow_id.clone(),
+            labels: Some(task.labels.clone()),
+            priority: task.priority,
+            // Projects still to be created are filled in once they are.
+            project_id: match &destination {
+                Some(Destination::Existing(project)) => project.id.clone(),
+                _ => None,
+            },
+            parent_id: None,
+            workspace_id: None,
+            owner_id: None,
+            assignee_ids: None,
+            due_at: task.due_at,
+            custom_fields: None,
+        };
+        let prepared_task = match prepare_new_task(&client, &user_id, &new_task).await {
+            Ok(mut task) => column_ends.place(&mut task).map(|()| task),
+            Err(err) => Err(err),
+        };
+        match prepared_task {
+            Ok(prepared_task) => {
+                report.tasks.push(ImportedTaskPreview {
+                    title: prepared_task.title.clone(),
+                    status: prepared_task.status.clone(),
+                    project: match &destination {
+                        Some(Destination::Existing(project)) => Some(project.name.clone()),
+                        Some(Destination::New(name)) => Some(name.clone()),
+                        None => None,
+                    },
+                    labels: prepared_task.labels.clone(),
+                    priority: prepared_task.priority,
+                    due_at: prepared_task.due_at,
+                    checklist_items: task.checklist.len(),
+                });
+                prepared.push((prepared_task, destination));
+            }
+            Err(err) => report.errors.push(ImportRowError { row: index as u64 + 1, message: err.message }),
+        }
+    }
+
+    let initial = workflow.initial_status().map(|s| s.name.clone()).unwrap_or_default();
+    warnings.extend(
+        unknown_columns
+            .into_iter()
+            .map(|column| format!("No status is named \"{}\"; its tasks start in {}", column, initial)),
+    );
+    report.warnings = warnings;
+
+    if request.preview {
+        return HttpResponse::Ok().json(report);
+    }
+    if !report.errors.is_empty() {
+        return HttpResponse::UnprocessableEntity().json(report);
+    }
+    if prepared.is_empty() {
+        return HttpResponse::Ok().json(report);
+    }
+
+    let now = Utc::now();
+    let mut created_projects: HashMap<String, String> = HashMap::new();
+    for name in &report.new_projects {
+        let project = Project {
+            id: None,
+            user_id: user_id.clone(),
+            workspace_id: None,
+            name: name.clone(),
+            description: None,
+            color: None,
+            archived: false,
+            custom_fields: Vec::new(),
+            created_at: now,
+            updated_at: now,
+        };
+        match projects_collection.insert_one(&project).await {
+            Ok(result) => {
+                if let Some(oid) = result.inserted_id.as_object_id() {
+                    created_projects.insert(name.to_lowercase(), oid.to_hex());
+                }
+            }
+            Err(err) => {
+                rollback_projects(&client, &created_projects).await;
+                return HttpResponse::InternalServerError().body(format!("Error creating project: {}", err));
+            }
+        }
+    }
+
+    let mut tasks: Vec<Task> = prepared
+        .into_iter()
+        .map(|(mut task, destination)| {
+            if let Some(Destination::New(name)) = destination {
+                task.project_id = created_projects.get(&name.to_lowercase()).cloned();
+            }
+            task
+        })
+        .collect();
+
+    let collection = client.database("rust_backend").collection::<Task>("tasks");
+    let result = match collection.insert_many(&tasks).await {
+        Ok(r) => r,
+        Err(err) => {
+            rollback_projects(&client, &created_projects).await;
+            return HttpResponse::InternalServerError().body(format!("Error inserting tasks: {}", err));
+        }
+    };
+    for (index, task) in tasks.iter_mut().enumerate() {
+        task.id = result.inserted_ids.get(&index).and_then(|id| id.as_object_id()).map(|oid| oid.to_hex());
+        log_history(&client, task, &user_id, HistoryAction::Created, diff_fields(&[], &task.tracked_fields())).await;
+    }
+    report.task_ids = tasks.into_iter().filter_map(|t| t.id).collect();
+
+    HttpResponse::Ok().json(report)
+}
+
+/// Removes the projects created for an import that then failed.
+async fn rollback_projects(client: &Client, created: &HashMap<String, String>) {
+    let ids: Vec<Bson> = created
+        .values()
+        .filter_map(|id| ObjectId::parse_str(id).ok())
+        .map(Bson::ObjectId)
+        .collect();
+    if ids.is_empty() {
+        return;
+    }
+    if let Err(err) = client
+        .database("rust_backend")
+        .collection::<Project>("projects")
+        .delete_many(doc! { "_id": { "$in": ids } })
+        .await
+    {
+        eprintln!("❌ failed to remove projects of a failed import: {}", err);
+    }
+}
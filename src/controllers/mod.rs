@@ -1,2 +1,24 @@
 pub mod user_controller;
-pub mod task_controller;
\ No newline at end of file
+pub mod task_controller;
+pub mod workflow_controller;
+pub mod comment_controller;
+pub mod attachment_controller;
+pub mod history_controller;
+pub mod trash_controller;
+pub mod batch_controller;
+pub mod project_controller;
+pub mod board_controller;
+pub mod time_controller;
+pub mod workspace_controller;
+pub mod notification_controller;
+pub mod template_controller;
+pub mod stats_controller;
+pub mod view_controller;
+pub mod custom_field_controller;
+pub mod csv_controller;
+pub mod calendar_controller;
+pub mod caldav_controller;
+pub mod account_controller;
+pub mod import_controller;
+pub mod webhook_controller;
+pub mod event_controller;
@@ -0,0 +1,257 @@
+use std::collections::HashMap;
+use actix_web::{web, HttpResponse, Responder};
+use mongodb::{bson::{doc, Bson}, Client};
+use mongodb::bson::oid::ObjectId;
+use futures::stream::TryStreamExt;
+use chrono::Utc;
+use serde_json::Value;
+use crate::models::history_model::{FieldChange, HistoryAction};
+use crate::models::notification_model::{
+    Notification, NotificationKind, NotificationPreferences, NotificationQuery, NotificationResponse,
+    UserNotificationPreferences,
+};
+use crate::models::task_model::Task;
+use crate::utils::auth::AuthenticatedUser;
+use crate::utils::pagination::{Page, PageQuery};
+
+async fn preferences_of(
+    client: &Client,
+    user_ids: &[String],
+) -> mongodb::error::Result<HashMap<String, NotificationPreferences>> {
+    let stored: Vec<UserNotificationPreferences> = client
+        .database("rust_backend")
+        .collection::<UserNotificationPreferences>("notification_preferences")
+        .find(doc! { "user_id": { "$in": user_ids } })
+        .await?
+        .try_collect()
+        .await?;
+
+    Ok(stored.into_iter().map(|p| (p.user_id, p.preferences)).collect())
+}
+
+/// Notifies `recipients` about a task, skipping the actor and anyone who turned `kind`
+/// off. The triggering change already succeeded, so failures are only logged.
+pub async fn notify(
+    client: &Client,
+    recipients: &[String],
+    kind: NotificationKind,
+    task: &Task,
+    actor_id: Option<&str>,
+    message: String,
+) {
+    let mut user_ids: Vec<String> = Vec::new();
+    for recipient in recipients {
+        if Some(recipient.as_str()) != actor_id && !user_ids.contains(recipient) {
+            user_ids.push(recipient.clone());
+        }
+    }
+    if user_ids.is_empty() {
+        return;
+    }
+
+    let preferences = match preferences_of(client, &user_ids).await {
+        Ok(p) => p,
+        Err(err) => {
+            eprintln!("❌ failed to load notification preferences: {}", err);
+            return;
+        }
+    };
+
+    let now = Utc::now();
+    let notifications: Vec<Notification> = user_ids
+        .into_iter()
+        .filter(|user_id| preferences.get(user_id).is_none_or(|p| p.allows(kind)))
+        .map(|user_id| Notification {
+            id: None,
+            user_id,
+            kind,
+            task_id: task.id.clone().unwrap_or_default(),
+            actor_id: actor_id.map(str::to_string),
+            message: message.clone(),
+            read: false,
+            created_at: now,
+        })
+        .collect();
+    if notifications.is_empty() {
+        return;
+    }
+
+    if let Err(err) = client
+        .database("rust_backend")
+        .collection::<Notification>("notifications")
+        .insert_many(notifications)
+        .await
+    {
+        eprintln!("❌ failed to store notifications for task {}: {}", task.id.as_deref().unwrap_or_default(), err);
+    }
+}
+
+/// The owner and assignees of a task.
+pub fn task_watchers(task: &Task) -> Vec<String> {
+    let mut watchers = vec![task.user_id.clone()];
+    watchers.extend(task.assignee_ids.iter().cloned());
+    watchers
+}
+
+fn string_list(value: &Value) -> Vec<String> {
+    value
+        .as_array()
+        .map(|items| items.iter().filter_map(|v| v.as_str().map(str::to_string)).collect())
+        .unwrap_or_default()
+}
+
+/// Turns the recorded changes of a task write into assignment and status notifications.
+pub async fn notify_task_changes(
+    client: &Client,
+    task: &Task,
+    actor_id: &str,
+    action: &HistoryAction,
+    changes: &[FieldChange],
+) {
+    match action {
+        HistoryAction::Created => {
+            let message = format!("You were assigned to \"{}\"", task.title);
+            notify(client, &task.assignee_ids, NotificationKind::Assigned, task, Some(actor_id), message).await;
+        }
+        HistoryAction::Updated => {
+            for change in changes {
+                match change.field.as_str() {
+                    "assignee_ids" => {
+                        let before = string_list(&change.from);
+                        let added: Vec<String> =
+                            string_list(&change.to).into_iter().filter(|id| !before.contains(id)).collect();
+                        let message = format!("You were assigned to \"{}\"", task.title);
+                        notify(client, &added, NotificationKind::Assigned, task, Some(actor_id), message).await;
+                    }
+                    "status" => {
+                        let message = format!(
+                            "\"{}\" moved from {} to {}",
+                            task.title,
+                            change.from.as_str().unwrap_or_default(),
+                            change.to.as_str().unwrap_or_default(),
+                        );
+                        notify(client, &task_watchers(task), NotificationKind::StatusChanged, task, Some(actor_id), message)
+                            .await;
+                    }
+                    _ => {}
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+pub async fn get_notifications(
+    auth: AuthenticatedUser,
+    query: web::Query<NotificationQuery>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let page = PageQuery { page: query.page, limit: query.limit };
+    let collection = client.database("rust_backend").collection::<Notification>("notifications");
+
+    let mut filter = doc! { "user_id": &auth.0 };
+    if query.unread_only.unwrap_or(false) {
+        filter.insert("read", false);
+    }
+
+    let total = match collection.count_documents(filter.clone()).await {
+        Ok(n) => n,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    let cursor = match collection
+        .find(filter)
+        .sort(doc! { "read": 1, "created_at": -1, "_id": -1 })
+        .skip(page.skip())
+        .limit(page.limit() as i64)
+        .await
+    {
+        Ok(c) => c,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    let items: Vec<NotificationResponse> = match cursor.try_collect::<Vec<_>>().await {
+        Ok(docs) => docs.into_iter().map(NotificationResponse::from).collect(),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error reading notifications: {}", e)),
+    };
+
+    HttpResponse::Ok().json(Page { items, page: page.page(), limit: page.limit(), total })
+}
+
+async fn set_read(client: &Client, user_id: &str, notification_id: &str, read: bool) -> HttpResponse {
+    let oid = match ObjectId::parse_str(notification_id) {
+        Ok(o) => o,
+        Err(_) => return HttpResponse::BadRequest().body("Invalid notification ID"),
+    };
+
+    let collection = client.database("rust_backend").collection::<Notification>("notifications");
+
+    match collection
+        .update_one(doc! { "_id": Bson::ObjectId(oid), "user_id": user_id }, doc! { "$set": { "read": read } })
+        .await
+    {
+        Ok(update_result) if update_result.matched_count > 0 => {
+            HttpResponse::Ok().body(if read { "Notification marked as read" } else { "Notification marked as unread" })
+        }
+        Ok(_) => HttpResponse::NotFound().body("Notification not found"),
+        Err(err) => HttpResponse::InternalServerError().body(format!("Error updating notification: {}", err)),
+    }
+}
+
+pub async fn mark_read(
+    auth: AuthenticatedUser,
+    notification_id: web::Path<String>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    set_read(&client, &auth.0, &notification_id, true).await
+}
+
+pub async fn mark_unread(
+    auth: AuthenticatedUser,
+    notification_id: web::Path<String>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    set_read(&client, &auth.0, &notification_id, false).await
+}
+
+pub async fn mark_all_read(
+    auth: AuthenticatedUser,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let collection = client.database("rust_backend").collection::<Notification>("notifications");
+
+    match collection
+        .update_many(doc! { "user_id": &auth.0, "read": false }, doc! { "$set": { "read": true } })
+        .await
+    {
+        Ok(update_result) => HttpResponse::Ok().json(serde_json::json!({ "updated": update_result.modified_count })),
+        Err(err) => HttpResponse::InternalServerError().body(format!("Error updating notifications: {}", err)),
+    }
+}
+
+pub async fn get_preferences(
+    auth: AuthenticatedUser,
+    client: web::Data<Client>,
+) -> impl Responder {
+    match preferences_of(&client, std::slice::from_ref(&auth.0)).await {
+        Ok(mut preferences) => HttpResponse::Ok().json(preferences.remove(&auth.0).unwrap_or_default()),
+        Err(err) => HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    }
+}
+
+pub async fn update_preferences(
+    auth: AuthenticatedUser,
+    preferences: web::Json<NotificationPreferences>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let preferences = preferences.into_inner();
+    let collection = client
+        .database("rust_backend")
+        .collection::<UserNotificationPreferences>("notification_preferences");
+
+    let stored = UserNotificationPreferences { user_id: auth.0.clone(), preferences };
+    match collection.replace_one(doc! { "user_id": &auth.0 }, &stored).upsert(true).await {
+        Ok(_) => HttpResponse::Ok().json(stored.preferences),
+        Err(err) => HttpResponse::InternalServerError().body(format!("Error saving preferences: {}", err)),
+    }
+}
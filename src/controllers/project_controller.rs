@@ -0,0 +1,395 @@
+use std::collections::HashMap;
+use actix_web::{web, HttpResponse, Responder};
+use mongodb::{bson::{doc, Bson, Document}, Client};
+use mongodb::bson::oid::ObjectId;
+use futures::stream::TryStreamExt;
+use chrono::Utc;
+use crate::models::project_model::{
+    is_valid_color, NewProject, Project, ProjectListQuery, ProjectResponse, TaskCounts, UpdateProject,
+};
+use crate::controllers::workspace_controller::{find_workspace, member_workspace_ids};
+use crate::models::task_model::{Task, TaskResponse};
+use crate::models::workspace_model::Workspace;
+use crate::utils::auth::AuthenticatedUser;
+
+/// Matches the projects `user_id` can use: their personal projects and those of the
+/// workspaces they are a member of.
+async fn access_filter(client: &Client, user_id: &str) -> mongodb::error::Result<Document> {
+    let workspace_ids = member_workspace_ids(client, user_id).await?;
+    Ok(doc! { "$or": [{ "user_id": user_id, "workspace_id": Bson::Null }, { "workspace_id": { "$in": workspace_ids } }] })
+}
+
+/// Looks up a project `user_id` can use; malformed ids are treated as not found.
+pub async fn find_project(
+    client: &Client,
+    user_id: &str,
+    project_id: &str,
+) -> mongodb::error::Result<Option<Project>> {
+    let oid = match ObjectId::parse_str(project_id) {
+        Ok(o) => o,
+        Err(_) => return Ok(None),
+    };
+
+    let mut filter = access_filter(client, user_id).await?;
+    filter.insert("_id", Bson::ObjectId(oid));
+    client
+        .database("rust_backend")
+        .collection::<Project>("projects")
+        .find_one(filter)
+        .await
+}
+
+/// Ids of the archived projects `user_id` can use, whose tasks default listings leave out.
+pub async fn archived_project_ids(client: &Client, user_id: &str) -> mongodb::error::Result<Vec<String>> {
+    let mut filter = access_filter(client, user_id).await?;
+    filter.insert("archived", true);
+    let projects: Vec<Project> = client
+        .database("rust_backend")
+        .collection::<Project>("projects")
+        .find(filter)
+        .await?
+        .try_collect()
+        .await?;
+
+    Ok(projects.into_iter().filter_map(|p| p.id).collect())
+}
+
+/// Counts the live tasks of the given projects per status. Workspace projects count
+/// the tasks of every member.
+async fn task_counts(client: &Client, project_ids: Vec<String>) -> mongodb::error::Result<HashMap<String, TaskCounts>> {
+    let pipeline = vec![
+        doc! { "$match": { "project_id": { "$in": project_ids }, "deleted_at": Bson::Null } },
+        doc! { "$group": {
+            "_id": { "project_id": "$project_id", "status": "$status" },
+            "count": { "$sum": 1 },
+        } },
+    ];
+
+    let groups: Vec<Document> = client
+        .database("rust_backend")
+        .collection::<Task>("tasks")
+        .aggregate(pipeline)
+        .await?
+        .try_collect()
+        .await?;
+
+    let mut counts: HashMap<String, TaskCounts> = HashMap::new();
+    for group in groups {
+        let Ok(key) = group.get_document("_id") else { continue };
+        let (Ok(project_id), Ok(status)) = (key.get_str("project_id"), key.get_str("status")) else { continue };
+        let count = match group.get("count") {
+            Some(Bson::Int32(n)) => *n as u64,
+            Some(Bson::Int64(n)) => *n as u64,
+            _ => 0,
+        };
+
+        let entry = counts.entry(project_id.to_string()).or_default();
+        entry.total += count;
+        *entry.by_status.entry(status.to_string()).or_default() += count;
+    }
+
+    Ok(counts)
+}
+
+async fn project_response(client: &Client, project: Project) -> HttpResponse {
+    match task_counts(client, project.id.iter().cloned().collect()).await {
+        Ok(mut counts) => {
+            let project_counts = project.id.as_ref().and_then(|id| counts.remove(id)).unwrap_or_default();
+            HttpResponse::Ok().json(ProjectResponse::new(project, project_counts))
+        }
+        Err(err) => HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    }
+}
+
+fn validate_color(color: &Option<String>) -> Result<(), HttpResponse> {
+    match color {
+        Some(color) if !is_valid_color(color) => {
+            Err(HttpResponse::BadRequest().body("Color must be a hex value like #3b82f6"))
+        }
+        _ => Ok(()),
+    }
+}
+
+pub async fn create_project(
+    auth: AuthenticatedUser,
+    project: web::Json<NewProject>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let project = project.into_inner();
+
+    let name = project.name.trim();
+    if name.is_empty() {
+        return HttpResponse::BadRequest().body("Project name is required");
+    }
+    if let Err(response) = validate_color(&project.color) {
+        return response;
+    }
+    if let Some(workspace_id) = &project.workspace_id {
+        match find_workspace(&client, &auth.0, workspace_id).await {
+            Ok(Some(_)) => {}
+            Ok(None) => return HttpResponse::BadRequest().body("Workspace not found"),
+            Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+        }
+    }
+
+    let now = Utc::now();
+    let mut new_project = Project {
+        id: None,
+        user_id: auth.0,
+        workspace_id: project.workspace_id,
+        name: name.to_string(),
+        description: project.description,
+        color: project.color,
+        archived: false,
+        custom_fields: Vec::new(),
+        created_at: now,
+        updated_at: now,
+    };
+
+    let collection = client.database("rust_backend").collection::<Project>("projects");
+
+    match collection.insert_one(&new_project).await {
+        Ok(insert_result) => {
+            if let Some(oid) = insert_result.inserted_id.as_object_id() {
+                new_project.id = Some(oid.to_hex());
+                HttpResponse::Ok().json(ProjectResponse::new(new_project, TaskCounts::default()))
+            } else {
+                HttpResponse::InternalServerError().body("Failed to get inserted ID")
+            }
+        }
+        Err(err) => HttpResponse::InternalServerError().body(format!("Error inserting project: {}", err)),
+    }
+}
+
+pub async fn get_projects(
+    auth: AuthenticatedUser,
+    query: web::Query<ProjectListQuery>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let collection = client.database("rust_backend").collection::<Project>("projects");
+
+    let mut filter = match access_filter(&client, &auth.0).await {
+        Ok(f) => f,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+    if !query.include_archived.unwrap_or(false) {
+        filter.insert("archived", false);
+    }
+
+    let cursor = match collection.find(filter).sort(doc! { "name": 1 }).await {
+        Ok(c) => c,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    let projects: Vec<Project> = match cursor.try_collect().await {
+        Ok(docs) => docs,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error reading projects: {}", e)),
+    };
+
+    let mut counts = match task_counts(&client, projects.iter().filter_map(|p| p.id.clone()).collect()).await {
+        Ok(c) => c,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    let projects: Vec<ProjectResponse> = projects
+        .into_iter()
+        .map(|project| {
+            let project_counts = project.id.as_ref().and_then(|id| counts.remove(id)).unwrap_or_default();
+            ProjectResponse::new(project, project_counts)
+        })
+        .collect();
+
+    HttpResponse::Ok().json(projects)
+}
+
+pub async fn get_project(
+    auth: AuthenticatedUser,
+    project_id: web::Path<String>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    match find_project(&client, &auth.0, &project_id).await {
+        Ok(Some(project)) => project_response(&client, project).await,
+        Ok(None) => HttpResponse::NotFound().body("Project not found"),
+        Err(err) => HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    }
+}
+
+pub async fn update_project(
+    auth: AuthenticatedUser,
+    project_id: web::Path<String>,
+    update: web::Json<UpdateProject>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let user_id = auth.0;
+    let update = update.into_inner();
+
+    let mut project = match find_project(&client, &user_id, &project_id).await {
+        Ok(Some(p)) => p,
+        Ok(None) => return HttpResponse::NotFound().body("Project not found"),
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    };
+
+    let now = Utc::now();
+    let mut update_doc = doc! { "updated_at": now.to_string() };
+
+    if let Some(name) = &update.name {
+        let name = name.trim();
+        if name.is_empty() {
+            return HttpResponse::BadRequest().body("Project name is required");
+        }
+        update_doc.insert("name", name);
+        project.name = name.to_string();
+    }
+    if let Some(description) = update.description {
+        update_doc.insert("description", description.clone());
+        project.description = description;
+    }
+    if let Some(color) = update.color {
+        if let Err(response) = validate_color(&color) {
+            return response;
+        }
+        update_doc.insert("color", color.clone());
+        project.color = color;
+    }
+    project.updated_at = now;
+
+    set_project_fields(&client, project, update_doc).await
+}
+
+/// Saves fields of a project the caller was already found to have access to.
+async fn set_project_fields(client: &Client, project: Project, fields: Document) -> HttpResponse {
+    let oid = match project.id.as_deref().map(ObjectId::parse_str) {
+        Some(Ok(o)) => o,
+        _ => return HttpResponse::BadRequest().body("Invalid project ID"),
+    };
+
+    let collection = client.database("rust_backend").collection::<Project>("projects");
+
+    match collection
+        .update_one(doc! { "_id": Bson::ObjectId(oid) }, doc! { "$set": fields })
+        .await
+    {
+        Ok(update_result) if update_result.matched_count > 0 => project_response(client, project).await,
+        Ok(_) => HttpResponse::NotFound().body("Project not found"),
+        Err(err) => HttpResponse::InternalServerError().body(format!("Error updating project: {}", err)),
+    }
+}
+
+async fn set_archived(user_id: String, project_id: &str, archived: bool, client: &Client) -> HttpResponse {
+    let mut project = match find_project(client, &user_id, project_id).await {
+        Ok(Some(p)) => p,
+        Ok(None) => return HttpResponse::NotFound().body("Project not found"),
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    };
+
+    let now = Utc::now();
+    project.archived = archived;
+    project.updated_at = now;
+
+    set_project_fields(client, project, doc! { "archived": archived, "updated_at": now.to_string() }).await
+}
+
+/// Archives a project, hiding it and its tasks from default listings.
+pub async fn archive_project(
+    auth: AuthenticatedUser,
+    project_id: web::Path<String>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    set_archived(auth.0, &project_id, true, &client).await
+}
+
+pub async fn unarchive_project(
+    auth: AuthenticatedUser,
+    project_id: web::Path<String>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    set_archived(auth.0, &project_id, false, &client).await
+}
+
+/// Deletes a project without tasks. Workspace projects can be deleted by their creator
+/// and the workspace owner.
+pub async fn delete_project(
+    auth: AuthenticatedUser,
+    project_id: web::Path<String>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let project_id = project_id.into_inner();
+
+    let oid = match ObjectId::parse_str(&project_id) {
+        Ok(o) => o,
+        Err(_) => return HttpResponse::BadRequest().body("Invalid project ID"),
+    };
+
+    let project = match find_project(&client, &auth.0, &project_id).await {
+        Ok(Some(p)) => p,
+        Ok(None) => return HttpResponse::NotFound().body("Project not found"),
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    };
+    if project.user_id != auth.0 {
+        let workspace_owner = match project.workspace_id.as_deref().map(ObjectId::parse_str) {
+            Some(Ok(workspace_oid)) => client
+                .database("rust_backend")
+                .collection::<Workspace>("workspaces")
+                .find_one(doc! { "_id": workspace_oid, "owner_id": &auth.0 })
+                .await
+                .map(|w| w.is_some()),
+            _ => Ok(false),
+        };
+        match workspace_owner {
+            Ok(true) => {}
+            Ok(false) => return HttpResponse::Forbidden().body("Only the project's creator or the workspace owner can delete it"),
+            Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+        }
+    }
+
+    // Trashed tasks count too, since restoring them would leave a dangling project.
+    let tasks = client.database("rust_backend").collection::<Task>("tasks");
+    match tasks.count_documents(doc! { "project_id": &project_id }).await {
+        Ok(0) => {}
+        Ok(_) => return HttpResponse::Conflict().body("Project still has tasks"),
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    }
+
+    let collection = client.database("rust_backend").collection::<Project>("projects");
+
+    match collection.delete_one(doc! { "_id": Bson::ObjectId(oid) }).await {
+        Ok(delete_result) => {
+            if delete_result.deleted_count > 0 {
+                HttpResponse::Ok().body("Project deleted successfully")
+            } else {
+                HttpResponse::NotFound().body("Project not found")
+            }
+        }
+        Err(err) => HttpResponse::InternalServerError().body(format!("Error deleting project: {}", err)),
+    }
+}
+
+/// Lists a project's tasks, archived or not; for workspace projects, those of every member.
+pub async fn get_project_tasks(
+    auth: AuthenticatedUser,
+    project_id: web::Path<String>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    match find_project(&client, &auth.0, &project_id).await {
+        Ok(Some(_)) => {}
+        Ok(None) => return HttpResponse::NotFound().body("Project not found"),
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    }
+
+    let collection = client.database("rust_backend").collection::<Task>("tasks");
+
+    let cursor = match collection
+        .find(doc! { "project_id": project_id.as_str(), "deleted_at": Bson::Null })
+        .await
+    {
+        Ok(c) => c,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    let tasks: Vec<TaskResponse> = match cursor.try_collect::<Vec<_>>().await {
+        Ok(docs) => docs.into_iter().map(TaskResponse::from).collect(),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error reading tasks: {}", e)),
+    };
+
+    HttpResponse::Ok().json(tasks)
+}
@@ -0,0 +1,217 @@
+use std::collections::BTreeMap;
+use actix_web::{web, HttpResponse, Responder};
+use mongodb::{bson::{doc, Bson, Document}, Client};
+use futures::stream::TryStreamExt;
+use chrono::{Datelike, Days, NaiveDate, Utc};
+use crate::models::stats_model::{PeriodCounts, StatsInterval, StatsQuery, StatusCount, TaskStats};
+use crate::models::task_model::Task;
+use crate::utils::auth::AuthenticatedUser;
+
+const DEFAULT_RANGE_DAYS: u64 = 30;
+/// Longest range a single request may cover.
+const MAX_RANGE_DAYS: i64 = 366;
+
+/// Parses a stored timestamp to a BSON date in an aggregation. Only the first 19
+/// characters are used, which covers both stored formats
+/// (`2026-10-19 01:51:28.79 UTC` and RFC 3339).
+fn parse_timestamp(field: &str) -> Document {
+    doc! { "$dateFromString": {
+        "dateString": { "$replaceAll": { "input": { "$substrBytes": [field, 0, 19] }, "find": "T", "replacement": " " } },
+        "format": "%Y-%m-%d %H:%M:%S",
+        "onError": Bson::Null,
+        "onNull": Bson::Null,
+    } }
+}
+
+/// The day (`YYYY-MM-DD`) or the Monday of the week a stored timestamp falls in.
+fn period_key(field: &str, interval: StatsInterval) -> Bson {
+    let day = doc! { "$substrBytes": [field, 0, 10] };
+    match interval {
+        StatsInterval::Day => Bson::Document(day),
+        StatsInterval::Week => Bson::Document(doc! { "$dateToString": {
+            "format": "%Y-%m-%d",
+            "date": { "$dateTrunc": {
+                "date": { "$dateFromString": { "dateString": day, "format": "%Y-%m-%d", "onError": Bson::Null } },
+                "unit": "week",
+                "startOfWeek": "monday",
+            } },
+        } }),
+    }
+}
+
+fn number(document: &Document, key: &str) -> f64 {
+    match document.get(key) {
+        Some(Bson::Int32(n)) => *n as f64,
+        Some(Bson::Int64(n)) => *n as f64,
+        Some(Bson::Double(n)) => *n,
+        _ => 0.0,
+    }
+}
+
+fn facet<'a>(result: &'a Document, name: &str) -> Vec<&'a Document> {
+    result
+        .get_array(name)
+        .map(|items| items.iter().filter_map(Bson::as_document).collect())
+        .unwrap_or_default()
+}
+
+/// Every period between `from` and `to`, so days or weeks without activity show up as zeros.
+fn empty_series(from: NaiveDate, to: NaiveDate, interval: StatsInterval) -> BTreeMap<String, PeriodCounts> {
+    let (mut period, step) = match interval {
+        StatsInterval::Day => (from, Days::new(1)),
+        StatsInterval::Week => {
+            let monday = from.checked_sub_days(Days::new(from.weekday().num_days_from_monday() as u64));
+            (monday.unwrap_or(from), Days::new(7))
+        }
+    };
+
+    let mut series = BTreeMap::new();
+    while period <= to {
+        series.insert(period.to_string(), PeriodCounts { period: period.to_string(), ..Default::default() });
+        period = match period.checked_add_days(step) {
+            Some(next) => next,
+            None => break,
+        };
+    }
+    series
+}
+
+/// Dashboard numbers for the caller's tasks, computed in a single aggregation.
+pub async fn get_task_stats(
+    auth: AuthenticatedUser,
+    query: web::Query<StatsQuery>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let today = Utc::now().date_naive();
+    let to = query.to.unwrap_or(today);
+    let from = match query.from.or_else(|| to.checked_sub_days(Days::new(DEFAULT_RANGE_DAYS - 1))) {
+        Some(from) => from,
+        None => return HttpResponse::BadRequest().body("to is out of range"),
+    };
+    if from > to {
+        return HttpResponse::BadRequest().body("from must not be after to");
+    }
+    if (to - from).num_days() >= MAX_RANGE_DAYS {
+        return HttpResponse::BadRequest().body(format!("The range cannot be longer than {} days", MAX_RANGE_DAYS));
+    }
+    let interval = query.interval;
+
+    let mut filter = doc! { "user_id": &auth.0, "deleted_at": Bson::Null };
+    if let Some(project_id) = &query.project_id {
+        filter.insert("project_id", project_id);
+    }
+    if let Some(label) = &query.label {
+        filter.insert("labels", label);
+    }
+
+    // Stored timestamps start with the day, so a day range is a string range.
+    let range = doc! {
+        "$gte": from.to_string(),
+        "$lt": to.checked_add_days(Days::new(1)).unwrap_or(to).to_string(),
+    };
+    let is_set = |field: &str| doc! { "$eq": [{ "$type": field }, "string"] };
+    let now = Utc::now().to_string();
+
+    let pipeline = vec![
+        doc! { "$match": filter },
+        doc! { "$facet": {
+            "by_status": [
+                { "$group": { "_id": "$status", "count": { "$sum": 1 } } },
+                { "$sort": { "_id": 1 } },
+            ],
+            "totals": [
+                { "$group": {
+                    "_id": Bson::Null,
+                    "total": { "$sum": 1 },
+                    "completed": { "$sum": { "$cond": [is_set("$completed_at"), 1, 0] } },
+                    "overdue": { "$sum": { "$cond": [
+                        { "$and": [
+                            is_set("$due_at"),
+                            { "$lt": ["$due_at", &now] },
+                            { "$not": [is_set("$completed_at")] },
+                        ] },
+                        1,
+                        0,
+                    ] } },
+                } },
+            ],
+            "created": [
+                { "$match": { "created_at": range.clone() } },
+                { "$group": { "_id": period_key("$created_at", interval), "count": { "$sum": 1 } } },
+            ],
+            "completed": [
+                { "$match": { "completed_at": range.clone() } },
+                { "$group": { "_id": period_key("$completed_at", interval), "count": { "$sum": 1 } } },
+            ],
+            "cycle_time": [
+                { "$match": { "completed_at": range.clone() } },
+                { "$group": {
+                    "_id": Bson::Null,
+                    "average_ms": { "$avg": { "$subtract": [parse_timestamp("$completed_at"), parse_timestamp("$created_at")] } },
+                } },
+            ],
+        } },
+    ];
+
+    let results: Vec<Document> = match client
+        .database("rust_backend")
+        .collection::<Task>("tasks")
+        .aggregate(pipeline)
+        .await
+    {
+        Ok(cursor) => match cursor.try_collect().await {
+            Ok(docs) => docs,
+            Err(e) => return HttpResponse::InternalServerError().body(format!("Error reading stats: {}", e)),
+        },
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+    let Some(result) = results.first() else {
+        return HttpResponse::InternalServerError().body("Aggregation returned no result");
+    };
+
+    let by_status = facet(result, "by_status")
+        .into_iter()
+        .filter_map(|group| {
+            Some(StatusCount { status: group.get_str("_id").ok()?.to_string(), count: number(group, "count") as u64 })
+        })
+        .collect();
+
+    let totals = facet(result, "totals").first().map(|t| (*t).clone()).unwrap_or_default();
+    let total = number(&totals, "total") as u64;
+    let completed = number(&totals, "completed") as u64;
+
+    let mut series = empty_series(from, to, interval);
+    for (name, is_created) in [("created", true), ("completed", false)] {
+        for group in facet(result, name) {
+            let Ok(period) = group.get_str("_id") else { continue };
+            let entry = series
+                .entry(period.to_string())
+                .or_insert_with(|| PeriodCounts { period: period.to_string(), ..Default::default() });
+            if is_created {
+                entry.created += number(group, "count") as u64;
+            } else {
+                entry.completed += number(group, "count") as u64;
+            }
+        }
+    }
+
+    let average_cycle_time_seconds = facet(result, "cycle_time")
+        .first()
+        .and_then(|c| match c.get("average_ms") {
+            Some(Bson::Double(ms)) => Some(ms / 1000.0),
+            _ => None,
+        });
+
+    HttpResponse::Ok().json(TaskStats {
+        total,
+        completed,
+        completion_rate: if total > 0 { completed as f64 / total as f64 } else { 0.0 },
+        overdue: number(&totals, "overdue") as u64,
+        by_status,
+        from,
+        to,
+        interval,
+        series: series.into_values().collect(),
+        average_cycle_time_seconds,
+    })
+}
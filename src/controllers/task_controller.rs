@@ -1,45 +1,461 @@
-use actix_web::{web, HttpResponse, Responder};
-use mongodb::{bson::{doc, Bson}, Client};
+use actix_web::{http::{header::{ETag, EntityTag, IfMatch, CONTENT_TYPE}, StatusCode}, web, HttpRequest, HttpResponse, Responder};
+use mongodb::{bson::{doc, Bson, Document}, options::ReturnDocument, Client};
 use mongodb::bson::oid::ObjectId;
 use futures::stream::TryStreamExt;
-use chrono::Utc;
-use crate::models::task_model::{NewTask, Task, TaskResponse, UpdateTask, TaskStatus};
+use chrono::{DateTime, Utc};
+use crate::controllers::attachment_controller::delete_task_attachments;
+use crate::controllers::board_controller::{end_of_column_rank, write_within_wip_limit, WipCheck};
+use crate::controllers::custom_field_controller::{check_custom_values, custom_query_value, retain_defined_fields};
+use crate::controllers::history_controller::record_history;
+use crate::controllers::time_controller::{delete_task_time_entries, stop_running_timer};
+use crate::controllers::workspace_controller::find_workspace;
+use crate::controllers::notification_controller::notify_task_changes;
+use crate::controllers::webhook_controller::enqueue_task_events;
+use crate::controllers::event_controller::record_task_event;
+use crate::controllers::project_controller::{archived_project_ids, find_project};
+use crate::controllers::workflow_controller::{default_workflow, find_workflow};
+use crate::models::custom_field_model::FieldValue;
+use crate::models::task_model::{
+    NewTask, PatchableTask, Task, TaskListQuery, TaskQuery, TaskResponse, TaskScope, UpdateTask, UpdateTaskQuery,
+};
+use crate::models::project_model::Project;
+use crate::models::view_model::SortDirection;
+use crate::models::workflow_model::{StatusCategory, Workflow};
+use crate::models::comment_model::Comment;
+use crate::models::history_model::{diff_fields, FieldChange, HistoryAction};
+use crate::storage::Storage;
+use crate::utils::auth::AuthenticatedUser;
+use crate::utils::markdown::MAX_DESCRIPTION_LEN;
 
-pub async fn create_task(
-    task: web::Json<NewTask>,
-    client: web::Data<Client>,
-) -> impl Responder {
+/// Looks up a task owned by `user_id`; malformed ids are treated as not found.
+///
+/// Every task endpoint goes through this, so only the owner reads a task by id or
+/// changes it. Assignees and creators see it in their `scope=assigned` and
+/// `scope=created` listings but can't edit it.
+pub async fn find_task(
+    client: &Client,
+    user_id: &str,
+    task_id: &str,
+) -> mongodb::error::Result<Option<Task>> {
+    let oid = match ObjectId::parse_str(task_id) {
+        Ok(o) => o,
+        Err(_) => return Ok(None),
+    };
+
+    client
+        .database("rust_backend")
+        .collection::<Task>("tasks")
+        .find_one(doc! { "_id": Bson::ObjectId(oid), "user_id": user_id, "deleted_at": Bson::Null })
+        .await
+}
+
+pub fn task_etag(task: &Task) -> ETag {
+    ETag(EntityTag::new_strong(task.version.to_string()))
+}
+
+/// Whether an optional `If-Match` header matches the task's current version.
+pub fn if_match_satisfied(if_match: &Option<web::Header<IfMatch>>, task: &Task) -> bool {
+    let current = EntityTag::new_strong(task.version.to_string());
+    match if_match.as_deref() {
+        None | Some(IfMatch::Any) => true,
+        Some(IfMatch::Items(tags)) => tags.iter().any(|tag| tag.strong_eq(&current)),
+    }
+}
+
+/// `412 Precondition Failed` carrying the current task so the client can merge.
+pub fn precondition_failed(current: Task) -> HttpResponse {
+    HttpResponse::PreconditionFailed()
+        .insert_header(task_etag(&current))
+        .json(TaskResponse::from(current))
+}
+
+/// Answers a version-guarded write that matched nothing because the task changed
+/// (or vanished) after it was read.
+pub async fn concurrent_write_failed(client: &Client, user_id: &str, task_id: &str, conditional: bool) -> HttpResponse {
+    match find_task(client, user_id, task_id).await {
+        Ok(Some(current)) if conditional => precondition_failed(current),
+        Ok(Some(_)) => HttpResponse::Conflict().body("Task was modified concurrently, please retry"),
+        Ok(None) => HttpResponse::NotFound().body("Task not found"),
+        Err(err) => HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    }
+}
+
+/// Records a history entry and sends the notifications it implies; the task change
+/// itself already succeeded, so failures are only logged.
+pub async fn log_history(client: &Client, task: &Task, actor_id: &str, action: HistoryAction, changes: Vec<FieldChange>) {
+    notify_task_changes(client, task, actor_id, &action, &changes).await;
+    enqueue_task_events(client, task, actor_id, &action, &changes).await;
+    record_task_event(client, task, actor_id, &action, &changes).await;
+
+    let task_id = task.id.as_deref().unwrap_or_default();
+    if let Err(err) = record_history(client, task_id, &task.user_id, actor_id, action, changes).await {
+        eprintln!("❌ failed to record history for task {}: {}", task_id, err);
+    }
+}
+
+const ACCEPT_PATCH: &str = "application/merge-patch+json, application/json-patch+json";
+
+/// A validation or lookup failure shared by the single-task and batch endpoints.
+#[derive(Debug)]
+pub struct TaskError {
+    pub status: StatusCode,
+    pub message: String,
+}
+
+impl TaskError {
+    pub fn bad_request(message: impl Into<String>) -> Self {
+        TaskError { status: StatusCode::BAD_REQUEST, message: message.into() }
+    }
+
+    pub fn not_found() -> Self {
+        TaskError { status: StatusCode::NOT_FOUND, message: "Task not found".to_string() }
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        TaskError { status: StatusCode::INTERNAL_SERVER_ERROR, message: message.into() }
+    }
+}
+
+impl From<TaskError> for HttpResponse {
+    fn from(err: TaskError) -> Self {
+        HttpResponse::build(err.status).body(err.message)
+    }
+}
+
+/// Trims the entries of a label, id or option list and drops blanks and duplicates, keeping order.
+pub(crate) fn normalize_list(items: &[String]) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for item in items.iter().map(|i| i.trim()).filter(|i| !i.is_empty()) {
+        if !normalized.iter().any(|i| i == item) {
+            normalized.push(item.to_string());
+        }
+    }
+    normalized
+}
+
+/// Validates and de-duplicates assignees: workspace tasks accept workspace members,
+/// personal tasks only their owner.
+pub async fn check_assignees(
+    client: &Client,
+    owner_id: &str,
+    workspace_id: Option<&str>,
+    assignee_ids: &[String],
+) -> Result<Vec<String>, TaskError> {
+    let assignees = normalize_list(assignee_ids);
+
+    let members = match workspace_id {
+        Some(workspace_id) => find_workspace(client, owner_id, workspace_id)
+            .await
+            .map_err(|err| TaskError::internal(format!("Database error: {}", err)))?
+            .ok_or_else(|| TaskError::bad_request("Workspace not found"))?
+            .member_ids,
+        None => vec![owner_id.to_string()],
+    };
+
+    match assignees.iter().find(|a| !members.contains(a)) {
+        Some(outsider) if workspace_id.is_some() => {
+            Err(TaskError::bad_request(format!("User {} is not a member of the task's workspace", outsider)))
+        }
+        Some(_) => Err(TaskError::bad_request("Tasks outside a workspace can only be assigned to their owner")),
+        None => Ok(assignees),
+    }
+}
+
+async fn ensure_project(client: &Client, user_id: &str, project_id: &str) -> Result<Project, TaskError> {
+    find_project(client, user_id, project_id)
+        .await
+        .map_err(|err| TaskError::internal(format!("Database error: {}", err)))?
+        .ok_or_else(|| TaskError::bad_request("Project not found"))
+}
+
+pub(crate) fn check_description(description: Option<&str>) -> Result<(), TaskError> {
+    match description {
+        Some(d) if d.len() > MAX_DESCRIPTION_LEN => {
+            Err(TaskError::bad_request(format!("Description must be at most {} bytes", MAX_DESCRIPTION_LEN)))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Validates a `NewTask` and builds the document to insert. `user_id` creates the task;
+/// it owns it too unless the task is in a workspace and names another member as owner,
+/// in which case the workflow, project and parent must be ones that member can use.
+pub async fn prepare_new_task(client: &Client, user_id: &str, task: &NewTask) -> Result<Task, TaskError> {
     if task.title.trim().is_empty() {
-        return HttpResponse::BadRequest().body("Title is required");
+        return Err(TaskError::bad_request("Title is required"));
     }
+    check_description(task.description.as_deref())?;
 
-    let database = client.database("rust_backend");
-    let collection = database.collection::<Task>("tasks");
+    let owner_id = match (&task.owner_id, task.workspace_id.as_deref()) {
+        (Some(owner_id), _) if owner_id == user_id => user_id,
+        (Some(owner_id), Some(workspace_id)) => {
+            check_assignees(client, user_id, Some(workspace_id), std::slice::from_ref(owner_id))
+                .await
+                .map_err(|err| TaskError { message: format!("Owner: {}", err.message), ..err })?;
+            owner_id.as_str()
+        }
+        (Some(_), None) => return Err(TaskError::bad_request("Only workspace tasks can be owned by someone else")),
+        (None, _) => user_id,
+    };
+
+    let workflow = match &task.workflow_id {
+        Some(workflow_id) => find_workflow(client, owner_id, workflow_id)
+            .await
+            .map_err(|err| TaskError::internal(format!("Database error: {}", err)))?
+            .ok_or_else(|| TaskError::bad_request("Workflow not found"))?,
+        None => default_workflow(client, owner_id)
+            .await
+            .map_err(|err| TaskError::internal(format!("Database error: {}", err)))?,
+    };
+
+    let project = match &task.project_id {
+        Some(project_id) => {
+            // The creator must be able to use the project as well as the owner.
+            if owner_id != user_id {
+                ensure_project(client, user_id, project_id).await?;
+            }
+            Some(ensure_project(client, owner_id, project_id).await?)
+        }
+        None => None,
+    };
+    let mut custom_fields = task.custom_fields.clone().unwrap_or_default();
+    let values = custom_fields.iter().map(|(k, v)| (k.clone(), Some(v.clone()))).collect();
+    check_custom_values(client, project.as_ref(), &values).await?;
+    for value in custom_fields.values_mut() {
+        if let FieldValue::List(items) = value {
+            *items = normalize_list(items);
+        }
+    }
+
+    if let Some(parent_id) = &task.parent_id {
+        let parent = find_task(client, owner_id, parent_id)
+            .await
+            .map_err(|err| TaskError::internal(format!("Database error: {}", err)))?;
+        // Another member's task only takes subtasks from within the same workspace.
+        if parent.is_none_or(|p| owner_id != user_id && p.workspace_id != task.workspace_id) {
+            return Err(TaskError::bad_request("Parent task not found"));
+        }
+    }
+
+    let assignee_ids = match &task.assignee_ids {
+        Some(ids) => ids.clone(),
+        None => vec![owner_id.to_string()],
+    };
+    let assignee_ids = check_assignees(client, user_id, task.workspace_id.as_deref(), &assignee_ids).await?;
+
+    let status = match &task.status {
+        Some(status) if workflow.status(status).is_some() => status.clone(),
+        Some(status) => return Err(TaskError::bad_request(format!("Unknown status: {}", status))),
+        None => match workflow.initial_status() {
+            Some(s) => s.name.clone(),
+            None => return Err(TaskError::internal("Workflow has no statuses")),
+        },
+    };
+
+    let done = workflow.status(&status).is_some_and(|s| s.category == StatusCategory::Done);
+    let workflow_id = workflow.id.clone().unwrap_or_default();
+    let rank = end_of_column_rank(client, owner_id, &workflow_id, &status).await?;
 
     let now = Utc::now();
-    let new_task = Task {
+    Ok(Task {
         id: None,
         title: task.title.clone(),
         description: task.description.clone(),
-        status: task.status.as_ref().cloned().unwrap_or(TaskStatus::Pending),
-        user_id: task.user_id.clone(),
+        status,
+        workflow_id,
+        labels: normalize_list(task.labels.as_deref().unwrap_or_default()),
+        priority: task.priority.unwrap_or_default(),
+        project_id: task.project_id.clone(),
+        parent_id: task.parent_id.clone(),
+        rank,
+        user_id: owner_id.to_string(),
+        creator_id: user_id.to_string(),
+        assignee_ids,
+        workspace_id: task.workspace_id.clone(),
+        due_at: task.due_at,
+        custom_fields,
+        due_soon_notified: false,
+        version: 1,
+        ical_uid: None,
+        caldav_name: None,
         created_at: now,
         updated_at: now,
+        completed_at: done.then_some(now),
+        deleted_at: None,
+    })
+}
+
+/// Applies `update` to a copy of `task` without any workflow checks and returns the
+/// `$set` document together with the task as it will look afterwards.
+pub fn apply_task_update(task: &Task, update: &UpdateTask, now: DateTime<Utc>) -> Result<(Document, Task), TaskError> {
+    let mut updated = task.clone();
+    updated.updated_at = now;
+    updated.version = task.version + 1;
+
+    let mut update_doc = doc! { "updated_at": now.to_string() };
+    if let Some(title) = &update.title {
+        if title.trim().is_empty() {
+            return Err(TaskError::bad_request("Title is required"));
+        }
+        update_doc.insert("title", title);
+        updated.title = title.clone();
+    }
+    if let Some(description) = &update.description {
+        check_description(description.as_deref())?;
+        update_doc.insert("description", description.clone());
+        updated.description = description.clone();
+    }
+    if let Some(labels) = &update.labels {
+        updated.labels = normalize_list(labels);
+        update_doc.insert("labels", &updated.labels);
+    }
+    if let Some(priority) = update.priority {
+        update_doc.insert("priority", mongodb::bson::to_bson(&priority).map_err(|e| TaskError::internal(e.to_string()))?);
+        updated.priority = priority;
+    }
+    if let Some(status) = &update.status {
+        update_doc.insert("status", status);
+        updated.status = status.clone();
+    }
+    if let Some(project_id) = &update.project_id {
+        update_doc.insert("project_id", project_id.clone());
+        updated.project_id = project_id.clone();
+    }
+    if let Some(assignee_ids) = &update.assignee_ids {
+        updated.assignee_ids = normalize_list(assignee_ids);
+        update_doc.insert("assignee_ids", &updated.assignee_ids);
+    }
+    if let Some(due_at) = &update.due_at {
+        update_doc.insert("due_at", due_at.map(|d| d.to_string()));
+        update_doc.insert("due_soon_notified", false);
+        updated.due_at = *due_at;
+        updated.due_soon_notified = false;
+    }
+    if let Some(values) = &update.custom_fields {
+        for (field_id, value) in values {
+            match value {
+                Some(FieldValue::List(items)) => {
+                    updated.custom_fields.insert(field_id.clone(), FieldValue::List(normalize_list(items)));
+                }
+                Some(value) => {
+                    updated.custom_fields.insert(field_id.clone(), value.clone());
+                }
+                None => {
+                    updated.custom_fields.remove(field_id);
+                }
+            }
+        }
+        update_doc.insert(
+            "custom_fields",
+            mongodb::bson::to_bson(&updated.custom_fields).map_err(|e| TaskError::internal(e.to_string()))?,
+        );
+    }
+
+    Ok((update_doc, updated))
+}
+
+pub fn check_transition(workflow: &Workflow, from: &str, to: &str) -> Result<(), TaskError> {
+    if workflow.status(to).is_none() {
+        return Err(TaskError::bad_request(format!("Unknown status: {}", to)));
+    }
+    if !workflow.can_transition(from, to) {
+        return Err(TaskError::bad_request(format!("Transition from {} to {} is not allowed", from, to)));
+    }
+    Ok(())
+}
+
+/// Validates `update` against the current `task`, including its workflow, and applies it.
+pub async fn prepare_task_update(
+    client: &Client,
+    user_id: &str,
+    task: &Task,
+    update: &UpdateTask,
+) -> Result<(Document, Task), TaskError> {
+    let workflow = match &update.status {
+        Some(status) => {
+            let workflow = find_workflow(client, user_id, &task.workflow_id)
+                .await
+                .map_err(|err| TaskError::internal(format!("Database error: {}", err)))?
+                .ok_or_else(|| TaskError::internal("Task workflow not found"))?;
+
+            check_transition(&workflow, &task.status, status)?;
+            Some(workflow)
+        }
+        None => None,
     };
+    // The project the task will be in, loaded when custom fields need checking.
+    let project = match (&update.project_id, &task.project_id) {
+        (Some(Some(project_id)), _) => Some(ensure_project(client, user_id, project_id).await?),
+        (None, Some(project_id)) if update.custom_fields.is_some() => find_project(client, user_id, project_id)
+            .await
+            .map_err(|err| TaskError::internal(format!("Database error: {}", err)))?,
+        _ => None,
+    };
+    if let Some(values) = &update.custom_fields {
+        check_custom_values(client, project.as_ref(), values).await?;
+    }
+    if let Some(assignee_ids) = &update.assignee_ids {
+        check_assignees(client, user_id, task.workspace_id.as_deref(), assignee_ids).await?;
+    }
 
-    match collection.insert_one(new_task).await {
+    let (mut update_doc, mut updated) = apply_task_update(task, update, Utc::now())?;
+    if let Some(workflow) = &workflow {
+        track_completion(workflow, &mut update_doc, &mut updated);
+    }
+    // A task moved to another column goes to its end instead of keeping its old rank.
+    if updated.status != task.status || updated.workflow_id != task.workflow_id {
+        updated.rank = end_of_column_rank(client, user_id, &updated.workflow_id, &updated.status).await?;
+        update_doc.insert("rank", &updated.rank);
+    }
+    // Values of fields the new project doesn't define don't carry over.
+    if update.project_id.is_some() && retain_defined_fields(&mut updated, project.as_ref()) {
+        update_doc.insert(
+            "custom_fields",
+            mongodb::bson::to_bson(&updated.custom_fields).map_err(|e| TaskError::internal(e.to_string()))?,
+        );
+    }
+    Ok((update_doc, updated))
+}
+
+/// Keeps `completed_at` in step with whether the task's status is a `Done` one.
+/// Returns whether it changed.
+pub fn track_completion(workflow: &Workflow, update_doc: &mut Document, task: &mut Task) -> bool {
+    let done = workflow.status(&task.status).is_some_and(|s| s.category == StatusCategory::Done);
+    match (done, task.completed_at) {
+        (true, None) => {
+            task.completed_at = Some(task.updated_at);
+            update_doc.insert("completed_at", task.updated_at.to_string());
+            true
+        }
+        (false, Some(_)) => {
+            task.completed_at = None;
+            update_doc.insert("completed_at", Bson::Null);
+            true
+        }
+        _ => false,
+    }
+}
+
+pub async fn create_task(
+    auth: AuthenticatedUser,
+    task: web::Json<NewTask>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let mut new_task = match prepare_new_task(&client, &auth.0, &task).await {
+        Ok(t) => t,
+        Err(err) => return err.into(),
+    };
+
+    let database = client.database("rust_backend");
+    let collection = database.collection::<Task>("tasks");
+
+    match collection.insert_one(&new_task).await {
         Ok(insert_result) => {
             if let Some(oid) = insert_result.inserted_id.as_object_id() {
-                let response = TaskResponse {
-                    id: Some(oid.to_hex()),
-                    title: task.title.clone(),
-                    description: task.description.clone(),
-                    status: task.status.as_ref().cloned().unwrap_or(TaskStatus::Pending),
-                    user_id: task.user_id.clone(),
-                    created_at: now,
-                    updated_at: now,
-                };
-                HttpResponse::Ok().json(response)
+                new_task.id = Some(oid.to_hex());
+                let changes = diff_fields(&[], &new_task.tracked_fields());
+                log_history(&client, &new_task, &new_task.creator_id, HistoryAction::Created, changes).await;
+                HttpResponse::Ok().insert_header(task_etag(&new_task)).json(TaskResponse::from(new_task))
             } else {
                 HttpResponse::InternalServerError().body("Failed to get inserted ID")
             }
@@ -48,28 +464,81 @@ pub async fn create_task(
     }
 }
 
+/// Matches the tasks `user_id` owns, is assigned to or created.
+pub fn scope_filter(scope: TaskScope, user_id: &str) -> Document {
+    match scope {
+        TaskScope::Owned => doc! { "user_id": user_id },
+        TaskScope::Assigned => doc! { "assignee_ids": user_id },
+        TaskScope::Created => doc! { "creator_id": user_id },
+    }
+}
+
+/// Lists the caller's tasks, optionally for one project. Tasks of archived projects
+/// are left out unless `include_archived` is set or the project is asked for explicitly.
 pub async fn get_tasks(
-    user_id: web::Path<String>,
+    auth: AuthenticatedUser,
+    query: web::Query<TaskListQuery>,
     client: web::Data<Client>,
 ) -> impl Responder {
     let database = client.database("rust_backend");
     let collection = database.collection::<Task>("tasks");
 
-    let cursor = match collection.find(doc! { "user_id": user_id.as_str() }).await {
+    let mut filter = scope_filter(query.scope, &auth.0);
+    filter.insert("deleted_at", Bson::Null);
+    let mut sort = None;
+    if let Some(project_id) = &query.project_id {
+        filter.insert("project_id", project_id);
+
+        if query.custom_field.is_some() || query.sort_by_field.is_some() {
+            let project = match find_project(&client, &auth.0, project_id).await {
+                Ok(Some(p)) => p,
+                Ok(None) => return HttpResponse::BadRequest().body("Project not found"),
+                Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+            };
+            if let Some(field_id) = &query.custom_field {
+                let Some(field) = project.custom_field(field_id) else {
+                    return HttpResponse::BadRequest().body(format!("Unknown custom field: {}", field_id));
+                };
+                let Some(raw) = &query.custom_value else {
+                    return HttpResponse::BadRequest().body("custom_value is required with custom_field");
+                };
+                match custom_query_value(field, raw) {
+                    Ok(value) => filter.insert(format!("custom_fields.{}", field_id), value),
+                    Err(message) => return HttpResponse::BadRequest().body(message),
+                };
+            }
+            if let Some(field_id) = &query.sort_by_field {
+                if project.custom_field(field_id).is_none() {
+                    return HttpResponse::BadRequest().body(format!("Unknown custom field: {}", field_id));
+                }
+                let direction = if query.order == SortDirection::Desc { -1 } else { 1 };
+                sort = Some(doc! { format!("custom_fields.{}", field_id): direction, "_id": 1 });
+            }
+        }
+    } else if query.custom_field.is_some() || query.sort_by_field.is_some() {
+        return HttpResponse::BadRequest().body("Custom field filters and sorting need a project_id");
+    } else if !query.include_archived.unwrap_or(false) {
+        let archived = match archived_project_ids(&client, &auth.0).await {
+            Ok(ids) => ids,
+            Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+        };
+        if !archived.is_empty() {
+            filter.insert("project_id", doc! { "$nin": archived });
+        }
+    }
+
+    let mut find = collection.find(filter);
+    if let Some(sort) = sort {
+        find = find.sort(sort);
+    }
+    let cursor = match find.await {
         Ok(c) => c,
         Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
     };
 
     let tasks: Vec<TaskResponse> = match cursor.try_collect::<Vec<_>>().await {
-        Ok(docs) => docs.into_iter().map(|task| TaskResponse {
-            id: task.id,
-            title: task.title,
-            description: task.description,
-            status: task.status,
-            user_id: task.user_id,
-            created_at: task.created_at,
-            updated_at: task.updated_at,
-        }).collect(),
+        Ok(docs) if query.html.unwrap_or(false) => docs.into_iter().map(|t| TaskResponse::from(t).with_html()).collect(),
+        Ok(docs) => docs.into_iter().map(TaskResponse::from).collect(),
         Err(e) => return HttpResponse::InternalServerError().body(format!("Error reading tasks: {}", e)),
     };
 
@@ -77,104 +546,299 @@ pub async fn get_tasks(
 }
 
 pub async fn get_task(
-    path: web::Path<(String, String)>,
+    auth: AuthenticatedUser,
+    task_id: web::Path<String>,
+    query: web::Query<TaskQuery>,
     client: web::Data<Client>,
 ) -> impl Responder {
-    let (user_id, task_id) = path.into_inner();
-
     let database = client.database("rust_backend");
     let collection = database.collection::<Task>("tasks");
 
-    let oid = match ObjectId::parse_str(&task_id) {
+    let oid = match ObjectId::parse_str(task_id.as_str()) {
         Ok(o) => o,
         Err(_) => return HttpResponse::BadRequest().body("Invalid task ID"),
     };
 
-    match collection.find_one(doc! { "_id": Bson::ObjectId(oid), "user_id": user_id }).await {
+    // Assignees can read tasks they don't own.
+    let filter = doc! {
+        "_id": Bson::ObjectId(oid),
+        "deleted_at": Bson::Null,
+        "$or": [{ "user_id": &auth.0 }, { "assignee_ids": &auth.0 }],
+    };
+
+    match collection.find_one(filter).await {
         Ok(Some(task)) => {
-            let response = TaskResponse {
-                id: task.id,
-                title: task.title,
-                description: task.description,
-                status: task.status,
-                user_id: task.user_id,
-                created_at: task.created_at,
-                updated_at: task.updated_at,
-            };
-            HttpResponse::Ok().json(response)
+            let response = TaskResponse::from(task.clone());
+            let response = if query.html.unwrap_or(false) { response.with_html() } else { response };
+            HttpResponse::Ok().insert_header(task_etag(&task)).json(response)
         }
         Ok(None) => HttpResponse::NotFound().body("Task not found"),
         Err(err) => HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
     }
 }
 
+/// Validates `update` and writes it, guarded by the version of `task` that was read.
+/// Returns the task as stored afterwards.
+pub async fn save_task_update(
+    client: &Client,
+    user_id: &str,
+    task: &Task,
+    update: &UpdateTask,
+    conditional: bool,
+) -> Result<Task, HttpResponse> {
+    let task_id = task.id.as_deref().unwrap_or_default();
+    let oid = ObjectId::parse_str(task_id).map_err(|_| HttpResponse::BadRequest().body("Invalid task ID"))?;
+
+    let (update_doc, updated) = prepare_task_update(client, user_id, task, update).await?;
+    let wip = if updated.status != task.status { WipCheck::load(client, &updated).await? } else { None };
+
+    let collection = client.database("rust_backend").collection::<Task>("tasks");
+    let filter = doc! { "_id": Bson::ObjectId(oid), "user_id": user_id, "deleted_at": Bson::Null, "version": task.version };
+    if !write_within_wip_limit(&collection, filter, task, update_doc, wip.as_ref()).await? {
+        return Err(concurrent_write_failed(client, user_id, task_id, conditional).await);
+    }
+
+    let changes = diff_fields(&task.tracked_fields(), &updated.tracked_fields());
+    if !changes.is_empty() {
+        log_history(client, &updated, user_id, HistoryAction::Updated, changes).await;
+    }
+    Ok(updated)
+}
+
 pub async fn update_task(
-    path: web::Path<(String, String)>,
+    auth: AuthenticatedUser,
+    task_id: web::Path<String>,
+    if_match: Option<web::Header<IfMatch>>,
+    query: web::Query<UpdateTaskQuery>,
     update: web::Json<UpdateTask>,
     client: web::Data<Client>,
 ) -> impl Responder {
-    let (user_id, task_id) = path.into_inner();
+    let user_id = auth.0;
 
     let database = client.database("rust_backend");
     let collection = database.collection::<Task>("tasks");
 
-    let oid = match ObjectId::parse_str(&task_id) {
+    let oid = match ObjectId::parse_str(task_id.as_str()) {
         Ok(o) => o,
         Err(_) => return HttpResponse::BadRequest().body("Invalid task ID"),
     };
 
-    let mut update_doc = doc! { "updated_at": Utc::now().to_rfc3339() };
-    if let Some(title) = &update.title {
-        update_doc.insert("title", title);
+    let task = match collection.find_one(doc! { "_id": Bson::ObjectId(oid), "user_id": &user_id, "deleted_at": Bson::Null }).await {
+        Ok(Some(t)) => t,
+        Ok(None) => return HttpResponse::NotFound().body("Task not found"),
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    };
+
+    if !if_match_satisfied(&if_match, &task) {
+        return precondition_failed(task);
     }
-    if let Some(description) = &update.description {
-        update_doc.insert("description", description);
+
+    let updated = match save_task_update(&client, &user_id, &task, &update, if_match.is_some()).await {
+        Ok(updated) => updated,
+        Err(response) => return response,
+    };
+
+    if query.stop_timer.unwrap_or(false) && updated.status != task.status {
+        stop_timer_if_completed(&client, &user_id, &updated).await;
     }
-    if let Some(status) = &update.status {
-        update_doc.insert("status", match status {
-            TaskStatus::Pending => "Pending",
-            TaskStatus::InProgress => "InProgress",
-            TaskStatus::Completed => "Completed",
-        });
-    }
-
-    match collection.update_one(
-        doc! { "_id": Bson::ObjectId(oid), "user_id": user_id },
-        doc! { "$set": update_doc },
-    ).await {
-        Ok(update_result) => {
-            if update_result.modified_count > 0 {
-                HttpResponse::Ok().body("Task updated successfully")
-            } else {
-                HttpResponse::NotFound().body("Task not found")
+
+    HttpResponse::Ok().insert_header(task_etag(&updated)).json(TaskResponse::from(updated))
+}
+
+/// Stops the task's running timer if its new status is a `Done` one. The update has
+/// already been saved, so failures are only logged.
+async fn stop_timer_if_completed(client: &Client, user_id: &str, task: &Task) {
+    let completed = match find_workflow(client, user_id, &task.workflow_id).await {
+        Ok(Some(workflow)) => workflow.status(&task.status).is_some_and(|s| s.category == StatusCategory::Done),
+        Ok(None) => false,
+        Err(err) => {
+            eprintln!("❌ failed to load workflow {}: {}", task.workflow_id, err);
+            false
+        }
+    };
+    if !completed {
+        return;
+    }
+
+    let task_id = task.id.as_deref().unwrap_or_default();
+    if let Err(err) = stop_running_timer(client, user_id, Some(task_id)).await {
+        eprintln!("❌ failed to stop timer for task {}: {}", task_id, err);
+    }
+}
+
+/// Applies an RFC 7396 merge patch (`application/merge-patch+json`) or an RFC 6902
+/// JSON Patch (`application/json-patch+json`) to the editable fields of a task.
+pub async fn patch_task(
+    auth: AuthenticatedUser,
+    req: HttpRequest,
+    task_id: web::Path<String>,
+    if_match: Option<web::Header<IfMatch>>,
+    body: web::Bytes,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let user_id = auth.0;
+
+    let task = match find_task(&client, &user_id, &task_id).await {
+        Ok(Some(t)) => t,
+        Ok(None) => return HttpResponse::NotFound().body("Task not found"),
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    };
+
+    if !if_match_satisfied(&if_match, &task) {
+        return precondition_failed(task);
+    }
+
+    let mut document = match serde_json::to_value(PatchableTask::from(&task)) {
+        Ok(v) => v,
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Serialization error: {}", err)),
+    };
+
+    let content_type = req
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(';').next())
+        .map(|v| v.trim().to_ascii_lowercase())
+        .unwrap_or_default();
+
+    match content_type.as_str() {
+        "application/merge-patch+json" => {
+            let patch: serde_json::Value = match serde_json::from_slice(&body) {
+                Ok(p) => p,
+                Err(err) => return HttpResponse::BadRequest().body(format!("Invalid merge patch: {}", err)),
+            };
+            json_patch::merge(&mut document, &patch);
+        }
+        "application/json-patch+json" => {
+            let patch: json_patch::Patch = match serde_json::from_slice(&body) {
+                Ok(p) => p,
+                Err(err) => return HttpResponse::BadRequest().body(format!("Invalid JSON Patch: {}", err)),
+            };
+            if let Err(err) = json_patch::patch(&mut document, &patch) {
+                return HttpResponse::UnprocessableEntity().body(format!("Patch could not be applied: {}", err));
             }
         }
-        Err(err) => HttpResponse::InternalServerError().body(format!("Error updating task: {}", err)),
+        _ => {
+            return HttpResponse::UnsupportedMediaType()
+                .insert_header(("Accept-Patch", ACCEPT_PATCH))
+                .body("Use application/merge-patch+json or application/json-patch+json");
+        }
+    }
+
+    let patched: PatchableTask = match serde_json::from_value(document) {
+        Ok(p) => p,
+        Err(err) => return HttpResponse::UnprocessableEntity().body(format!("Patched task is invalid: {}", err)),
+    };
+
+    let update = UpdateTask {
+        title: (patched.title != task.title).then_some(patched.title),
+        description: (patched.description != task.description).then_some(patched.description),
+        status: (patched.status != task.status).then_some(patched.status),
+        labels: (patched.labels != task.labels).then_some(patched.labels),
+        priority: (patched.priority != task.priority).then_some(patched.priority),
+        project_id: (patched.project_id != task.project_id).then_some(patched.project_id),
+        assignee_ids: (patched.assignee_ids != task.assignee_ids).then_some(patched.assignee_ids),
+        due_at: (patched.due_at != task.due_at).then_some(patched.due_at),
+        custom_fields: (patched.custom_fields != task.custom_fields).then(|| {
+            let removed = task.custom_fields.keys().filter(|k| !patched.custom_fields.contains_key(*k));
+            removed
+                .map(|k| (k.clone(), None))
+                .chain(patched.custom_fields.iter().map(|(k, v)| (k.clone(), Some(v.clone()))))
+                .collect()
+        }),
+    };
+
+    match save_task_update(&client, &user_id, &task, &update, if_match.is_some()).await {
+        Ok(updated) => HttpResponse::Ok()
+            .insert_header(task_etag(&updated))
+            .json(TaskResponse::from(updated)),
+        Err(response) => response,
     }
 }
 
+/// Moves a task to the trash; `purge_task` removes it for good.
 pub async fn delete_task(
-    path: web::Path<(String, String)>,
+    auth: AuthenticatedUser,
+    task_id: web::Path<String>,
+    if_match: Option<web::Header<IfMatch>>,
     client: web::Data<Client>,
 ) -> impl Responder {
-    let (user_id, task_id) = path.into_inner();
-
     let database = client.database("rust_backend");
     let collection = database.collection::<Task>("tasks");
 
-    let oid = match ObjectId::parse_str(&task_id) {
+    let oid = match ObjectId::parse_str(task_id.as_str()) {
         Ok(o) => o,
         Err(_) => return HttpResponse::BadRequest().body("Invalid task ID"),
     };
 
-    match collection.delete_one(doc! { "_id": Bson::ObjectId(oid), "user_id": user_id }).await {
-        Ok(delete_result) => {
-            if delete_result.deleted_count > 0 {
-                HttpResponse::Ok().body("Task deleted successfully")
-            } else {
-                HttpResponse::NotFound().body("Task not found")
+    let task = match collection.find_one(doc! { "_id": Bson::ObjectId(oid), "user_id": &auth.0, "deleted_at": Bson::Null }).await {
+        Ok(Some(t)) => t,
+        Ok(None) => return HttpResponse::NotFound().body("Task not found"),
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    };
+
+    if !if_match_satisfied(&if_match, &task) {
+        return precondition_failed(task);
+    }
+
+    match trash_task(&client, &auth.0, &task, if_match.is_some()).await {
+        Ok(_) => HttpResponse::Ok().body("Task moved to trash"),
+        Err(response) => response,
+    }
+}
+
+/// Moves `task` to the trash, guarded by the version that was read, and stops its timer.
+/// Returns the task as stored afterwards.
+pub async fn trash_task(client: &Client, user_id: &str, task: &Task, conditional: bool) -> Result<Task, HttpResponse> {
+    let task_id = task.id.as_deref().unwrap_or_default();
+    let oid = ObjectId::parse_str(task_id).map_err(|_| HttpResponse::BadRequest().body("Invalid task ID"))?;
+
+    let collection = client.database("rust_backend").collection::<Task>("tasks");
+    let now = Utc::now();
+    match collection.find_one_and_update(
+        doc! { "_id": Bson::ObjectId(oid), "user_id": user_id, "deleted_at": Bson::Null, "version": task.version },
+        doc! { "$set": { "deleted_at": now.to_string() }, "$inc": { "version": 1 } },
+    ).return_document(ReturnDocument::After).await {
+        Ok(Some(task)) => {
+            let changes = vec![FieldChange {
+                field: "deleted_at".to_string(),
+                from: serde_json::Value::Null,
+                to: serde_json::json!(now),
+            }];
+            log_history(client, &task, user_id, HistoryAction::Trashed, changes).await;
+            // Like completing it, trashing a task ends the time spent on it.
+            if let Err(err) = stop_running_timer(client, user_id, Some(task_id)).await {
+                eprintln!("❌ failed to stop timer for task {}: {}", task_id, err);
             }
+            Ok(task)
         }
-        Err(err) => HttpResponse::InternalServerError().body(format!("Error deleting task: {}", err)),
+        Ok(None) => Err(concurrent_write_failed(client, user_id, task_id, conditional).await),
+        Err(err) => Err(HttpResponse::InternalServerError().body(format!("Error deleting task: {}", err))),
     }
-}
\ No newline at end of file
+}
+
+/// Permanently deletes a task together with its comments and attachments.
+/// The task history is kept for auditing.
+pub async fn purge_task(client: &Client, storage: &dyn Storage, task: &Task, actor_id: &str) -> Result<(), String> {
+    let task_id = task.id.as_deref().unwrap_or_default();
+    let oid = ObjectId::parse_str(task_id).map_err(|e| e.to_string())?;
+
+    let database = client.database("rust_backend");
+
+    delete_task_attachments(client, storage, task_id).await?;
+    delete_task_time_entries(client, task_id).await.map_err(|e| e.to_string())?;
+    database
+        .collection::<Comment>("comments")
+        .delete_many(doc! { "task_id": task_id })
+        .await
+        .map_err(|e| e.to_string())?;
+    database
+        .collection::<Task>("tasks")
+        .delete_one(doc! { "_id": Bson::ObjectId(oid) })
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let changes = diff_fields(&task.tracked_fields(), &[]);
+    log_history(client, task, actor_id, HistoryAction::Deleted, changes).await;
+    Ok(())
+}
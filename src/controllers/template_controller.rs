@@ -0,0 +1,428 @@
+use std::collections::HashMap;
+use actix_web::{web, HttpResponse, Responder};
+use mongodb::{bson::{doc, Bson}, Client};
+use mongodb::bson::oid::ObjectId;
+use futures::stream::TryStreamExt;
+use chrono::{Datelike, Utc};
+use crate::controllers::task_controller::{find_task, log_history, prepare_new_task, TaskError};
+use crate::models::history_model::{diff_fields, HistoryAction};
+use crate::models::task_model::{NewTask, Task, TaskResponse};
+use crate::models::template_model::{
+    InstantiateTemplate, NewTemplate, TaskTemplate, TaskTree, TemplateFromTask, TemplateSubtask,
+};
+use crate::utils::auth::AuthenticatedUser;
+use crate::utils::rank::rank_between;
+
+async fn find_template(
+    client: &Client,
+    user_id: &str,
+    template_id: &str,
+) -> mongodb::error::Result<Option<TaskTemplate>> {
+    let oid = match ObjectId::parse_str(template_id) {
+        Ok(o) => o,
+        Err(_) => return Ok(None),
+    };
+
+    client
+        .database("rust_backend")
+        .collection::<TaskTemplate>("templates")
+        .find_one(doc! { "_id": Bson::ObjectId(oid), "user_id": user_id })
+        .await
+}
+
+fn validate_template(template: &NewTemplate) -> Result<(), String> {
+    if template.name.trim().is_empty() {
+        return Err("Template name is required".to_string());
+    }
+    if template.title.trim().is_empty() {
+        return Err("Template title is required".to_string());
+    }
+    if template.subtasks.iter().any(|s| s.title.trim().is_empty()) {
+        return Err("Subtask title is required".to_string());
+    }
+    Ok(())
+}
+
+/// Replaces `{{name}}` placeholders. `date`, `week`, `month` and `year` are built in;
+/// `variables` add to and override them.
+fn render(text: &str, variables: &HashMap<String, String>) -> Result<String, TaskError> {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let end = after
+            .find("}}")
+            .ok_or_else(|| TaskError::bad_request("Unclosed {{ in template"))?;
+        let name = after[..end].trim();
+        let value = variables
+            .get(name)
+            .ok_or_else(|| TaskError::bad_request(format!("Unknown template variable: {}", name)))?;
+        out.push_str(value);
+        rest = &after[end + 2..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+fn template_variables(custom: &HashMap<String, String>) -> HashMap<String, String> {
+    let today = Utc::now().date_naive();
+    let week = today.iso_week();
+
+    let mut variables = HashMap::from([
+        ("date".to_string(), today.to_string()),
+        ("week".to_string(), format!("{}-W{:02}", week.year(), week.week())),
+        ("month".to_string(), today.format("%Y-%m").to_string()),
+        ("year".to_string(), today.year().to_string()),
+    ]);
+    variables.extend(custom.iter().map(|(k, v)| (k.clone(), v.clone())));
+    variables
+}
+
+pub async fn create_template(
+    auth: AuthenticatedUser,
+    template: web::Json<NewTemplate>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let template = template.into_inner();
+    if let Err(msg) = validate_template(&template) {
+        return HttpResponse::BadRequest().body(msg);
+    }
+
+    let now = Utc::now();
+    let new_template = TaskTemplate {
+        id: None,
+        user_id: auth.0,
+        name: template.name.trim().to_string(),
+        title: template.title,
+        description: template.description,
+        labels: template.labels,
+        priority: template.priority,
+        subtasks: template.subtasks,
+        created_at: now,
+        updated_at: now,
+    };
+
+    insert_template(&client, new_template).await
+}
+
+async fn insert_template(client: &Client, mut template: TaskTemplate) -> HttpResponse {
+    let collection = client.database("rust_backend").collection::<TaskTemplate>("templates");
+
+    match collection.insert_one(&template).await {
+        Ok(insert_result) => {
+            if let Some(oid) = insert_result.inserted_id.as_object_id() {
+                template.id = Some(oid.to_hex());
+                HttpResponse::Ok().json(template)
+            } else {
+                HttpResponse::InternalServerError().body("Failed to get inserted ID")
+            }
+        }
+        Err(err) => HttpResponse::InternalServerError().body(format!("Error inserting template: {}", err)),
+    }
+}
+
+/// Saves an existing task and its subtasks as a template.
+pub async fn create_template_from_task(
+    auth: AuthenticatedUser,
+    task_id: web::Path<String>,
+    body: web::Json<TemplateFromTask>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let task_id = task_id.into_inner();
+    if body.name.trim().is_empty() {
+        return HttpResponse::BadRequest().body("Template name is required");
+    }
+
+    let task = match find_task(&client, &auth.0, &task_id).await {
+        Ok(Some(t)) => t,
+        Ok(None) => return HttpResponse::NotFound().body("Task not found"),
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    };
+
+    let subtasks: Vec<Task> = match client
+        .database("rust_backend")
+        .collection::<Task>("tasks")
+        .find(doc! { "user_id": &auth.0, "parent_id": &task_id, "deleted_at": Bson::Null })
+        .sort(doc! { "rank": 1, "_id": 1 })
+        .await
+    {
+        Ok(cursor) => match cursor.try_collect().await {
+            Ok(docs) => docs,
+            Err(e) => return HttpResponse::InternalServerError().body(format!("Error reading tasks: {}", e)),
+        },
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    let now = Utc::now();
+    let template = TaskTemplate {
+        id: None,
+        user_id: auth.0,
+        name: body.name.trim().to_string(),
+        title: task.title,
+        description: task.description,
+        labels: task.labels,
+        priority: task.priority,
+        subtasks: subtasks
+            .into_iter()
+            .map(|s| TemplateSubtask { title: s.title, description: s.description })
+            .collect(),
+        created_at: now,
+        updated_at: now,
+    };
+
+    insert_template(&client, template).await
+}
+
+pub async fn get_templates(
+    auth: AuthenticatedUser,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let collection = client.database("rust_backend").collection::<TaskTemplate>("templates");
+
+    let cursor = match collection.find(doc! { "user_id": &auth.0 }).sort(doc! { "name": 1 }).await {
+        Ok(c) => c,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    match cursor.try_collect::<Vec<_>>().await {
+        Ok(templates) => HttpResponse::Ok().json(templates),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error reading templates: {}", e)),
+    }
+}
+
+pub async fn get_template(
+    auth: AuthenticatedUser,
+    template_id: web::Path<String>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    match find_template(&client, &auth.0, &template_id).await {
+        Ok(Some(template)) => HttpResponse::Ok().json(template),
+        Ok(None) => HttpResponse::NotFound().body("Template not found"),
+        Err(err) => HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    }
+}
+
+pub async fn update_template(
+    auth: AuthenticatedUser,
+    template_id: web::Path<String>,
+    update: web::Json<NewTemplate>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let update = update.into_inner();
+    if let Err(msg) = validate_template(&update) {
+        return HttpResponse::BadRequest().body(msg);
+    }
+
+    let mut template = match find_template(&client, &auth.0, &template_id).await {
+        Ok(Some(t)) => t,
+        Ok(None) => return HttpResponse::NotFound().body("Template not found"),
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    };
+    template.name = update.name.trim().to_string();
+    template.title = update.title;
+    template.description = update.description;
+    template.labels = update.labels;
+    template.priority = update.priority;
+    template.subtasks = update.subtasks;
+    template.updated_at = Utc::now();
+
+    let oid = match template.id.as_deref().map(ObjectId::parse_str) {
+        Some(Ok(o)) => o,
+        _ => return HttpResponse::BadRequest().body("Invalid template ID"),
+    };
+
+    let collection = client.database("rust_backend").collection::<TaskTemplate>("templates");
+
+    match collection.replace_one(doc! { "_id": Bson::ObjectId(oid), "user_id": &auth.0 }, &template).await {
+        Ok(update_result) if update_result.matched_count > 0 => HttpResponse::Ok().json(template),
+        Ok(_) => HttpResponse::NotFound().body("Template not found"),
+        Err(err) => HttpResponse::InternalServerError().body(format!("Error updating template: {}", err)),
+    }
+}
+
+pub async fn delete_template(
+    auth: AuthenticatedUser,
+    template_id: web::Path<String>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let oid = match ObjectId::parse_str(template_id.as_str()) {
+        Ok(o) => o,
+        Err(_) => return HttpResponse::BadRequest().body("Invalid template ID"),
+    };
+
+    let collection = client.database("rust_backend").collection::<TaskTemplate>("templates");
+
+    match collection.delete_one(doc! { "_id": Bson::ObjectId(oid), "user_id": &auth.0 }).await {
+        Ok(delete_result) => {
+            if delete_result.deleted_count > 0 {
+                HttpResponse::Ok().body("Template deleted successfully")
+            } else {
+                HttpResponse::NotFound().body("Template not found")
+            }
+        }
+        Err(err) => HttpResponse::InternalServerError().body(format!("Error deleting template: {}", err)),
+    }
+}
+
+/// Renders and validates the task and subtasks a template produces, without writing.
+async fn plan_instance(
+    client: &Client,
+    user_id: &str,
+    template: &TaskTemplate,
+    request: &InstantiateTemplate,
+) -> Result<(Task, Vec<Task>), TaskError> {
+    let variables = template_variables(&request.variables);
+    let render_opt = |text: &Option<String>| text.as_deref().map(|t| render(t, &variables)).transpose();
+
+    let new_task = |title: String, description: Option<String>| NewTask {
+        title,
+        description,
+        status: None,
+        workflow_id: request.workflow_id.clone(),
+        labels: Some(template.labels.clone()),
+        priority: Some(template.priority),
+        project_id: request.project_id.clone(),
+        parent_id: None,
+        workspace_id: request.workspace_id.clone(),
+        owner_id: None,
+        assignee_ids: None,
+        due_at: request.due_at,
+        custom_fields: None,
+    };
+
+    let parent = prepare_new_task(
+        client,
+        user_id,
+        &new_task(render(&template.title, &variables)?, render_opt(&template.description)?),
+    )
+    .await?;
+
+    let mut subtasks = Vec::with_capacity(template.subtasks.len());
+    let mut rank = parent.rank.clone();
+    for subtask in &template.subtasks {
+        let mut task = prepare_new_task(
+            client,
+            user_id,
+            &new_task(render(&subtask.title, &variables)?, render_opt(&subtask.description)?),
+        )
+        .await?;
+        // Keep subtasks in template order behind their parent on the board.
+        rank = rank_between(Some(&rank), None).map_err(TaskError::internal)?;
+        task.rank = rank.clone();
+        subtasks.push(task);
+    }
+
+    Ok((parent, subtasks))
+}
+
+/// Creates a task and its subtasks from a template in one call.
+pub async fn instantiate_template(
+    auth: AuthenticatedUser,
+    template_id: web::Path<String>,
+    request: Option<web::Json<InstantiateTemplate>>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let user_id = auth.0;
+    let request = request.map(|r| r.into_inner()).unwrap_or_default();
+
+    let template = match find_template(&client, &user_id, &template_id).await {
+        Ok(Some(t)) => t,
+        Ok(None) => return HttpResponse::NotFound().body("Template not found"),
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    };
+
+    let (mut parent, mut subtasks) = match plan_instance(&client, &user_id, &template, &request).await {
+        Ok(v) => v,
+        Err(err) => return err.into(),
+    };
+
+    let collection = client.database("rust_backend").collection::<Task>("tasks");
+
+    let parent_oid = match collection.insert_one(&parent).await {
+        Ok(insert_result) => match insert_result.inserted_id.as_object_id() {
+            Some(oid) => oid,
+            None => return HttpResponse::InternalServerError().body("Failed to get inserted ID"),
+        },
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Error inserting task: {}", err)),
+    };
+    parent.id = Some(parent_oid.to_hex());
+
+    for subtask in subtasks.iter_mut() {
+        subtask.parent_id = parent.id.clone();
+    }
+    if !subtasks.is_empty() {
+        match collection.insert_many(&subtasks).await {
+            Ok(insert_result) => {
+                for (index, id) in insert_result.inserted_ids {
+                    subtasks[index].id = id.as_object_id().map(|oid| oid.to_hex());
+                }
+            }
+            Err(err) => {
+                // Don't leave a half-instantiated tree behind.
+                let _ = collection.delete_one(doc! { "_id": Bson::ObjectId(parent_oid) }).await;
+                return HttpResponse::InternalServerError().body(format!("Error inserting subtasks: {}", err));
+            }
+        }
+    }
+
+    for task in std::iter::once(&parent).chain(&subtasks) {
+        log_history(&client, task, &user_id, HistoryAction::Created, diff_fields(&[], &task.tracked_fields())).await;
+    }
+
+    HttpResponse::Ok().json(TaskTree {
+        task: TaskResponse::from(parent),
+        subtasks: subtasks.into_iter().map(TaskResponse::from).collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variables(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        template_variables(&pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect())
+    }
+
+    #[test]
+    fn fills_in_built_in_variables() {
+        let today = Utc::now().date_naive();
+        let rendered = render("Report {{date}} {{month}} {{year}}", &variables(&[])).unwrap();
+        assert_eq!(rendered, format!("Report {} {} {}", today, today.format("%Y-%m"), today.year()));
+
+        let week = render("{{week}}", &variables(&[])).unwrap();
+        assert_eq!(week, format!("{}-W{:02}", today.iso_week().year(), today.iso_week().week()));
+    }
+
+    #[test]
+    fn custom_variables_override_built_ins() {
+        let vars = variables(&[("date", "tomorrow"), ("client", "ACME")]);
+        assert_eq!(render("{{client}}: {{date}}", &vars).unwrap(), "ACME: tomorrow");
+    }
+
+    #[test]
+    fn ignores_whitespace_inside_braces() {
+        let vars = variables(&[("client", "ACME")]);
+        assert_eq!(render("{{ client }}/{{client  }}/{{\tclient}}", &vars).unwrap(), "ACME/ACME/ACME");
+    }
+
+    #[test]
+    fn rejects_unknown_variables() {
+        let err = render("Hi {{ name }}", &variables(&[])).unwrap_err();
+        assert_eq!(err.message, "Unknown template variable: name");
+    }
+
+    #[test]
+    fn rejects_unclosed_braces() {
+        let vars = variables(&[("a", "1")]);
+        assert_eq!(render("{{a}} and {{a", &vars).unwrap_err().message, "Unclosed {{ in template");
+        // A lone `}}` is just text.
+        assert_eq!(render("a}} {a}", &vars).unwrap(), "a}} {a}");
+    }
+
+    #[test]
+    fn keeps_multi_byte_text() {
+        let vars = variables(&[("ville", "Zürich"), ("名前", "花子")]);
+        assert_eq!(render("Café → {{ville}} ✓ {{名前}}さん", &vars).unwrap(), "Café → Zürich ✓ 花子さん");
+    }
+}
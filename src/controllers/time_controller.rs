@@ -0,0 +1,342 @@
+use std::collections::{BTreeMap, HashMap};
+use actix_web::{web, HttpResponse, Responder};
+use mongodb::{bson::{doc, Bson, Document}, options::ReturnDocument, Client};
+use mongodb::bson::oid::ObjectId;
+use mongodb::error::{ErrorKind, WriteFailure};
+use futures::stream::TryStreamExt;
+use chrono::{Days, Duration, Utc};
+use crate::controllers::task_controller::find_task;
+use crate::models::task_model::Task;
+use crate::models::time_entry_model::{
+    NewTimeEntry, StartTimer, TimeEntry, TimeEntryResponse, TimeGrouping, TimeQuery, TimeSummary, TimeTotal,
+};
+use crate::utils::auth::AuthenticatedUser;
+
+/// Longest time entry that can be logged by hand.
+const MAX_ENTRY_SECONDS: i64 = 24 * 60 * 60;
+
+fn is_duplicate_key(err: &mongodb::error::Error) -> bool {
+    matches!(&*err.kind, ErrorKind::Write(WriteFailure::WriteError(e)) if e.code == 11000)
+}
+
+/// Stops the user's running timer, optionally only if it belongs to `task_id`.
+pub async fn stop_running_timer(
+    client: &Client,
+    user_id: &str,
+    task_id: Option<&str>,
+) -> mongodb::error::Result<Option<TimeEntry>> {
+    let mut filter = doc! { "user_id": user_id, "running": true };
+    if let Some(task_id) = task_id {
+        filter.insert("task_id", task_id);
+    }
+
+    client
+        .database("rust_backend")
+        .collection::<TimeEntry>("time_entries")
+        .find_one_and_update(filter, doc! { "$set": { "running": false, "ended_at": Utc::now().to_string() } })
+        .return_document(ReturnDocument::After)
+        .await
+}
+
+pub async fn start_timer(
+    auth: AuthenticatedUser,
+    task_id: web::Path<String>,
+    body: Option<web::Json<StartTimer>>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let task_id = task_id.into_inner();
+    match find_task(&client, &auth.0, &task_id).await {
+        Ok(Some(_)) => {}
+        Ok(None) => return HttpResponse::NotFound().body("Task not found"),
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    }
+
+    let now = Utc::now();
+    let mut entry = TimeEntry {
+        id: None,
+        task_id,
+        user_id: auth.0,
+        note: body.and_then(|b| b.into_inner().note),
+        started_at: now,
+        ended_at: None,
+        running: true,
+        created_at: now,
+    };
+
+    let collection = client.database("rust_backend").collection::<TimeEntry>("time_entries");
+
+    match collection.insert_one(&entry).await {
+        Ok(insert_result) => {
+            if let Some(oid) = insert_result.inserted_id.as_object_id() {
+                entry.id = Some(oid.to_hex());
+                HttpResponse::Ok().json(TimeEntryResponse::from(entry))
+            } else {
+                HttpResponse::InternalServerError().body("Failed to get inserted ID")
+            }
+        }
+        Err(err) if is_duplicate_key(&err) => {
+            HttpResponse::Conflict().body("A timer is already running; stop it before starting another")
+        }
+        Err(err) => HttpResponse::InternalServerError().body(format!("Error starting timer: {}", err)),
+    }
+}
+
+pub async fn stop_task_timer(
+    auth: AuthenticatedUser,
+    task_id: web::Path<String>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    match stop_running_timer(&client, &auth.0, Some(&task_id)).await {
+        Ok(Some(entry)) => HttpResponse::Ok().json(TimeEntryResponse::from(entry)),
+        Ok(None) => HttpResponse::NotFound().body("No timer is running for this task"),
+        Err(err) => HttpResponse::InternalServerError().body(format!("Error stopping timer: {}", err)),
+    }
+}
+
+/// Returns the running timer, or `null` when none is running.
+pub async fn get_timer(
+    auth: AuthenticatedUser,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let collection = client.database("rust_backend").collection::<TimeEntry>("time_entries");
+
+    match collection.find_one(doc! { "user_id": &auth.0, "running": true }).await {
+        Ok(entry) => HttpResponse::Ok().json(entry.map(TimeEntryResponse::from)),
+        Err(err) => HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    }
+}
+
+pub async fn stop_timer(
+    auth: AuthenticatedUser,
+    client: web::Data<Client>,
+) -> impl Responder {
+    match stop_running_timer(&client, &auth.0, None).await {
+        Ok(Some(entry)) => HttpResponse::Ok().json(TimeEntryResponse::from(entry)),
+        Ok(None) => HttpResponse::NotFound().body("No timer is running"),
+        Err(err) => HttpResponse::InternalServerError().body(format!("Error stopping timer: {}", err)),
+    }
+}
+
+pub async fn add_time_entry(
+    auth: AuthenticatedUser,
+    task_id: web::Path<String>,
+    entry: web::Json<NewTimeEntry>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let task_id = task_id.into_inner();
+    let entry = entry.into_inner();
+
+    match find_task(&client, &auth.0, &task_id).await {
+        Ok(Some(_)) => {}
+        Ok(None) => return HttpResponse::NotFound().body("Task not found"),
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    }
+
+    let ended_at = match (entry.ended_at, entry.duration_seconds) {
+        (Some(ended_at), None) => ended_at,
+        (None, Some(seconds)) if seconds <= 0 => {
+            return HttpResponse::BadRequest().body("duration_seconds must be positive");
+        }
+        (None, Some(seconds)) => {
+            match Duration::try_seconds(seconds).and_then(|d| entry.started_at.checked_add_signed(d)) {
+                Some(ended_at) => ended_at,
+                None => return HttpResponse::BadRequest().body("duration_seconds is out of range"),
+            }
+        }
+        _ => return HttpResponse::BadRequest().body("Give either ended_at or duration_seconds"),
+    };
+    if ended_at <= entry.started_at {
+        return HttpResponse::BadRequest().body("Time entries must end after they start");
+    }
+    if ended_at - entry.started_at > Duration::seconds(MAX_ENTRY_SECONDS) {
+        return HttpResponse::BadRequest().body("Time entries cannot be longer than 24 hours");
+    }
+    if ended_at > Utc::now() {
+        return HttpResponse::BadRequest().body("Time entries cannot end in the future");
+    }
+
+    let mut new_entry = TimeEntry {
+        id: None,
+        task_id,
+        user_id: auth.0,
+        note: entry.note,
+        started_at: entry.started_at,
+        ended_at: Some(ended_at),
+        running: false,
+        created_at: Utc::now(),
+    };
+
+    let collection = client.database("rust_backend").collection::<TimeEntry>("time_entries");
+
+    match collection.insert_one(&new_entry).await {
+        Ok(insert_result) => {
+            if let Some(oid) = insert_result.inserted_id.as_object_id() {
+                new_entry.id = Some(oid.to_hex());
+                HttpResponse::Ok().json(TimeEntryResponse::from(new_entry))
+            } else {
+                HttpResponse::InternalServerError().body("Failed to get inserted ID")
+            }
+        }
+        Err(err) => HttpResponse::InternalServerError().body(format!("Error inserting time entry: {}", err)),
+    }
+}
+
+/// Ids of the user's tasks in a project, including trashed ones whose time still counts.
+async fn project_task_ids(client: &Client, user_id: &str, project_id: &str) -> mongodb::error::Result<Vec<String>> {
+    let tasks: Vec<Task> = client
+        .database("rust_backend")
+        .collection::<Task>("tasks")
+        .find(doc! { "user_id": user_id, "project_id": project_id })
+        .await?
+        .try_collect()
+        .await?;
+
+    Ok(tasks.into_iter().filter_map(|t| t.id).collect())
+}
+
+async fn find_time_entries(client: &Client, user_id: &str, query: &TimeQuery) -> mongodb::error::Result<Vec<TimeEntry>> {
+    let mut filter = doc! { "user_id": user_id };
+
+    match (&query.task_id, &query.project_id) {
+        (Some(task_id), _) => {
+            filter.insert("task_id", task_id);
+        }
+        (None, Some(project_id)) => {
+            filter.insert("task_id", doc! { "$in": project_task_ids(client, user_id, project_id).await? });
+        }
+        (None, None) => {}
+    }
+
+    // `started_at` is stored as "YYYY-MM-DD HH:MM:SS UTC", so days compare as string prefixes.
+    let mut started = Document::new();
+    if let Some(from) = query.from {
+        started.insert("$gte", from.to_string());
+    }
+    if let Some(to) = query.to.and_then(|to| to.checked_add_days(Days::new(1))) {
+        started.insert("$lt", to.to_string());
+    }
+    if !started.is_empty() {
+        filter.insert("started_at", started);
+    }
+
+    client
+        .database("rust_backend")
+        .collection::<TimeEntry>("time_entries")
+        .find(filter)
+        .sort(doc! { "started_at": 1 })
+        .await?
+        .try_collect()
+        .await
+}
+
+pub async fn get_task_time_entries(
+    auth: AuthenticatedUser,
+    task_id: web::Path<String>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let query = TimeQuery { task_id: Some(task_id.into_inner()), project_id: None, from: None, to: None, group_by: None };
+
+    match find_time_entries(&client, &auth.0, &query).await {
+        Ok(entries) => HttpResponse::Ok().json(entries.into_iter().map(TimeEntryResponse::from).collect::<Vec<_>>()),
+        Err(err) => HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    }
+}
+
+pub async fn get_time_entries(
+    auth: AuthenticatedUser,
+    query: web::Query<TimeQuery>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    match find_time_entries(&client, &auth.0, &query).await {
+        Ok(entries) => HttpResponse::Ok().json(entries.into_iter().map(TimeEntryResponse::from).collect::<Vec<_>>()),
+        Err(err) => HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    }
+}
+
+pub async fn delete_time_entry(
+    auth: AuthenticatedUser,
+    entry_id: web::Path<String>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let oid = match ObjectId::parse_str(entry_id.as_str()) {
+        Ok(o) => o,
+        Err(_) => return HttpResponse::BadRequest().body("Invalid time entry ID"),
+    };
+
+    let collection = client.database("rust_backend").collection::<TimeEntry>("time_entries");
+
+    match collection.delete_one(doc! { "_id": Bson::ObjectId(oid), "user_id": &auth.0 }).await {
+        Ok(delete_result) => {
+            if delete_result.deleted_count > 0 {
+                HttpResponse::Ok().body("Time entry deleted successfully")
+            } else {
+                HttpResponse::NotFound().body("Time entry not found")
+            }
+        }
+        Err(err) => HttpResponse::InternalServerError().body(format!("Error deleting time entry: {}", err)),
+    }
+}
+
+/// Totals tracked time per task, project or day (of `started_at`, in UTC). Running
+/// timers count up to now.
+pub async fn get_time_summary(
+    auth: AuthenticatedUser,
+    query: web::Query<TimeQuery>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let group_by = query.group_by.unwrap_or(TimeGrouping::Task);
+
+    let entries = match find_time_entries(&client, &auth.0, &query).await {
+        Ok(e) => e,
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    };
+
+    let mut projects: HashMap<String, Option<String>> = HashMap::new();
+    if group_by == TimeGrouping::Project {
+        let ids: Vec<Bson> = entries
+            .iter()
+            .filter_map(|e| ObjectId::parse_str(&e.task_id).ok())
+            .map(Bson::ObjectId)
+            .collect();
+        let tasks: Vec<Task> = match client
+            .database("rust_backend")
+            .collection::<Task>("tasks")
+            .find(doc! { "_id": { "$in": ids }, "user_id": &auth.0 })
+            .await
+        {
+            Ok(cursor) => match cursor.try_collect().await {
+                Ok(docs) => docs,
+                Err(e) => return HttpResponse::InternalServerError().body(format!("Error reading tasks: {}", e)),
+            },
+            Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+        };
+        projects = tasks.into_iter().filter_map(|t| Some((t.id?, t.project_id))).collect();
+    }
+
+    let now = Utc::now();
+    let mut totals: BTreeMap<Option<String>, i64> = BTreeMap::new();
+    for entry in &entries {
+        let key = match group_by {
+            TimeGrouping::Task => Some(entry.task_id.clone()),
+            TimeGrouping::Project => projects.get(&entry.task_id).cloned().flatten(),
+            TimeGrouping::Day => Some(entry.started_at.date_naive().to_string()),
+        };
+        *totals.entry(key).or_default() += entry.duration_seconds(now);
+    }
+
+    HttpResponse::Ok().json(TimeSummary {
+        group_by,
+        total_seconds: totals.values().sum(),
+        totals: totals.into_iter().map(|(key, seconds)| TimeTotal { key, seconds }).collect(),
+    })
+}
+
+/// Removes a task's time entries when the task is permanently deleted.
+pub async fn delete_task_time_entries(client: &Client, task_id: &str) -> mongodb::error::Result<()> {
+    client
+        .database("rust_backend")
+        .collection::<TimeEntry>("time_entries")
+        .delete_many(doc! { "task_id": task_id })
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,106 @@
+use actix_web::{web, HttpResponse, Responder};
+use mongodb::{bson::{doc, Bson}, Client};
+use mongodb::bson::oid::ObjectId;
+use futures::stream::TryStreamExt;
+use crate::controllers::board_controller::{write_within_wip_limit, WipCheck};
+use crate::controllers::task_controller::{log_history, purge_task};
+use crate::models::history_model::{FieldChange, HistoryAction};
+use crate::models::task_model::{Task, TaskResponse};
+use crate::storage::Storage;
+use crate::utils::auth::AuthenticatedUser;
+
+pub async fn get_trash(
+    auth: AuthenticatedUser,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let database = client.database("rust_backend");
+    let collection = database.collection::<Task>("tasks");
+
+    let cursor = match collection
+        .find(doc! { "user_id": &auth.0, "deleted_at": { "$ne": Bson::Null } })
+        .sort(doc! { "deleted_at": -1 })
+        .await
+    {
+        Ok(c) => c,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    let tasks: Vec<TaskResponse> = match cursor.try_collect::<Vec<_>>().await {
+        Ok(docs) => docs.into_iter().map(TaskResponse::from).collect(),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error reading trash: {}", e)),
+    };
+
+    HttpResponse::Ok().json(tasks)
+}
+
+pub async fn restore_task(
+    auth: AuthenticatedUser,
+    task_id: web::Path<String>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let oid = match ObjectId::parse_str(task_id.as_str()) {
+        Ok(o) => o,
+        Err(_) => return HttpResponse::BadRequest().body("Invalid task ID"),
+    };
+
+    let database = client.database("rust_backend");
+    let collection = database.collection::<Task>("tasks");
+
+    let filter = doc! { "_id": Bson::ObjectId(oid), "user_id": &auth.0, "deleted_at": { "$ne": Bson::Null } };
+    let mut task = match collection.find_one(filter.clone()).await {
+        Ok(Some(t)) => t,
+        Ok(None) => return HttpResponse::NotFound().body("Task not found in trash"),
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    };
+
+    // The task goes back into its board column, which may be full by now.
+    let wip = match WipCheck::load(&client, &task).await {
+        Ok(wip) => wip,
+        Err(err) => return err.into(),
+    };
+    let mut guarded = filter;
+    guarded.insert("version", task.version);
+    match write_within_wip_limit(&collection, guarded, &task, doc! { "deleted_at": Bson::Null }, wip.as_ref()).await {
+        Ok(true) => {
+            task.version += 1;
+            let changes = vec![FieldChange {
+                field: "deleted_at".to_string(),
+                from: serde_json::json!(task.deleted_at.take()),
+                to: serde_json::Value::Null,
+            }];
+            log_history(&client, &task, &auth.0, HistoryAction::Restored, changes).await;
+            HttpResponse::Ok().json(TaskResponse::from(task))
+        }
+        Ok(false) => HttpResponse::Conflict().body("Task was modified concurrently"),
+        Err(err) => err.into(),
+    }
+}
+
+pub async fn delete_task_permanently(
+    auth: AuthenticatedUser,
+    task_id: web::Path<String>,
+    client: web::Data<Client>,
+    storage: web::Data<dyn Storage>,
+) -> impl Responder {
+    let oid = match ObjectId::parse_str(task_id.as_str()) {
+        Ok(o) => o,
+        Err(_) => return HttpResponse::BadRequest().body("Invalid task ID"),
+    };
+
+    let database = client.database("rust_backend");
+    let collection = database.collection::<Task>("tasks");
+
+    let task = match collection
+        .find_one(doc! { "_id": Bson::ObjectId(oid), "user_id": &auth.0, "deleted_at": { "$ne": Bson::Null } })
+        .await
+    {
+        Ok(Some(t)) => t,
+        Ok(None) => return HttpResponse::NotFound().body("Task not found in trash"),
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    };
+
+    match purge_task(&client, storage.get_ref(), &task, &auth.0).await {
+        Ok(()) => HttpResponse::Ok().body("Task deleted permanently"),
+        Err(err) => HttpResponse::InternalServerError().body(format!("Error deleting task: {}", err)),
+    }
+}
@@ -0,0 +1,513 @@
+use std::cmp::Ordering;
+use actix_web::{web, HttpResponse, Responder};
+use mongodb::{bson::{doc, to_bson, Bson, Document}, options::ReturnDocument, Client};
+use mongodb::bson::oid::ObjectId;
+use futures::stream::TryStreamExt;
+use chrono::{DateTime, Duration, Utc};
+use serde_json::json;
+use crate::controllers::project_controller::archived_project_ids;
+use crate::controllers::task_controller::scope_filter;
+use crate::controllers::workspace_controller::{find_workspace, member_workspace_ids};
+use crate::models::task_model::{Task, TaskResponse, TaskScope};
+use crate::models::view_model::{
+    GroupBy, NewView, SavedView, SortDirection, SortField, TaskGroup, ViewFilter, ViewResponse, ViewSort, ViewTasks,
+};
+use crate::utils::auth::AuthenticatedUser;
+use crate::utils::pagination::PageQuery;
+
+const MAX_RELATIVE_DAYS: i64 = 3650;
+const STALE_DAYS: i64 = 30;
+
+/// The smart views every user has without setting them up.
+fn builtin_views() -> Vec<ViewResponse> {
+    let builtin = |id: &str, name: &str, filter: ViewFilter, field: SortField| ViewResponse {
+        id: id.to_string(),
+        name: name.to_string(),
+        builtin: true,
+        user_id: None,
+        workspace_id: None,
+        filter,
+        sort: ViewSort { field, direction: SortDirection::Asc },
+        group_by: None,
+    };
+
+    vec![
+        builtin(
+            "today",
+            "Today",
+            ViewFilter { scope: TaskScope::Assigned, due_to_days: Some(0), completed: Some(false), ..Default::default() },
+            SortField::DueAt,
+        ),
+        builtin(
+            "upcoming",
+            "Upcoming",
+            ViewFilter {
+                scope: TaskScope::Assigned,
+                due_from_days: Some(1),
+                due_to_days: Some(7),
+                completed: Some(false),
+                ..Default::default()
+            },
+            SortField::DueAt,
+        ),
+        builtin(
+            "stale",
+            "Stale",
+            ViewFilter { stale_days: Some(STALE_DAYS), completed: Some(false), ..Default::default() },
+            SortField::UpdatedAt,
+        ),
+    ]
+}
+
+/// Looks up a built-in view, or a saved view `user_id` owns or that is shared with
+/// one of their workspaces; malformed ids are treated as not found.
+async fn find_view(client: &Client, user_id: &str, view_id: &str) -> mongodb::error::Result<Option<ViewResponse>> {
+    if let Some(view) = builtin_views().into_iter().find(|v| v.id == view_id) {
+        return Ok(Some(view));
+    }
+
+    let oid = match ObjectId::parse_str(view_id) {
+        Ok(o) => o,
+        Err(_) => return Ok(None),
+    };
+    let workspace_ids = member_workspace_ids(client, user_id).await?;
+
+    let view = client
+        .database("rust_backend")
+        .collection::<SavedView>("views")
+        .find_one(doc! {
+            "_id": Bson::ObjectId(oid),
+            "$or": [{ "user_id": user_id }, { "workspace_id": { "$in": workspace_ids } }],
+        })
+        .await?;
+    Ok(view.map(ViewResponse::from))
+}
+
+async fn validate_view(client: &Client, user_id: &str, view: &NewView) -> Result<(), HttpResponse> {
+    if view.name.trim().is_empty() {
+        return Err(HttpResponse::BadRequest().body("View name is required"));
+    }
+
+    let filter = &view.filter;
+    let days = [filter.due_from_days, filter.due_to_days, filter.stale_days];
+    if days.iter().flatten().any(|d| d.abs() > MAX_RELATIVE_DAYS) {
+        return Err(HttpResponse::BadRequest().body(format!("Day offsets must be within {} days", MAX_RELATIVE_DAYS)));
+    }
+    if filter.stale_days.is_some_and(|d| d < 0) {
+        return Err(HttpResponse::BadRequest().body("stale_days must not be negative"));
+    }
+    if let (Some(from), Some(to)) = (filter.due_from_days, filter.due_to_days)
+        && from > to
+    {
+        return Err(HttpResponse::BadRequest().body("due_from_days must not be after due_to_days"));
+    }
+
+    if let Some(workspace_id) = &view.workspace_id {
+        match find_workspace(client, user_id, workspace_id).await {
+            Ok(Some(_)) => {}
+            Ok(None) => return Err(HttpResponse::BadRequest().body("Workspace not found")),
+            Err(err) => return Err(HttpResponse::InternalServerError().body(format!("Database error: {}", err))),
+        }
+    }
+    Ok(())
+}
+
+/// The task query of a view as seen by `viewer_id`. Views shared with a workspace only
+/// cover that workspace's tasks.
+async fn task_filter(
+    client: &Client,
+    viewer_id: &str,
+    view: &ViewResponse,
+    now: DateTime<Utc>,
+) -> mongodb::error::Result<Document> {
+    let filter = &view.filter;
+    let today = now.date_naive();
+
+    let mut conditions = vec![scope_filter(filter.scope, viewer_id), doc! { "deleted_at": Bson::Null }];
+    if let Some(workspace_id) = &view.workspace_id {
+        conditions.push(doc! { "workspace_id": workspace_id });
+    }
+    if !filter.statuses.is_empty() {
+        conditions.push(doc! { "status": { "$in": &filter.statuses } });
+    }
+    if !filter.labels.is_empty() {
+        conditions.push(doc! { "labels": { "$in": &filter.labels } });
+    }
+    if !filter.priorities.is_empty() {
+        conditions.push(doc! { "priority": { "$in": to_bson(&filter.priorities)? } });
+    }
+    if let Some(project_id) = &filter.project_id {
+        conditions.push(doc! { "project_id": project_id });
+    } else {
+        let archived = archived_project_ids(client, viewer_id).await?;
+        if !archived.is_empty() {
+            conditions.push(doc! { "project_id": { "$nin": archived } });
+        }
+    }
+    if let Some(assignee_id) = &filter.assignee_id {
+        conditions.push(doc! { "assignee_ids": assignee_id });
+    }
+    // Stored timestamps start with the day, so day bounds are string bounds.
+    if let Some(days) = filter.due_from_days {
+        conditions.push(doc! { "due_at": { "$gte": (today + Duration::days(days)).to_string() } });
+    }
+    if let Some(days) = filter.due_to_days {
+        conditions.push(doc! { "due_at": { "$lt": (today + Duration::days(days + 1)).to_string() } });
+    }
+    if let Some(days) = filter.stale_days {
+        conditions.push(doc! { "updated_at": { "$lt": (now - Duration::days(days)).to_string() } });
+    }
+    match filter.completed {
+        Some(true) => conditions.push(doc! { "completed_at": { "$type": "string" } }),
+        Some(false) => conditions.push(doc! { "completed_at": Bson::Null }),
+        None => {}
+    }
+
+    Ok(doc! { "$and": conditions })
+}
+
+/// Orders by `sort`, with tasks missing the sort value last and ties broken by id.
+fn sort_tasks(tasks: &mut [Task], sort: ViewSort) {
+    fn present_first<T: Ord>(a: Option<T>, b: Option<T>, direction: SortDirection) -> Ordering {
+        match (a, b) {
+            (Some(a), Some(b)) if direction == SortDirection::Desc => b.cmp(&a),
+            (Some(a), Some(b)) => a.cmp(&b),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        }
+    }
+
+    tasks.sort_by(|a, b| {
+        let ordering = match sort.field {
+            SortField::Rank => present_first(Some(&a.rank), Some(&b.rank), sort.direction),
+            SortField::Title => {
+                present_first(Some(a.title.to_lowercase()), Some(b.title.to_lowercase()), sort.direction)
+            }
+            SortField::Priority => present_first(Some(a.priority), Some(b.priority), sort.direction),
+            SortField::DueAt => present_first(a.due_at, b.due_at, sort.direction),
+            SortField::CreatedAt => present_first(Some(a.created_at), Some(b.created_at), sort.direction),
+            SortField::UpdatedAt => present_first(Some(a.updated_at), Some(b.updated_at), sort.direction),
+        };
+        ordering.then_with(|| a.id.cmp(&b.id))
+    });
+}
+
+fn group_keys(task: &Task, group_by: GroupBy) -> Vec<Option<String>> {
+    let keys: Vec<Option<String>> = match group_by {
+        GroupBy::Status => vec![Some(task.status.clone())],
+        GroupBy::Priority => vec![json!(task.priority).as_str().map(str::to_string)],
+        GroupBy::Project => vec![task.project_id.clone()],
+        GroupBy::Label => task.labels.iter().cloned().map(Some).collect(),
+        GroupBy::Assignee => task.assignee_ids.iter().cloned().map(Some).collect(),
+    };
+    if keys.is_empty() { vec![None] } else { keys }
+}
+
+/// Groups sorted tasks; groups come in the order their first task appears in.
+fn group_tasks(tasks: Vec<Task>, group_by: Option<GroupBy>) -> Vec<TaskGroup> {
+    let Some(group_by) = group_by else {
+        return vec![TaskGroup { key: None, tasks: tasks.into_iter().map(TaskResponse::from).collect() }];
+    };
+
+    let mut groups: Vec<TaskGroup> = Vec::new();
+    for task in tasks {
+        for key in group_keys(&task, group_by) {
+            let index = match groups.iter().position(|g| g.key == key) {
+                Some(index) => index,
+                None => {
+                    groups.push(TaskGroup { key, tasks: Vec::new() });
+                    groups.len() - 1
+                }
+            };
+            groups[index].tasks.push(TaskResponse::from(task.clone()));
+        }
+    }
+    groups
+}
+
+pub async fn create_view(
+    auth: AuthenticatedUser,
+    view: web::Json<NewView>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let view = view.into_inner();
+    if let Err(response) = validate_view(&client, &auth.0, &view).await {
+        return response;
+    }
+
+    let now = Utc::now();
+    let mut new_view = SavedView {
+        id: None,
+        user_id: auth.0,
+        workspace_id: view.workspace_id,
+        name: view.name.trim().to_string(),
+        filter: view.filter,
+        sort: view.sort,
+        group_by: view.group_by,
+        created_at: now,
+        updated_at: now,
+    };
+
+    let collection = client.database("rust_backend").collection::<SavedView>("views");
+
+    match collection.insert_one(&new_view).await {
+        Ok(insert_result) => {
+            if let Some(oid) = insert_result.inserted_id.as_object_id() {
+                new_view.id = Some(oid.to_hex());
+                HttpResponse::Ok().json(ViewResponse::from(new_view))
+            } else {
+                HttpResponse::InternalServerError().body("Failed to get inserted ID")
+            }
+        }
+        Err(err) => HttpResponse::InternalServerError().body(format!("Error inserting view: {}", err)),
+    }
+}
+
+/// Lists the built-in views, then the caller's own and shared views by name.
+pub async fn get_views(
+    auth: AuthenticatedUser,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let workspace_ids = match member_workspace_ids(&client, &auth.0).await {
+        Ok(ids) => ids,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    let collection = client.database("rust_backend").collection::<SavedView>("views");
+    let cursor = match collection
+        .find(doc! { "$or": [{ "user_id": &auth.0 }, { "workspace_id": { "$in": workspace_ids } }] })
+        .sort(doc! { "name": 1 })
+        .await
+    {
+        Ok(c) => c,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    let mut views = builtin_views();
+    match cursor.try_collect::<Vec<_>>().await {
+        Ok(saved) => views.extend(saved.into_iter().map(ViewResponse::from)),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error reading views: {}", e)),
+    }
+
+    HttpResponse::Ok().json(views)
+}
+
+pub async fn get_view(
+    auth: AuthenticatedUser,
+    view_id: web::Path<String>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    match find_view(&client, &auth.0, &view_id).await {
+        Ok(Some(view)) => HttpResponse::Ok().json(view),
+        Ok(None) => HttpResponse::NotFound().body("View not found"),
+        Err(err) => HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    }
+}
+
+/// Replaces a view; only its owner can change it.
+pub async fn update_view(
+    auth: AuthenticatedUser,
+    view_id: web::Path<String>,
+    view: web::Json<NewView>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let view = view.into_inner();
+    let oid = match ObjectId::parse_str(view_id.as_str()) {
+        Ok(o) => o,
+        Err(_) => return HttpResponse::BadRequest().body("Invalid view ID"),
+    };
+    if let Err(response) = validate_view(&client, &auth.0, &view).await {
+        return response;
+    }
+
+    let collection = client.database("rust_backend").collection::<SavedView>("views");
+    let update_doc = doc! {
+        "name": view.name.trim(),
+        "workspace_id": &view.workspace_id,
+        "filter": to_bson(&view.filter).unwrap_or(Bson::Null),
+        "sort": to_bson(&view.sort).unwrap_or(Bson::Null),
+        "group_by": to_bson(&view.group_by).unwrap_or(Bson::Null),
+        "updated_at": Utc::now().to_string(),
+    };
+
+    match collection
+        .find_one_and_update(doc! { "_id": Bson::ObjectId(oid), "user_id": &auth.0 }, doc! { "$set": update_doc })
+        .return_document(ReturnDocument::After)
+        .await
+    {
+        Ok(Some(updated)) => HttpResponse::Ok().json(ViewResponse::from(updated)),
+        Ok(None) => HttpResponse::NotFound().body("View not found"),
+        Err(err) => HttpResponse::InternalServerError().body(format!("Error updating view: {}", err)),
+    }
+}
+
+pub async fn delete_view(
+    auth: AuthenticatedUser,
+    view_id: web::Path<String>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let oid = match ObjectId::parse_str(view_id.as_str()) {
+        Ok(o) => o,
+        Err(_) => return HttpResponse::BadRequest().body("Invalid view ID"),
+    };
+
+    let collection = client.database("rust_backend").collection::<SavedView>("views");
+
+    match collection.delete_one(doc! { "_id": Bson::ObjectId(oid), "user_id": &auth.0 }).await {
+        Ok(delete_result) => {
+            if delete_result.deleted_count > 0 {
+                HttpResponse::Ok().body("View deleted successfully")
+            } else {
+                HttpResponse::NotFound().body("View not found")
+            }
+        }
+        Err(err) => HttpResponse::InternalServerError().body(format!("Error deleting view: {}", err)),
+    }
+}
+
+/// Runs a view for the caller: a page of the tasks it matches, sorted and grouped.
+/// Paging follows the sort, and groups are built from the tasks of the page.
+pub async fn get_view_tasks(
+    auth: AuthenticatedUser,
+    view_id: web::Path<String>,
+    query: web::Query<PageQuery>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let view = match find_view(&client, &auth.0, &view_id).await {
+        Ok(Some(v)) => v,
+        Ok(None) => return HttpResponse::NotFound().body("View not found"),
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    };
+
+    let filter = match task_filter(&client, &auth.0, &view, Utc::now()).await {
+        Ok(f) => f,
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    };
+
+    let collection = client.database("rust_backend").collection::<Task>("tasks");
+    let cursor = match collection.find(filter).await {
+        Ok(c) => c,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    let mut tasks: Vec<Task> = match cursor.try_collect().await {
+        Ok(docs) => docs,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error reading tasks: {}", e)),
+    };
+    // The sort puts missing values last in either direction and compares titles
+    // without case, so it runs here rather than in the query.
+    sort_tasks(&mut tasks, view.sort);
+    let total = tasks.len() as u64;
+    let tasks: Vec<Task> = tasks
+        .into_iter()
+        .skip(usize::try_from(query.skip()).unwrap_or(usize::MAX))
+        .take(query.limit() as usize)
+        .collect();
+
+    let groups = group_tasks(tasks, view.group_by);
+    HttpResponse::Ok().json(ViewTasks { view, groups, page: query.page(), limit: query.limit(), total })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(id: &str, extra: serde_json::Value) -> Task {
+        let mut task = json!({
+            "_id": format!("6500000000000000000000{}", id),
+            "title": "Task",
+            "status": "Pending",
+            "workflow_id": "default",
+            "user_id": "650000000000000000000001",
+            "rank": "m",
+            "created_at": "2026-10-01 08:00:00 UTC",
+            "updated_at": "2026-10-01 08:00:00 UTC",
+            "completed_at": null,
+            "deleted_at": null,
+        });
+        task.as_object_mut().unwrap().extend(extra.as_object().unwrap().clone());
+        serde_json::from_value(task).unwrap()
+    }
+
+    fn sorted(tasks: &mut [Task], field: SortField, direction: SortDirection) -> Vec<&str> {
+        sort_tasks(tasks, ViewSort { field, direction });
+        tasks.iter().map(|t| &t.id.as_deref().unwrap()[22..]).collect()
+    }
+
+    #[test]
+    fn sorts_missing_values_last_in_either_direction() {
+        let mut tasks = vec![
+            task("01", json!({})),
+            task("02", json!({ "due_at": "2026-10-05 00:00:00 UTC" })),
+            task("03", json!({ "due_at": "2026-10-03 00:00:00 UTC" })),
+            task("04", json!({})),
+        ];
+        assert_eq!(sorted(&mut tasks, SortField::DueAt, SortDirection::Asc), vec!["03", "02", "01", "04"]);
+        assert_eq!(sorted(&mut tasks, SortField::DueAt, SortDirection::Desc), vec!["02", "03", "01", "04"]);
+    }
+
+    #[test]
+    fn sorts_titles_without_case_and_priorities_by_urgency() {
+        let mut tasks = vec![
+            task("01", json!({ "title": "beta", "priority": "Low" })),
+            task("02", json!({ "title": "Alpha", "priority": "Urgent" })),
+            task("03", json!({ "title": "alpha", "priority": "Medium" })),
+        ];
+        // Equal titles fall back to the id.
+        assert_eq!(sorted(&mut tasks, SortField::Title, SortDirection::Asc), vec!["02", "03", "01"]);
+        assert_eq!(sorted(&mut tasks, SortField::Title, SortDirection::Desc), vec!["01", "02", "03"]);
+        assert_eq!(sorted(&mut tasks, SortField::Priority, SortDirection::Desc), vec!["02", "03", "01"]);
+        assert_eq!(sorted(&mut tasks, SortField::Priority, SortDirection::Asc), vec!["01", "03", "02"]);
+    }
+
+    fn groups(tasks: Vec<Task>, group_by: Option<GroupBy>) -> Vec<(Option<String>, Vec<String>)> {
+        group_tasks(tasks, group_by)
+            .into_iter()
+            .map(|g| (g.key, g.tasks.into_iter().map(|t| t.id.unwrap()[22..].to_string()).collect()))
+            .collect()
+    }
+
+    fn key(key: &str) -> Option<String> {
+        Some(key.to_string())
+    }
+
+    #[test]
+    fn tasks_appear_under_each_of_their_labels() {
+        let tasks = vec![
+            task("01", json!({ "labels": ["work", "urgent"] })),
+            task("02", json!({ "labels": [] })),
+            task("03", json!({ "labels": ["urgent"] })),
+        ];
+        assert_eq!(
+            groups(tasks, Some(GroupBy::Label)),
+            vec![
+                (key("work"), vec!["01".to_string()]),
+                (key("urgent"), vec!["01".to_string(), "03".to_string()]),
+                (None, vec!["02".to_string()]),
+            ]
+        );
+    }
+
+    #[test]
+    fn tasks_without_a_value_share_the_none_group() {
+        let tasks = vec![
+            task("01", json!({})),
+            task("02", json!({ "project_id": "650000000000000000000010" })),
+            task("03", json!({})),
+        ];
+        assert_eq!(
+            groups(tasks, Some(GroupBy::Project)),
+            vec![
+                (None, vec!["01".to_string(), "03".to_string()]),
+                (key("650000000000000000000010"), vec!["02".to_string()]),
+            ]
+        );
+    }
+
+    #[test]
+    fn ungrouped_views_have_one_group() {
+        let tasks = vec![task("01", json!({})), task("02", json!({}))];
+        assert_eq!(groups(tasks, None), vec![(None, vec!["01".to_string(), "02".to_string()])]);
+    }
+}
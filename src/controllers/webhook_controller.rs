@@ -0,0 +1,463 @@
+use std::{env, net::IpAddr};
+use actix_web::{web, HttpResponse, Responder};
+use mongodb::{bson::{doc, to_bson, Bson, Document}, options::ReturnDocument, Client};
+use mongodb::bson::oid::ObjectId;
+use futures::stream::TryStreamExt;
+use chrono::Utc;
+use reqwest::Url;
+use tokio::net::lookup_host;
+use serde_json::{json, Value};
+use uuid::Uuid;
+use crate::models::history_model::{FieldChange, HistoryAction};
+use crate::models::task_model::{Task, TaskResponse};
+use crate::models::webhook_model::{
+    DeliveryStatus, NewWebhook, UpdateWebhook, Webhook, WebhookDelivery, WebhookDeliveryResponse, WebhookEvent,
+    WebhookResponse,
+};
+use crate::utils::auth::AuthenticatedUser;
+use crate::utils::pagination::{Page, PageQuery};
+
+const MAX_WEBHOOKS: u64 = 20;
+
+/// Whether an address is reachable from the internet. Loopback, private, link-local and
+/// other reserved ranges are not, and hooks must not be able to reach into them.
+pub fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(ip.is_unspecified()
+                || ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || ip.is_multicast()
+                || a == 0
+                // Carrier-grade NAT, 100.64.0.0/10.
+                || (a == 100 && (64..128).contains(&b)))
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(mapped) => is_public(IpAddr::V4(mapped)),
+            None => !(ip.is_unspecified() || ip.is_loopback() || ip.is_unique_local() || ip.is_unicast_link_local() || ip.is_multicast()),
+        },
+    }
+}
+
+/// Set `WEBHOOK_ALLOW_PRIVATE_URLS=true` to allow private receivers, e.g. to try hooks
+/// out against a local server.
+pub fn private_urls_allowed() -> bool {
+    env::var("WEBHOOK_ALLOW_PRIVATE_URLS").is_ok_and(|v| v == "true")
+}
+
+/// Checks that a webhook URL points at a public address. Host names are resolved and
+/// every address they resolve to must be public.
+pub async fn check_destination(url: &Url) -> Result<(), String> {
+    if private_urls_allowed() {
+        return Ok(());
+    }
+
+    let host = url.host_str().ok_or("Webhook URLs need a host")?;
+    let addresses: Vec<IpAddr> = match host.trim_start_matches('[').trim_end_matches(']').parse() {
+        Ok(ip) => vec![ip],
+        Err(_) => {
+            let port = url.port_or_known_default().unwrap_or(443);
+            lookup_host((host, port))
+                .await
+                .map_err(|_| format!("Could not resolve {}", host))?
+                .map(|address| address.ip())
+                .collect()
+        }
+    };
+    if addresses.is_empty() || !addresses.into_iter().all(is_public) {
+        return Err("Webhook URLs must point at a public address".to_string());
+    }
+    Ok(())
+}
+
+/// Accepts absolute `http` and `https` URLs of public receivers.
+async fn validate_url(url: &str) -> Result<String, HttpResponse> {
+    let parsed = Url::parse(url.trim()).map_err(|_| HttpResponse::BadRequest().body("Invalid webhook URL"))?;
+    if !matches!(parsed.scheme(), "http" | "https") || parsed.host_str().is_none() {
+        return Err(HttpResponse::BadRequest().body("Webhook URLs must be http or https"));
+    }
+    check_destination(&parsed).await.map_err(|err| HttpResponse::BadRequest().body(err))?;
+    Ok(parsed.to_string())
+}
+
+fn validate_events(events: &[WebhookEvent]) -> Result<Vec<WebhookEvent>, HttpResponse> {
+    let mut unique: Vec<WebhookEvent> = Vec::new();
+    for event in events {
+        if *event == WebhookEvent::Ping {
+            return Err(HttpResponse::BadRequest().body("ping can't be subscribed to"));
+        }
+        if !unique.contains(event) {
+            unique.push(*event);
+        }
+    }
+    if unique.is_empty() {
+        return Err(HttpResponse::BadRequest().body("At least one event is required"));
+    }
+    Ok(unique)
+}
+
+async fn find_webhook(client: &Client, user_id: &str, webhook_id: &str) -> mongodb::error::Result<Option<Webhook>> {
+    let oid = match ObjectId::parse_str(webhook_id) {
+        Ok(o) => o,
+        Err(_) => return Ok(None),
+    };
+    client
+        .database("rust_backend")
+        .collection::<Webhook>("webhooks")
+        .find_one(doc! { "_id": Bson::ObjectId(oid), "user_id": user_id })
+        .await
+}
+
+/// The events a recorded task change triggers. Restoring a task re-creates it and
+/// moving it to the trash deletes it; purging it from the trash sends nothing more.
+fn task_events(action: &HistoryAction, changes: &[FieldChange]) -> Vec<WebhookEvent> {
+    match action {
+        HistoryAction::Created | HistoryAction::Restored => vec![WebhookEvent::TaskCreated],
+        HistoryAction::Updated if changes.iter().any(|c| c.field == "status") => {
+            vec![WebhookEvent::TaskUpdated, WebhookEvent::TaskStatusChanged]
+        }
+        HistoryAction::Updated => vec![WebhookEvent::TaskUpdated],
+        HistoryAction::Trashed => vec![WebhookEvent::TaskDeleted],
+        HistoryAction::Deleted => Vec::new(),
+    }
+}
+
+fn new_delivery(webhook: &Webhook, event: WebhookEvent, data: Value) -> WebhookDelivery {
+    let now = Utc::now();
+    let mut payload = json!({
+        "id": Uuid::new_v4().to_string(),
+        "event": event,
+        "created_at": now.to_string(),
+    });
+    if let (Value::Object(payload), Value::Object(data)) = (&mut payload, data) {
+        payload.extend(data);
+    }
+
+    WebhookDelivery {
+        id: None,
+        webhook_id: webhook.id.clone().unwrap_or_default(),
+        user_id: webhook.user_id.clone(),
+        event,
+        payload: payload.to_string(),
+        status: DeliveryStatus::Pending,
+        attempts: 0,
+        next_attempt_at: Some(now),
+        response_status: None,
+        error: None,
+        created_at: now,
+        delivered_at: None,
+    }
+}
+
+/// Queues deliveries of a task change for the task owner's webhooks; the delivery job
+/// sends them. The change already succeeded, so failures are only logged.
+pub async fn enqueue_task_events(
+    client: &Client,
+    task: &Task,
+    actor_id: &str,
+    action: &HistoryAction,
+    changes: &[FieldChange],
+) {
+    let events = task_events(action, changes);
+    if events.is_empty() {
+        return;
+    }
+
+    let webhooks: Vec<Webhook> = match client
+        .database("rust_backend")
+        .collection::<Webhook>("webhooks")
+        .find(doc! {
+            "user_id": &task.user_id,
+            "active": true,
+            "events": { "$in": events.iter().map(|e| e.name()).collect::<Vec<_>>() },
+        })
+        .await
+    {
+        Ok(cursor) => match cursor.try_collect().await {
+            Ok(w) => w,
+            Err(err) => {
+                eprintln!("❌ failed to read webhooks: {}", err);
+                return;
+            }
+        },
+        Err(err) => {
+            eprintln!("❌ failed to load webhooks: {}", err);
+            return;
+        }
+    };
+    if webhooks.is_empty() {
+        return;
+    }
+
+    let data = json!({
+        "actor_id": actor_id,
+        "task": TaskResponse::from(task.clone()),
+        "changes": changes,
+    });
+    let deliveries: Vec<WebhookDelivery> = webhooks
+        .iter()
+        .flat_map(|webhook| {
+            events
+                .iter()
+                .filter(|event| webhook.events.contains(event))
+                .map(|event| new_delivery(webhook, *event, data.clone()))
+        })
+        .collect();
+
+    if let Err(err) = client
+        .database("rust_backend")
+        .collection::<WebhookDelivery>("webhook_deliveries")
+        .insert_many(deliveries)
+        .await
+    {
+        eprintln!("❌ failed to queue webhook deliveries for task {}: {}", task.id.as_deref().unwrap_or_default(), err);
+    }
+}
+
+/// Registers a webhook. Its signing secret is only returned here.
+pub async fn create_webhook(
+    auth: AuthenticatedUser,
+    webhook: web::Json<NewWebhook>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let url = match validate_url(&webhook.url).await {
+        Ok(u) => u,
+        Err(response) => return response,
+    };
+    let events = match validate_events(&webhook.events) {
+        Ok(e) => e,
+        Err(response) => return response,
+    };
+
+    let collection = client.database("rust_backend").collection::<Webhook>("webhooks");
+    match collection.count_documents(doc! { "user_id": &auth.0 }).await {
+        Ok(count) if count >= MAX_WEBHOOKS => {
+            return HttpResponse::BadRequest().body(format!("At most {} webhooks can be registered", MAX_WEBHOOKS));
+        }
+        Ok(_) => {}
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    }
+
+    let now = Utc::now();
+    let mut new_webhook = Webhook {
+        id: None,
+        user_id: auth.0,
+        url,
+        events,
+        secret: format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple()),
+        active: true,
+        consecutive_failures: 0,
+        disabled_reason: None,
+        created_at: now,
+        updated_at: now,
+    };
+
+    match collection.insert_one(&new_webhook).await {
+        Ok(insert_result) => {
+            if let Some(oid) = insert_result.inserted_id.as_object_id() {
+                new_webhook.id = Some(oid.to_hex());
+                let secret = new_webhook.secret.clone();
+                let mut response = WebhookResponse::from(new_webhook);
+                response.secret = Some(secret);
+                HttpResponse::Ok().json(response)
+            } else {
+                HttpResponse::InternalServerError().body("Failed to get inserted ID")
+            }
+        }
+        Err(err) => HttpResponse::InternalServerError().body(format!("Error inserting webhook: {}", err)),
+    }
+}
+
+pub async fn get_webhooks(
+    auth: AuthenticatedUser,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let collection = client.database("rust_backend").collection::<Webhook>("webhooks");
+    let cursor = match collection.find(doc! { "user_id": &auth.0 }).sort(doc! { "created_at": 1 }).await {
+        Ok(c) => c,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    match cursor.try_collect::<Vec<_>>().await {
+        Ok(webhooks) => {
+            HttpResponse::Ok().json(webhooks.into_iter().map(WebhookResponse::from).collect::<Vec<_>>())
+        }
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error reading webhooks: {}", e)),
+    }
+}
+
+pub async fn get_webhook(
+    auth: AuthenticatedUser,
+    webhook_id: web::Path<String>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    match find_webhook(&client, &auth.0, &webhook_id).await {
+        Ok(Some(webhook)) => HttpResponse::Ok().json(WebhookResponse::from(webhook)),
+        Ok(None) => HttpResponse::NotFound().body("Webhook not found"),
+        Err(err) => HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    }
+}
+
+/// Changes the URL or events of a webhook, or turns it off and on. Turning on a
+/// webhook that was disabled after failing clears its failures.
+pub async fn update_webhook(
+    auth: AuthenticatedUser,
+    webhook_id: web::Path<String>,
+    update: web::Json<UpdateWebhook>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let oid = match ObjectId::parse_str(webhook_id.as_str()) {
+        Ok(o) => o,
+        Err(_) => return HttpResponse::BadRequest().body("Invalid webhook ID"),
+    };
+
+    let mut update_doc = Document::new();
+    if let Some(url) = &update.url {
+        match validate_url(url).await {
+            Ok(u) => update_doc.insert("url", u),
+            Err(response) => return response,
+        };
+    }
+    if let Some(events) = &update.events {
+        match validate_events(events) {
+            Ok(e) => update_doc.insert("events", to_bson(&e).unwrap_or(Bson::Null)),
+            Err(response) => return response,
+        };
+    }
+    if let Some(active) = update.active {
+        update_doc.insert("active", active);
+        if active {
+            update_doc.insert("consecutive_failures", 0);
+            update_doc.insert("disabled_reason", Bson::Null);
+        }
+    }
+    update_doc.insert("updated_at", Utc::now().to_string());
+
+    let collection = client.database("rust_backend").collection::<Webhook>("webhooks");
+    match collection
+        .find_one_and_update(doc! { "_id": Bson::ObjectId(oid), "user_id": &auth.0 }, doc! { "$set": update_doc })
+        .return_document(ReturnDocument::After)
+        .await
+    {
+        Ok(Some(updated)) => HttpResponse::Ok().json(WebhookResponse::from(updated)),
+        Ok(None) => HttpResponse::NotFound().body("Webhook not found"),
+        Err(err) => HttpResponse::InternalServerError().body(format!("Error updating webhook: {}", err)),
+    }
+}
+
+/// Deletes a webhook with its delivery log; queued deliveries are dropped.
+pub async fn delete_webhook(
+    auth: AuthenticatedUser,
+    webhook_id: web::Path<String>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let oid = match ObjectId::parse_str(webhook_id.as_str()) {
+        Ok(o) => o,
+        Err(_) => return HttpResponse::BadRequest().body("Invalid webhook ID"),
+    };
+
+    let collection = client.database("rust_backend").collection::<Webhook>("webhooks");
+    match collection.delete_one(doc! { "_id": Bson::ObjectId(oid), "user_id": &auth.0 }).await {
+        Ok(delete_result) if delete_result.deleted_count > 0 => {}
+        Ok(_) => return HttpResponse::NotFound().body("Webhook not found"),
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Error deleting webhook: {}", err)),
+    }
+
+    if let Err(err) = client
+        .database("rust_backend")
+        .collection::<WebhookDelivery>("webhook_deliveries")
+        .delete_many(doc! { "webhook_id": webhook_id.as_str() })
+        .await
+    {
+        eprintln!("❌ failed to delete deliveries of webhook {}: {}", webhook_id, err);
+    }
+    HttpResponse::Ok().body("Webhook deleted successfully")
+}
+
+/// Queues a `ping` delivery to check that the receiver is reachable and verifies
+/// signatures.
+pub async fn test_webhook(
+    auth: AuthenticatedUser,
+    webhook_id: web::Path<String>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let webhook = match find_webhook(&client, &auth.0, &webhook_id).await {
+        Ok(Some(w)) => w,
+        Ok(None) => return HttpResponse::NotFound().body("Webhook not found"),
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    };
+    if !webhook.active {
+        return HttpResponse::Conflict().body("Webhook is disabled");
+    }
+
+    let mut delivery = new_delivery(&webhook, WebhookEvent::Ping, json!({ "webhook_id": &webhook.id }));
+    let collection = client.database("rust_backend").collection::<WebhookDelivery>("webhook_deliveries");
+    match collection.insert_one(&delivery).await {
+        Ok(insert_result) => {
+            delivery.id = insert_result.inserted_id.as_object_id().map(|oid| oid.to_hex());
+            HttpResponse::Accepted().json(WebhookDeliveryResponse::from(delivery))
+        }
+        Err(err) => HttpResponse::InternalServerError().body(format!("Error queueing delivery: {}", err)),
+    }
+}
+
+/// The delivery log of a webhook, newest first.
+pub async fn get_deliveries(
+    auth: AuthenticatedUser,
+    webhook_id: web::Path<String>,
+    query: web::Query<PageQuery>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    match find_webhook(&client, &auth.0, &webhook_id).await {
+        Ok(Some(_)) => {}
+        Ok(None) => return HttpResponse::NotFound().body("Webhook not found"),
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    }
+
+    let collection = client.database("rust_backend").collection::<WebhookDelivery>("webhook_deliveries");
+    let filter = doc! { "webhook_id": webhook_id.as_str() };
+
+    let total = match collection.count_documents(filter.clone()).await {
+        Ok(t) => t,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+    let cursor = match collection
+        .find(filter)
+        .sort(doc! { "created_at": -1, "_id": -1 })
+        .skip(query.skip())
+        .limit(query.limit() as i64)
+        .await
+    {
+        Ok(c) => c,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    match cursor.try_collect::<Vec<_>>().await {
+        Ok(deliveries) => HttpResponse::Ok().json(Page {
+            items: deliveries.into_iter().map(WebhookDeliveryResponse::from).collect(),
+            page: query.page(),
+            limit: query.limit(),
+            total,
+        }),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error reading deliveries: {}", e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_public_addresses_are_public() {
+        for private in ["127.0.0.1", "10.1.2.3", "172.16.0.1", "192.168.1.1", "169.254.169.254", "100.64.0.1", "0.0.0.0", "::1", "fd00::1", "fe80::1", "::ffff:127.0.0.1"] {
+            assert!(!is_public(private.parse().unwrap()), "{} is private", private);
+        }
+        for public in ["93.184.216.34", "100.128.0.1", "2606:4700::1111"] {
+            assert!(is_public(public.parse().unwrap()), "{} is public", public);
+        }
+    }
+}
@@ -0,0 +1,305 @@
+use actix_web::{web, HttpResponse, Responder};
+use mongodb::{bson::{doc, Bson}, Client};
+use mongodb::bson::oid::ObjectId;
+use futures::stream::TryStreamExt;
+use crate::controllers::workspace_controller::{find_workspace, member_workspace_ids};
+use crate::models::task_model::Task;
+use crate::models::workflow_model::{NewWorkflow, Workflow, DEFAULT_WORKFLOW_ID};
+use crate::utils::auth::AuthenticatedUser;
+
+/// Looks up a workflow visible to `user_id`: the built-in default workflow, the user's
+/// own and those shared with their workspaces.
+pub async fn find_workflow(
+    client: &Client,
+    user_id: &str,
+    workflow_id: &str,
+) -> mongodb::error::Result<Option<Workflow>> {
+    if workflow_id == DEFAULT_WORKFLOW_ID {
+        return Ok(Some(Workflow::builtin_default()));
+    }
+
+    let oid = match ObjectId::parse_str(workflow_id) {
+        Ok(o) => o,
+        Err(_) => return Ok(None),
+    };
+
+    let workspace_ids = member_workspace_ids(client, user_id).await?;
+    client
+        .database("rust_backend")
+        .collection::<Workflow>("workflows")
+        .find_one(doc! {
+            "_id": Bson::ObjectId(oid),
+            "$or": [{ "user_id": user_id }, { "workspace_id": { "$in": workspace_ids } }],
+        })
+        .await
+}
+
+/// Looks up a workflow `user_id` can change: only its owner can.
+async fn owned_workflow(client: &Client, user_id: &str, workflow_id: &str) -> Result<Workflow, HttpResponse> {
+    match find_workflow(client, user_id, workflow_id).await {
+        Ok(Some(workflow)) if workflow.user_id == user_id => Ok(workflow),
+        Ok(_) => Err(HttpResponse::NotFound().body("Workflow not found")),
+        Err(err) => Err(HttpResponse::InternalServerError().body(format!("Database error: {}", err))),
+    }
+}
+
+async fn check_workspace(client: &Client, user_id: &str, workspace_id: Option<&str>) -> Result<(), HttpResponse> {
+    let Some(workspace_id) = workspace_id else { return Ok(()) };
+    match find_workspace(client, user_id, workspace_id).await {
+        Ok(Some(_)) => Ok(()),
+        Ok(None) => Err(HttpResponse::BadRequest().body("Workspace not found")),
+        Err(err) => Err(HttpResponse::InternalServerError().body(format!("Database error: {}", err))),
+    }
+}
+
+/// The workflow new tasks use when none is given: the user's default, or the built-in one.
+pub async fn default_workflow(client: &Client, user_id: &str) -> mongodb::error::Result<Workflow> {
+    let found = client
+        .database("rust_backend")
+        .collection::<Workflow>("workflows")
+        .find_one(doc! { "user_id": user_id, "is_default": true })
+        .await?;
+
+    Ok(found.unwrap_or_else(Workflow::builtin_default))
+}
+
+async fn clear_default(client: &Client, user_id: &str) -> mongodb::error::Result<()> {
+    client
+        .database("rust_backend")
+        .collection::<Workflow>("workflows")
+        .update_many(
+            doc! { "user_id": user_id, "is_default": true },
+            doc! { "$set": { "is_default": false } },
+        )
+        .await?;
+    Ok(())
+}
+
+fn workflow_to_bson(workflow: &Workflow) -> Result<(Bson, Bson), String> {
+    let statuses = mongodb::bson::to_bson(&workflow.statuses).map_err(|e| e.to_string())?;
+    let transitions = mongodb::bson::to_bson(&workflow.transitions).map_err(|e| e.to_string())?;
+    Ok((statuses, transitions))
+}
+
+pub async fn create_workflow(
+    auth: AuthenticatedUser,
+    workflow: web::Json<NewWorkflow>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let user_id = auth.0;
+    let workflow = workflow.into_inner();
+
+    let mut new_workflow = Workflow {
+        id: None,
+        user_id: user_id.clone(),
+        workspace_id: workflow.workspace_id,
+        name: workflow.name,
+        statuses: workflow.statuses,
+        transitions: workflow.transitions,
+        is_default: workflow.is_default.unwrap_or(false),
+    };
+
+    if let Err(msg) = new_workflow.validate() {
+        return HttpResponse::BadRequest().body(msg);
+    }
+    if let Err(response) = check_workspace(&client, &user_id, new_workflow.workspace_id.as_deref()).await {
+        return response;
+    }
+
+    if new_workflow.is_default
+        && let Err(err) = clear_default(&client, &user_id).await
+    {
+        return HttpResponse::InternalServerError().body(format!("Database error: {}", err));
+    }
+
+    let collection = client.database("rust_backend").collection::<Workflow>("workflows");
+
+    match collection.insert_one(&new_workflow).await {
+        Ok(insert_result) => {
+            if let Some(oid) = insert_result.inserted_id.as_object_id() {
+                new_workflow.id = Some(oid.to_hex());
+                HttpResponse::Ok().json(new_workflow)
+            } else {
+                HttpResponse::InternalServerError().body("Failed to get inserted ID")
+            }
+        }
+        Err(err) => HttpResponse::InternalServerError().body(format!("Error inserting workflow: {}", err)),
+    }
+}
+
+/// Lists the built-in workflow, then the caller's own and shared workflows.
+pub async fn get_workflows(
+    auth: AuthenticatedUser,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let workspace_ids = match member_workspace_ids(&client, &auth.0).await {
+        Ok(ids) => ids,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    let collection = client.database("rust_backend").collection::<Workflow>("workflows");
+    let cursor = match collection
+        .find(doc! { "$or": [{ "user_id": &auth.0 }, { "workspace_id": { "$in": workspace_ids } }] })
+        .await
+    {
+        Ok(c) => c,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    let mut workflows = vec![Workflow::builtin_default()];
+    match cursor.try_collect::<Vec<_>>().await {
+        Ok(docs) => workflows.extend(docs),
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Error reading workflows: {}", e)),
+    }
+
+    HttpResponse::Ok().json(workflows)
+}
+
+pub async fn get_workflow(
+    auth: AuthenticatedUser,
+    workflow_id: web::Path<String>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    match find_workflow(&client, &auth.0, &workflow_id).await {
+        Ok(Some(workflow)) => HttpResponse::Ok().json(workflow),
+        Ok(None) => HttpResponse::NotFound().body("Workflow not found"),
+        Err(err) => HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    }
+}
+
+pub async fn update_workflow(
+    auth: AuthenticatedUser,
+    workflow_id: web::Path<String>,
+    update: web::Json<NewWorkflow>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let user_id = auth.0;
+    let workflow_id = workflow_id.into_inner();
+    let update = update.into_inner();
+
+    if workflow_id == DEFAULT_WORKFLOW_ID {
+        return HttpResponse::BadRequest().body("The default workflow cannot be modified");
+    }
+
+    let oid = match ObjectId::parse_str(&workflow_id) {
+        Ok(o) => o,
+        Err(_) => return HttpResponse::BadRequest().body("Invalid workflow ID"),
+    };
+
+    let previous = match owned_workflow(&client, &user_id, &workflow_id).await {
+        Ok(workflow) => workflow,
+        Err(response) => return response,
+    };
+
+    let workflow = Workflow {
+        id: Some(workflow_id.clone()),
+        user_id: user_id.clone(),
+        workspace_id: update.workspace_id,
+        name: update.name,
+        statuses: update.statuses,
+        transitions: update.transitions,
+        is_default: update.is_default.unwrap_or(false),
+    };
+
+    if let Err(msg) = workflow.validate() {
+        return HttpResponse::BadRequest().body(msg);
+    }
+    if let Err(response) = check_workspace(&client, &user_id, workflow.workspace_id.as_deref()).await {
+        return response;
+    }
+
+    let tasks = client.database("rust_backend").collection::<Task>("tasks");
+    // Members keep the workflow of their tasks, so it stays shared while they use it.
+    if workflow.workspace_id != previous.workspace_id {
+        match tasks.count_documents(doc! { "workflow_id": &workflow_id, "user_id": { "$ne": &user_id } }).await {
+            Ok(0) => {}
+            Ok(_) => return HttpResponse::Conflict().body("Other members' tasks still use this workflow"),
+            Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+        }
+    }
+
+    // Refuse to drop statuses that tasks are still sitting in.
+    let status_names: Vec<&str> = workflow.statuses.iter().map(|s| s.name.as_str()).collect();
+    match tasks
+        .count_documents(doc! { "workflow_id": &workflow_id, "status": { "$nin": status_names } })
+        .await
+    {
+        Ok(0) => {}
+        Ok(_) => return HttpResponse::Conflict().body("Tasks still use a status removed from this workflow"),
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    }
+
+    if workflow.is_default
+        && let Err(err) = clear_default(&client, &user_id).await
+    {
+        return HttpResponse::InternalServerError().body(format!("Database error: {}", err));
+    }
+
+    let (statuses, transitions) = match workflow_to_bson(&workflow) {
+        Ok(v) => v,
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Serialization error: {}", err)),
+    };
+
+    let collection = client.database("rust_backend").collection::<Workflow>("workflows");
+
+    match collection.update_one(
+        doc! { "_id": Bson::ObjectId(oid), "user_id": &user_id },
+        doc! { "$set": {
+            "workspace_id": &workflow.workspace_id,
+            "name": &workflow.name,
+            "statuses": statuses,
+            "transitions": transitions,
+            "is_default": workflow.is_default,
+        } },
+    ).await {
+        Ok(update_result) => {
+            if update_result.matched_count > 0 {
+                HttpResponse::Ok().json(workflow)
+            } else {
+                HttpResponse::NotFound().body("Workflow not found")
+            }
+        }
+        Err(err) => HttpResponse::InternalServerError().body(format!("Error updating workflow: {}", err)),
+    }
+}
+
+pub async fn delete_workflow(
+    auth: AuthenticatedUser,
+    workflow_id: web::Path<String>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let workflow_id = workflow_id.into_inner();
+
+    if workflow_id == DEFAULT_WORKFLOW_ID {
+        return HttpResponse::BadRequest().body("The default workflow cannot be deleted");
+    }
+
+    let oid = match ObjectId::parse_str(&workflow_id) {
+        Ok(o) => o,
+        Err(_) => return HttpResponse::BadRequest().body("Invalid workflow ID"),
+    };
+
+    if let Err(response) = owned_workflow(&client, &auth.0, &workflow_id).await {
+        return response;
+    }
+
+    let tasks = client.database("rust_backend").collection::<Task>("tasks");
+    match tasks.count_documents(doc! { "workflow_id": &workflow_id }).await {
+        Ok(0) => {}
+        Ok(_) => return HttpResponse::Conflict().body("Workflow is still used by tasks"),
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    }
+
+    let collection = client.database("rust_backend").collection::<Workflow>("workflows");
+
+    match collection.delete_one(doc! { "_id": Bson::ObjectId(oid), "user_id": &auth.0 }).await {
+        Ok(delete_result) => {
+            if delete_result.deleted_count > 0 {
+                HttpResponse::Ok().body("Workflow deleted successfully")
+            } else {
+                HttpResponse::NotFound().body("Workflow not found")
+            }
+        }
+        Err(err) => HttpResponse::InternalServerError().body(format!("Error deleting workflow: {}", err)),
+    }
+}
@@ -0,0 +1,286 @@
+use actix_web::{web, HttpResponse, Responder};
+use mongodb::{bson::{doc, Bson}, options::ReturnDocument, Client};
+use mongodb::bson::oid::ObjectId;
+use futures::stream::TryStreamExt;
+use chrono::Utc;
+use crate::controllers::task_controller::log_history;
+use crate::models::history_model::{diff_fields, HistoryAction};
+use crate::models::task_model::Task;
+use crate::models::user_model::User;
+use crate::models::view_model::SavedView;
+use crate::models::workspace_model::{NewMember, NewWorkspace, Workspace};
+use crate::utils::auth::AuthenticatedUser;
+
+/// Looks up a workspace `user_id` is a member of; malformed ids are treated as not found.
+pub async fn find_workspace(
+    client: &Client,
+    user_id: &str,
+    workspace_id: &str,
+) -> mongodb::error::Result<Option<Workspace>> {
+    let oid = match ObjectId::parse_str(workspace_id) {
+        Ok(o) => o,
+        Err(_) => return Ok(None),
+    };
+
+    client
+        .database("rust_backend")
+        .collection::<Workspace>("workspaces")
+        .find_one(doc! { "_id": Bson::ObjectId(oid), "member_ids": user_id })
+        .await
+}
+
+/// Ids of the workspaces `user_id` is a member of.
+pub async fn member_workspace_ids(client: &Client, user_id: &str) -> mongodb::error::Result<Vec<String>> {
+    let workspaces: Vec<Workspace> = client
+        .database("rust_backend")
+        .collection::<Workspace>("workspaces")
+        .find(doc! { "member_ids": user_id })
+        .await?
+        .try_collect()
+        .await?;
+
+    Ok(workspaces.into_iter().filter_map(|w| w.id).collect())
+}
+
+pub async fn create_workspace(
+    auth: AuthenticatedUser,
+    workspace: web::Json<NewWorkspace>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let name = workspace.name.trim();
+    if name.is_empty() {
+        return HttpResponse::BadRequest().body("Workspace name is required");
+    }
+
+    let mut new_workspace = Workspace {
+        id: None,
+        name: name.to_string(),
+        owner_id: auth.0.clone(),
+        member_ids: vec![auth.0],
+        created_at: Utc::now(),
+    };
+
+    let collection = client.database("rust_backend").collection::<Workspace>("workspaces");
+
+    match collection.insert_one(&new_workspace).await {
+        Ok(insert_result) => {
+            if let Some(oid) = insert_result.inserted_id.as_object_id() {
+                new_workspace.id = Some(oid.to_hex());
+                HttpResponse::Ok().json(new_workspace)
+            } else {
+                HttpResponse::InternalServerError().body("Failed to get inserted ID")
+            }
+        }
+        Err(err) => HttpResponse::InternalServerError().body(format!("Error inserting workspace: {}", err)),
+    }
+}
+
+pub async fn get_workspaces(
+    auth: AuthenticatedUser,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let collection = client.database("rust_backend").collection::<Workspace>("workspaces");
+
+    let cursor = match collection.find(doc! { "member_ids": &auth.0 }).await {
+        Ok(c) => c,
+        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    };
+
+    match cursor.try_collect::<Vec<_>>().await {
+        Ok(workspaces) => HttpResponse::Ok().json(workspaces),
+        Err(e) => HttpResponse::InternalServerError().body(format!("Error reading workspaces: {}", e)),
+    }
+}
+
+pub async fn get_workspace(
+    auth: AuthenticatedUser,
+    workspace_id: web::Path<String>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    match find_workspace(&client, &auth.0, &workspace_id).await {
+        Ok(Some(workspace)) => HttpResponse::Ok().json(workspace),
+        Ok(None) => HttpResponse::NotFound().body("Workspace not found"),
+        Err(err) => HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    }
+}
+
+/// Adds an existing user to the workspace; only the owner can add members.
+pub async fn add_member(
+    auth: AuthenticatedUser,
+    workspace_id: web::Path<String>,
+    member: web::Json<NewMember>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let mut workspace = match find_workspace(&client, &auth.0, &workspace_id).await {
+        Ok(Some(w)) => w,
+        Ok(None) => return HttpResponse::NotFound().body("Workspace not found"),
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    };
+    if workspace.owner_id != auth.0 {
+        return HttpResponse::Forbidden().body("Only the workspace owner can add members");
+    }
+
+    let user_oid = match ObjectId::parse_str(&member.user_id) {
+        Ok(o) => o,
+        Err(_) => return HttpResponse::BadRequest().body("Invalid user ID"),
+    };
+    let users = client.database("rust_backend").collection::<User>("users");
+    match users.count_documents(doc! { "_id": Bson::ObjectId(user_oid) }).await {
+        Ok(0) => return HttpResponse::NotFound().body("User not found"),
+        Ok(_) => {}
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    }
+
+    let collection = client.database("rust_backend").collection::<Workspace>("workspaces");
+    let oid = match ObjectId::parse_str(workspace_id.as_str()) {
+        Ok(o) => o,
+        Err(_) => return HttpResponse::BadRequest().body("Invalid workspace ID"),
+    };
+
+    match collection
+        .update_one(doc! { "_id": Bson::ObjectId(oid) }, doc! { "$addToSet": { "member_ids": &member.user_id } })
+        .await
+    {
+        Ok(_) => {
+            if !workspace.is_member(&member.user_id) {
+                workspace.member_ids.push(member.user_id.clone());
+            }
+            HttpResponse::Ok().json(workspace)
+        }
+        Err(err) => HttpResponse::InternalServerError().body(format!("Error adding member: {}", err)),
+    }
+}
+
+/// Removes a member, who is also unassigned from the workspace's tasks. The owner can
+/// remove anyone but themselves; other members can only remove themselves.
+pub async fn remove_member(
+    auth: AuthenticatedUser,
+    path: web::Path<(String, String)>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let (workspace_id, member_id) = path.into_inner();
+
+    let workspace = match find_workspace(&client, &auth.0, &workspace_id).await {
+        Ok(Some(w)) => w,
+        Ok(None) => return HttpResponse::NotFound().body("Workspace not found"),
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    };
+    if workspace.owner_id != auth.0 && member_id != auth.0 {
+        return HttpResponse::Forbidden().body("Only the workspace owner can remove other members");
+    }
+    if member_id == workspace.owner_id {
+        return HttpResponse::BadRequest().body("The workspace owner cannot be removed");
+    }
+    if !workspace.is_member(&member_id) {
+        return HttpResponse::NotFound().body("Member not found");
+    }
+
+    let oid = match ObjectId::parse_str(&workspace_id) {
+        Ok(o) => o,
+        Err(_) => return HttpResponse::BadRequest().body("Invalid workspace ID"),
+    };
+
+    let database = client.database("rust_backend");
+    if let Err(err) = database
+        .collection::<Workspace>("workspaces")
+        .update_one(doc! { "_id": Bson::ObjectId(oid) }, doc! { "$pull": { "member_ids": &member_id } })
+        .await
+    {
+        return HttpResponse::InternalServerError().body(format!("Error removing member: {}", err));
+    }
+
+    // Views the member shared stay theirs but are no longer shared.
+    if let Err(err) = database
+        .collection::<SavedView>("views")
+        .update_many(
+            doc! { "workspace_id": &workspace_id, "user_id": &member_id },
+            doc! { "$set": { "workspace_id": Bson::Null } },
+        )
+        .await
+    {
+        return HttpResponse::InternalServerError().body(format!("Error unsharing views: {}", err));
+    }
+
+    match unassign_member(&client, &auth.0, &workspace_id, &member_id).await {
+        Ok(()) => HttpResponse::Ok().body("Member removed successfully"),
+        Err(err) => HttpResponse::InternalServerError().body(format!("Error unassigning member: {}", err)),
+    }
+}
+
+/// Takes a removed member off the workspace's tasks one at a time, so each change is
+/// recorded in the task history and sent to watchers, webhooks and event streams.
+async fn unassign_member(client: &Client, actor_id: &str, workspace_id: &str, member_id: &str) -> mongodb::error::Result<()> {
+    let collection = client.database("rust_backend").collection::<Task>("tasks");
+    let filter = doc! { "workspace_id": workspace_id, "assignee_ids": member_id };
+
+    loop {
+        let now = Utc::now();
+        let before = collection
+            .find_one_and_update(
+                filter.clone(),
+                doc! {
+                    "$pull": { "assignee_ids": member_id },
+                    "$set": { "updated_at": now.to_string() },
+                    "$inc": { "version": 1 },
+                },
+            )
+            .return_document(ReturnDocument::Before)
+            .await?;
+        let Some(before) = before else { return Ok(()) };
+
+        let mut after = before.clone();
+        after.assignee_ids.retain(|id| id != member_id);
+        after.updated_at = now;
+        after.version += 1;
+        let changes = diff_fields(&before.tracked_fields(), &after.tracked_fields());
+        log_history(client, &after, actor_id, HistoryAction::Updated, changes).await;
+    }
+}
+
+pub async fn delete_workspace(
+    auth: AuthenticatedUser,
+    workspace_id: web::Path<String>,
+    client: web::Data<Client>,
+) -> impl Responder {
+    let workspace_id = workspace_id.into_inner();
+
+    let oid = match ObjectId::parse_str(&workspace_id) {
+        Ok(o) => o,
+        Err(_) => return HttpResponse::BadRequest().body("Invalid workspace ID"),
+    };
+
+    match find_workspace(&client, &auth.0, &workspace_id).await {
+        Ok(Some(w)) if w.owner_id == auth.0 => {}
+        Ok(Some(_)) => return HttpResponse::Forbidden().body("Only the workspace owner can delete it"),
+        Ok(None) => return HttpResponse::NotFound().body("Workspace not found"),
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    }
+
+    let tasks = client.database("rust_backend").collection::<Task>("tasks");
+    match tasks.count_documents(doc! { "workspace_id": &workspace_id }).await {
+        Ok(0) => {}
+        Ok(_) => return HttpResponse::Conflict().body("Workspace still has tasks"),
+        Err(err) => return HttpResponse::InternalServerError().body(format!("Database error: {}", err)),
+    }
+
+    let collection = client.database("rust_backend").collection::<Workspace>("workspaces");
+
+    match collection.delete_one(doc! { "_id": Bson::ObjectId(oid), "owner_id": &auth.0 }).await {
+        Ok(delete_result) => {
+            if delete_result.deleted_count > 0 {
+                if let Err(err) = client
+                    .database("rust_backend")
+                    .collection::<SavedView>("views")
+                    .update_many(doc! { "workspace_id": &workspace_id }, doc! { "$set": { "workspace_id": Bson::Null } })
+                    .await
+                {
+                    eprintln!("❌ failed to unshare views of workspace {}: {}", workspace_id, err);
+                }
+                HttpResponse::Ok().body("Workspace deleted successfully")
+            } else {
+                HttpResponse::NotFound().body("Workspace not found")
+            }
+        }
+        Err(err) => HttpResponse::InternalServerError().body(format!("Error deleting workspace: {}", err)),
+    }
+}
@@ -0,0 +1,191 @@
+use futures::stream::TryStreamExt;
+use mongodb::{Client, IndexModel, bson::{doc, oid::ObjectId}, error::Result, options::IndexOptions};
+
+use crate::models::calendar_model::CalendarFeed;
+use crate::models::event_model::TaskEvent;
+use crate::models::task_model::Task;
+use crate::models::time_entry_model::TimeEntry;
+use crate::models::webhook_model::WebhookDelivery;
+use crate::models::workflow_model::{StatusCategory, Workflow, DEFAULT_WORKFLOW_ID};
+use crate::utils::rank::rank_sequence;
+
+pub async fn run_migrations(client: &Client) -> Result<()> {
+    migrate_task_workflows(client).await?;
+    migrate_task_versions(client).await?;
+    migrate_task_assignees(client).await?;
+    migrate_task_completion(client).await?;
+    migrate_task_ranks(client).await?;
+    create_time_entry_indexes(client).await?;
+    create_calendar_feed_indexes(client).await?;
+    create_task_event_indexes(client).await?;
+    create_webhook_delivery_indexes(client).await?;
+    Ok(())
+}
+
+/// Tasks created before workflows existed used the fixed Pending/InProgress/Completed
+/// statuses, which are exactly the statuses of the built-in default workflow.
+async fn migrate_task_workflows(client: &Client) -> Result<()> {
+    let collection = client.database("rust_backend").collection::<Task>("tasks");
+
+    let result = collection
+        .update_many(
+            doc! { "workflow_id": { "$exists": false } },
+            doc! { "$set": { "workflow_id": DEFAULT_WORKFLOW_ID } },
+        )
+        .await?;
+
+    if result.modified_count > 0 {
+        println!("✅ Moved {} tasks to the default workflow", result.modified_count);
+    }
+
+    Ok(())
+}
+
+/// Tasks written before optimistic concurrency control start at version 1.
+async fn migrate_task_versions(client: &Client) -> Result<()> {
+    let collection = client.database("rust_backend").collection::<Task>("tasks");
+
+    collection
+        .update_many(
+            doc! { "version": { "$exists": false } },
+            doc! { "$set": { "version": 1 } },
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Before assignees existed the owner was also creator and assignee.
+async fn migrate_task_assignees(client: &Client) -> Result<()> {
+    let collection = client.database("rust_backend").collection::<Task>("tasks");
+
+    collection
+        .update_many(
+            doc! { "creator_id": { "$exists": false } },
+            vec![doc! { "$set": { "creator_id": "$user_id", "assignee_ids": ["$user_id"] } }],
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Tasks that were already done when completion times started being recorded are
+/// treated as completed at their last update.
+async fn migrate_task_completion(client: &Client) -> Result<()> {
+    let database = client.database("rust_backend");
+    let tasks = database.collection::<Task>("tasks");
+
+    let mut workflows: Vec<Workflow> = database
+        .collection::<Workflow>("workflows")
+        .find(doc! {})
+        .await?
+        .try_collect()
+        .await?;
+    workflows.push(Workflow::builtin_default());
+
+    for workflow in &workflows {
+        let done: Vec<&str> = workflow
+            .statuses
+            .iter()
+            .filter(|s| s.category == StatusCategory::Done)
+            .map(|s| s.name.as_str())
+            .collect();
+        tasks
+            .update_many(
+                doc! {
+                    "workflow_id": workflow.id.as_deref().unwrap_or_default(),
+                    "status": { "$in": done },
+                    "completed_at": { "$exists": false },
+                },
+                vec![doc! { "$set": { "completed_at": "$updated_at" } }],
+            )
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Gives unranked tasks board positions in creation order.
+async fn migrate_task_ranks(client: &Client) -> Result<()> {
+    let collection = client.database("rust_backend").collection::<Task>("tasks");
+
+    let tasks: Vec<Task> = collection
+        .find(doc! { "rank": { "$exists": false } })
+        .sort(doc! { "created_at": 1 })
+        .await?
+        .try_collect()
+        .await?;
+
+    let ranks = rank_sequence(tasks.len());
+    for (task, rank) in tasks.iter().zip(&ranks) {
+        let Some(oid) = task.id.as_deref().and_then(|id| ObjectId::parse_str(id).ok()) else { continue };
+        collection
+            .update_one(doc! { "_id": oid }, doc! { "$set": { "rank": rank } })
+            .await?;
+    }
+
+    if !tasks.is_empty() {
+        println!("✅ Ranked {} tasks for the board", tasks.len());
+    }
+
+    Ok(())
+}
+
+/// Enforces at most one running timer per user.
+async fn create_time_entry_indexes(client: &Client) -> Result<()> {
+    let collection = client.database("rust_backend").collection::<TimeEntry>("time_entries");
+
+    let running_timer = IndexModel::builder()
+        .keys(doc! { "user_id": 1 })
+        .options(
+            IndexOptions::builder()
+                .name("one_running_timer_per_user".to_string())
+                .unique(true)
+                .partial_filter_expression(doc! { "running": true })
+                .build(),
+        )
+        .build();
+    collection.create_index(running_timer).await?;
+
+    Ok(())
+}
+
+/// Feeds are looked up by token, and each user has at most one.
+async fn create_calendar_feed_indexes(client: &Client) -> Result<()> {
+    let collection = client.database("rust_backend").collection::<CalendarFeed>("calendar_feeds");
+
+    for key in ["token_hash", "user_id"] {
+        let index = IndexModel::builder()
+            .keys(doc! { key: 1 })
+            .options(IndexOptions::builder().unique(true).build())
+            .build();
+        collection.create_index(index).await?;
+    }
+
+    Ok(())
+}
+
+/// Streams read events by sequence number. Events from before there was one can't be
+/// resumed from and are only kept for a day anyway, so they are dropped.
+async fn create_task_event_indexes(client: &Client) -> Result<()> {
+    let collection = client.database("rust_backend").collection::<TaskEvent>("task_events");
+    collection.delete_many(doc! { "seq": { "$exists": false } }).await?;
+
+    let index = IndexModel::builder()
+        .keys(doc! { "seq": 1 })
+        .options(IndexOptions::builder().unique(true).build())
+        .build();
+    collection.create_index(index).await?;
+
+    Ok(())
+}
+
+/// The delivery job claims the pending delivery due first, over and over.
+async fn create_webhook_delivery_indexes(client: &Client) -> Result<()> {
+    let collection = client.database("rust_backend").collection::<WebhookDelivery>("webhook_deliveries");
+
+    let index = IndexModel::builder().keys(doc! { "status": 1, "next_attempt_at": 1 }).build();
+    collection.create_index(index).await?;
+
+    Ok(())
+}
@@ -0,0 +1,87 @@
+//! Parsers turning the exports of other tools into tasks to import.
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use crate::models::import_model::ImportFormat;
+use crate::models::task_model::Priority;
+
+pub mod todoist;
+pub mod trello;
+pub mod todo_txt;
+
+#[derive(Debug, Clone)]
+pub struct ChecklistItem {
+    pub text: String,
+    pub done: bool,
+}
+
+/// A task read from another tool, before it is matched to a workflow and projects.
+#[derive(Debug, Default)]
+pub struct ImportedTask {
+    pub title: String,
+    pub description: Option<String>,
+    /// The list, column or section the task was in.
+    pub column: Option<String>,
+    pub project: Option<String>,
+    pub labels: Vec<String>,
+    pub priority: Option<Priority>,
+    pub due_at: Option<DateTime<Utc>>,
+    pub done: bool,
+    /// Checklist items and subtasks, kept as a Markdown task list in the description.
+    pub checklist: Vec<ChecklistItem>,
+}
+
+impl ImportedTask {
+    pub fn full_description(&self) -> Option<String> {
+        let items: Vec<String> = self
+            .checklist
+            .iter()
+            .map(|item| format!("- [{}] {}", if item.done { "x" } else { " " }, item.text.replace('\n', " ")))
+            .collect();
+        match (&self.description, items.is_empty()) {
+            (description, true) => description.clone(),
+            (Some(description), false) => Some(format!("{}\n\n{}", description, items.join("\n"))),
+            (None, false) => Some(items.join("\n")),
+        }
+    }
+
+    /// Adds a paragraph, such as a comment, to the description.
+    fn append_description(&mut self, text: &str) {
+        self.description = Some(match self.description.take() {
+            Some(description) => format!("{}\n\n{}", description, text),
+            None => text.to_string(),
+        });
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Parsed {
+    pub tasks: Vec<ImportedTask>,
+    pub warnings: Vec<String>,
+}
+
+/// Reads an RFC 3339 date-time, a date-time without a zone, or a date; the last two
+/// are taken as UTC.
+pub fn parse_date(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if let Ok(date) = DateTime::parse_from_rfc3339(value) {
+        return Some(date.with_timezone(&Utc));
+    }
+    if let Ok(date) = NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S") {
+        return Some(date.and_utc());
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()?.and_hms_opt(0, 0, 0).map(|d| d.and_utc())
+}
+
+fn optional(value: &str) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+pub fn parse(format: ImportFormat, data: &str) -> Result<Parsed, String> {
+    match format {
+        ImportFormat::TodoistCsv => todoist::parse_csv(data),
+        ImportFormat::TodoistJson => todoist::parse_json(data),
+        ImportFormat::Trello => trello::parse(data),
+        ImportFormat::TodoTxt => Ok(todo_txt::parse(data)),
+    }
+}
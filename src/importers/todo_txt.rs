@@ -0,0 +1,114 @@
+//! todo.txt files (http://todotxt.org).
+
+use chrono::NaiveDate;
+use crate::importers::{parse_date, ImportedTask, Parsed};
+use crate::models::task_model::Priority;
+
+fn is_date(word: &str) -> bool {
+    NaiveDate::parse_from_str(word, "%Y-%m-%d").is_ok()
+}
+
+/// (A) is the most important; anything below (C) counts as low.
+fn priority(letter: char) -> Priority {
+    match letter {
+        'A' => Priority::Urgent,
+        'B' => Priority::High,
+        'C' => Priority::Medium,
+        _ => Priority::Low,
+    }
+}
+
+fn priority_letter(word: &str) -> Option<char> {
+    let mut chars = word.strip_prefix('(')?.strip_suffix(')')?.chars();
+    match (chars.next(), chars.next()) {
+        (Some(letter), None) if letter.is_ascii_uppercase() => Some(letter),
+        _ => None,
+    }
+}
+
+/// Reads one task per line. `x` marks it done, `(A)` to `(Z)` is its priority, the
+/// first `+project` its project, `@contexts` become labels and `due:` its due date.
+/// Completed tasks keep their priority as `pri:A`.
+pub fn parse(data: &str) -> Parsed {
+    let mut parsed = Parsed::default();
+    for line in data.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        let mut words = line.split_whitespace().peekable();
+        let mut task = ImportedTask::default();
+
+        if words.peek() == Some(&"x") {
+            task.done = true;
+            words.next();
+            // Completion and creation dates.
+            while words.peek().is_some_and(|w| is_date(w)) {
+                words.next();
+            }
+        } else {
+            if let Some(letter) = words.peek().and_then(|w| priority_letter(w)) {
+                task.priority = Some(priority(letter));
+                words.next();
+            }
+            if words.peek().is_some_and(|w| is_date(w)) {
+                words.next();
+            }
+        }
+
+        let mut title = Vec::new();
+        for word in words {
+            if let Some(project) = word.strip_prefix('+').filter(|p| !p.is_empty()) {
+                task.project.get_or_insert_with(|| project.to_string());
+            } else if let Some(context) = word.strip_prefix('@').filter(|c| !c.is_empty()) {
+                task.labels.push(context.to_string());
+            } else if let Some(due) = word.strip_prefix("due:") {
+                match parse_date(due) {
+                    Some(due_at) => task.due_at = Some(due_at),
+                    None => parsed.warnings.push(format!("\"{}\": due date \"{}\" is not a date and was left out", line, due)),
+                }
+            } else if let Some(letter) = word.strip_prefix("pri:").and_then(|p| p.chars().next()) {
+                task.priority = Some(priority(letter.to_ascii_uppercase()));
+            } else {
+                title.push(word);
+            }
+        }
+
+        task.title = if title.is_empty() { line.to_string() } else { title.join(" ") };
+        parsed.tasks.push(task);
+    }
+    parsed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_priorities_projects_contexts_and_due_dates() {
+        let parsed = parse("(A) 2025-01-01 Call mom +Family @phone due:2025-02-03\n\n");
+        assert_eq!(parsed.tasks.len(), 1);
+
+        let task = &parsed.tasks[0];
+        assert_eq!(task.title, "Call mom");
+        assert_eq!(task.priority, Some(Priority::Urgent));
+        assert_eq!(task.project.as_deref(), Some("Family"));
+        assert_eq!(task.labels, vec!["phone"]);
+        assert_eq!(task.due_at.unwrap().to_string(), "2025-02-03 00:00:00 UTC");
+        assert!(!task.done);
+    }
+
+    #[test]
+    fn reads_completed_tasks() {
+        let parsed = parse("x 2025-01-02 2025-01-01 Pay rent pri:b +Home +Money");
+        let task = &parsed.tasks[0];
+        assert!(task.done);
+        assert_eq!(task.title, "Pay rent");
+        assert_eq!(task.priority, Some(Priority::High));
+        assert_eq!(task.project.as_deref(), Some("Home"));
+    }
+
+    #[test]
+    fn warns_about_bad_due_dates() {
+        let parsed = parse("Water plants due:tomorrow");
+        assert_eq!(parsed.tasks[0].title, "Water plants");
+        assert!(parsed.tasks[0].due_at.is_none());
+        assert_eq!(parsed.warnings.len(), 1);
+    }
+}
@@ -0,0 +1,336 @@
+//! Todoist project CSV exports and JSON backups.
+
+use std::collections::HashMap;
+use serde::Deserialize;
+use serde_json::Value;
+use crate::importers::{optional, parse_date, ChecklistItem, ImportedTask, Parsed};
+use crate::models::task_model::Priority;
+
+/// Todoist shows p1 (most important) to p4 (none); CSV files use the same numbers.
+fn csv_priority(priority: &str) -> Option<Priority> {
+    match priority.trim() {
+        "1" => Some(Priority::Urgent),
+        "2" => Some(Priority::High),
+        "3" => Some(Priority::Medium),
+        "4" => Some(Priority::Low),
+        _ => None,
+    }
+}
+
+/// The API numbers priorities the other way round: 4 is p1.
+fn api_priority(priority: u64) -> Priority {
+    match priority {
+        4 => Priority::Urgent,
+        3 => Priority::High,
+        2 => Priority::Medium,
+        _ => Priority::Low,
+    }
+}
+
+/// Splits the `@labels` out of a task's content.
+fn split_labels(content: &str) -> (String, Vec<String>) {
+    let mut labels = Vec::new();
+    let mut words = Vec::new();
+    for word in content.split_whitespace() {
+        match word.strip_prefix('@') {
+            Some(label) if !label.is_empty() => labels.push(label.to_string()),
+            _ => words.push(word),
+        }
+    }
+    (words.join(" "), labels)
+}
+
+fn due_date(task: &mut ImportedTask, date: &str, warnings: &mut Vec<String>) {
+    if date.trim().is_empty() {
+        return;
+    }
+    match parse_date(date) {
+        Some(due_at) => task.due_at = Some(due_at),
+        None => warnings.push(format!("\"{}\": due date \"{}\" is not a date and was left out", task.title, date.trim())),
+    }
+}
+
+/// Reads a project exported as CSV. Sections become columns, indented tasks checklist
+/// items of the task above and notes paragraphs of its description.
+pub fn parse_csv(data: &str) -> Result<Parsed, String> {
+    let mut reader = csv::ReaderBuilder::new().flexible(true).from_reader(data.as_bytes());
+    let headers = reader.headers().map_err(|e| format!("Invalid CSV header: {}", e))?.clone();
+    let column = |name: &str| headers.iter().position(|h| h.trim().eq_ignore_ascii_case(name));
+    let (Some(kind), Some(content)) = (column("TYPE"), column("CONTENT")) else {
+        return Err("Not a Todoist CSV export: TYPE and CONTENT columns are required".to_string());
+    };
+    let (description, priority, indent, date) = (column("DESCRIPTION"), column("PRIORITY"), column("INDENT"), column("DATE"));
+
+    let mut parsed = Parsed::default();
+    let mut section = None;
+    for record in reader.records() {
+        let record = record.map_err(|e| format!("Invalid CSV: {}", e))?;
+        let field = |index: Option<usize>| index.and_then(|i| record.get(i)).unwrap_or_default();
+
+        match field(Some(kind)).trim() {
+            "section" => section = optional(field(Some(content))),
+            "note" => {
+                if let (Some(task), Some(note)) = (parsed.tasks.last_mut(), optional(field(Some(content)))) {
+                    task.append_description(&note);
+                }
+            }
+            "task" => {
+                let (title, labels) = split_labels(field(Some(content)));
+                let nested = field(indent).trim().parse::<u32>().is_ok_and(|i| i > 1);
+                if nested && let Some(parent) = parsed.tasks.last_mut() {
+                    parent.checklist.push(ChecklistItem { text: title, done: false });
+                    continue;
+                }
+
+                let mut task = ImportedTask {
+                    title,
+                    description: optional(field(description)),
+                    column: section.clone(),
+                    labels,
+                    priority: csv_priority(field(priority)),
+                    ..Default::default()
+                };
+                due_date(&mut task, field(date), &mut parsed.warnings);
+                parsed.tasks.push(task);
+            }
+            _ => {}
+        }
+    }
+    Ok(parsed)
+}
+
+#[derive(Debug, Deserialize)]
+struct Backup {
+    #[serde(default)]
+    items: Vec<Item>,
+    #[serde(default)]
+    projects: Vec<Named>,
+    #[serde(default)]
+    sections: Vec<Named>,
+    #[serde(default)]
+    notes: Vec<Note>,
+}
+
+/// Ids are strings in current backups and numbers in older ones.
+#[derive(Debug, Deserialize)]
+struct Item {
+    id: Value,
+    content: String,
+    #[serde(default)]
+    description: String,
+    project_id: Option<Value>,
+    section_id: Option<Value>,
+    parent_id: Option<Value>,
+    #[serde(default)]
+    labels: Vec<String>,
+    priority: Option<u64>,
+    due: Option<Due>,
+    #[serde(default)]
+    checked: Value,
+    #[serde(default)]
+    is_deleted: Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct Due {
+    date: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Named {
+    id: Value,
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Note {
+    item_id: Value,
+    content: String,
+}
+
+fn id(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// Flags are booleans, or 0 and 1 in older backups.
+fn flag(value: &Value) -> bool {
+    value.as_bool().unwrap_or_else(|| value.as_u64().is_some_and(|v| v != 0))
+}
+
+/// Reads a backup. Projects keep their names, sections become columns, subtasks
+/// checklist items of their top-level task and notes paragraphs of its description.
+pub fn parse_json(data: &str) -> Result<Parsed, String> {
+    let backup: Backup = serde_json::from_str(data).map_err(|e| format!("Not a Todoist backup: {}", e))?;
+    let names = |named: &[Named]| -> HashMap<String, String> {
+        named.iter().filter_map(|n| Some((id(&n.id)?, n.name.clone()))).collect()
+    };
+    let (projects, sections) = (names(&backup.projects), names(&backup.sections));
+
+    let items: Vec<&Item> = backup.items.iter().filter(|item| !flag(&item.is_deleted)).collect();
+    let parents: HashMap<String, Option<String>> = items
+        .iter()
+        .filter_map(|item| Some((id(&item.id)?, item.parent_id.as_ref().and_then(id))))
+        .collect();
+    // The top-level task a subtask belongs to; `None` for top-level tasks.
+    let root = |item: &Item| {
+        let mut current = item.parent_id.as_ref().and_then(id)?;
+        for _ in 0..parents.len() {
+            match parents.get(&current) {
+                Some(Some(parent)) => current = parent.clone(),
+                _ => break,
+            }
+        }
+        Some(current)
+    };
+
+    let mut parsed = Parsed::default();
+    let mut positions: HashMap<String, usize> = HashMap::new();
+    for item in items.iter().filter(|item| root(item).is_none()) {
+        let mut task = ImportedTask {
+            title: item.content.trim().to_string(),
+            description: optional(&item.description),
+            column: item.section_id.as_ref().and_then(id).and_then(|s| sections.get(&s).cloned()),
+            project: item.project_id.as_ref().and_then(id).and_then(|p| projects.get(&p).cloned()),
+            labels: item.labels.clone(),
+            priority: item.priority.map(api_priority),
+            done: flag(&item.checked),
+            ..Default::default()
+        };
+        if let Some(due) = &item.due {
+            due_date(&mut task, &due.date, &mut parsed.warnings);
+        }
+        if let Some(item_id) = id(&item.id) {
+            positions.insert(item_id, parsed.tasks.len());
+        }
+        parsed.tasks.push(task);
+    }
+
+    for item in &items {
+        if let Some(position) = root(item).and_then(|r| positions.get(&r)) {
+            let text = item.content.trim().to_string();
+            parsed.tasks[*position].checklist.push(ChecklistItem { text, done: flag(&item.checked) });
+        }
+    }
+    for note in &backup.notes {
+        if let Some(position) = id(&note.item_id).and_then(|i| positions.get(&i))
+            && let Some(content) = optional(&note.content)
+        {
+            parsed.tasks[*position].append_description(&content);
+        }
+    }
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CSV: &str = "\
+TYPE,CONTENT,DESCRIPTION,PRIORITY,INDENT,AUTHOR,RESPONSIBLE,DATE,DATE_LANG,TIMEZONE
+task,Plan trip @travel,Summer,1,1,,,2025-06-01,en,UTC
+task,Book flights,,4,2,,,,en,UTC
+task,Book hotel,,4,3,,,,en,UTC
+note,Ask about pets,,,,,,,,
+,,,,,,,,,
+section,Doing,,,,,,,,
+task,Pack,,2,1,,,someday,en,UTC
+";
+
+    #[test]
+    fn csv_nests_indented_tasks_and_appends_notes() {
+        let parsed = parse_csv(CSV).unwrap();
+        assert_eq!(parsed.tasks.len(), 2);
+
+        let trip = &parsed.tasks[0];
+        assert_eq!(trip.title, "Plan trip");
+        assert_eq!(trip.labels, vec!["travel"]);
+        assert_eq!(trip.priority, Some(Priority::Urgent));
+        assert_eq!(trip.due_at.unwrap().to_string(), "2025-06-01 00:00:00 UTC");
+        assert!(trip.column.is_none());
+        let items: Vec<&str> = trip.checklist.iter().map(|item| item.text.as_str()).collect();
+        assert_eq!(items, vec!["Book flights", "Book hotel"]);
+        assert_eq!(trip.description.as_deref(), Some("Summer\n\nAsk about pets"));
+    }
+
+    #[test]
+    fn csv_sections_become_columns() {
+        let parsed = parse_csv(CSV).unwrap();
+        let pack = &parsed.tasks[1];
+        assert_eq!(pack.title, "Pack");
+        assert_eq!(pack.column.as_deref(), Some("Doing"));
+        assert_eq!(pack.priority, Some(Priority::High));
+        assert!(pack.due_at.is_none());
+        assert_eq!(parsed.warnings.len(), 1);
+    }
+
+    #[test]
+    fn csv_requires_type_and_content() {
+        assert!(parse_csv("NAME,DATE\nPack,2025-06-01\n").is_err());
+    }
+
+    #[test]
+    fn json_reads_numeric_and_string_ids() {
+        let parsed = parse_json(
+            r#"{
+                "projects": [{"id": 1, "name": "Home"}, {"id": "p2", "name": "Work"}],
+                "sections": [{"id": 10, "name": "Later"}],
+                "items": [
+                    {"id": 100, "content": "Fix sink", "project_id": 1, "section_id": 10, "priority": 4},
+                    {"id": "i2", "content": "Report", "project_id": "p2", "priority": 1, "due": {"date": "2025-03-04"}}
+                ],
+                "notes": [{"item_id": 100, "content": "Call plumber"}, {"item_id": "i2", "content": "By Friday"}]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(parsed.tasks.len(), 2);
+
+        let sink = &parsed.tasks[0];
+        assert_eq!(sink.project.as_deref(), Some("Home"));
+        assert_eq!(sink.column.as_deref(), Some("Later"));
+        assert_eq!(sink.priority, Some(Priority::Urgent));
+        assert_eq!(sink.description.as_deref(), Some("Call plumber"));
+
+        let report = &parsed.tasks[1];
+        assert_eq!(report.project.as_deref(), Some("Work"));
+        assert_eq!(report.priority, Some(Priority::Low));
+        assert_eq!(report.due_at.unwrap().to_string(), "2025-03-04 00:00:00 UTC");
+        assert_eq!(report.description.as_deref(), Some("By Friday"));
+    }
+
+    #[test]
+    fn json_folds_subtask_chains_into_the_top_level_task() {
+        let parsed = parse_json(
+            r#"{"items": [
+                {"id": "a", "content": "Move"},
+                {"id": "c", "content": "Label boxes", "parent_id": "b", "checked": 1},
+                {"id": "b", "content": "Pack", "parent_id": "a", "checked": false},
+                {"id": "d", "content": "Return keys", "parent_id": "a", "checked": true}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(parsed.tasks.len(), 1);
+
+        let items: Vec<(&str, bool)> = parsed.tasks[0].checklist.iter().map(|i| (i.text.as_str(), i.done)).collect();
+        assert_eq!(items, vec![("Label boxes", true), ("Pack", false), ("Return keys", true)]);
+    }
+
+    #[test]
+    fn json_skips_deleted_items_and_reads_flags() {
+        let parsed = parse_json(
+            r#"{"items": [
+                {"id": 1, "content": "Gone", "is_deleted": 1},
+                {"id": 2, "content": "Also gone", "is_deleted": true},
+                {"id": 3, "content": "Done", "checked": 1, "is_deleted": 0},
+                {"id": 4, "content": "Open", "checked": 0},
+                {"id": 5, "content": "Orphan", "parent_id": 1}
+            ]}"#,
+        )
+        .unwrap();
+        let tasks: Vec<(&str, bool)> = parsed.tasks.iter().map(|t| (t.title.as_str(), t.done)).collect();
+        assert_eq!(tasks, vec![("Done", true), ("Open", false)]);
+    }
+}
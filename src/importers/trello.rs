@@ -0,0 +1,190 @@
+//! Trello board JSON exports.
+
+use std::collections::HashMap;
+use serde::Deserialize;
+use crate::importers::{optional, parse_date, ChecklistItem, ImportedTask, Parsed};
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Board {
+    #[serde(default)]
+    lists: Vec<List>,
+    cards: Vec<Card>,
+    #[serde(default)]
+    checklists: Vec<Checklist>,
+}
+
+#[derive(Debug, Deserialize)]
+struct List {
+    id: String,
+    name: String,
+    #[serde(default)]
+    closed: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Card {
+    name: String,
+    #[serde(default)]
+    desc: String,
+    id_list: String,
+    #[serde(default)]
+    closed: bool,
+    due: Option<String>,
+    #[serde(default)]
+    due_complete: bool,
+    #[serde(default)]
+    labels: Vec<Label>,
+    #[serde(default)]
+    id_checklists: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Label {
+    #[serde(default)]
+    name: String,
+    color: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Checklist {
+    id: String,
+    #[serde(default)]
+    name: String,
+    #[serde(rename = "checkItems", default)]
+    check_items: Vec<CheckItem>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CheckItem {
+    name: String,
+    state: String,
+    #[serde(default)]
+    pos: f64,
+}
+
+/// Reads a board. Lists become columns, unnamed labels go by their color and checklists
+/// are merged into one. Archived cards and cards of archived lists are skipped.
+pub fn parse(data: &str) -> Result<Parsed, String> {
+    let board: Board = serde_json::from_str(data).map_err(|e| format!("Not a Trello board export: {}", e))?;
+    let lists: HashMap<&str, &List> = board.lists.iter().map(|l| (l.id.as_str(), l)).collect();
+    let checklists: HashMap<&str, &Checklist> = board.checklists.iter().map(|c| (c.id.as_str(), c)).collect();
+
+    let mut parsed = Parsed::default();
+    let mut skipped = 0;
+    for card in &board.cards {
+        let list = lists.get(card.id_list.as_str());
+        if card.closed || list.is_some_and(|l| l.closed) {
+            skipped += 1;
+            continue;
+        }
+
+        let mut task = ImportedTask {
+            title: card.name.trim().to_string(),
+            description: optional(&card.desc),
+            column: list.map(|l| l.name.trim().to_string()),
+            labels: card
+                .labels
+                .iter()
+                .filter_map(|label| optional(&label.name).or_else(|| label.color.clone()))
+                .collect(),
+            done: card.due_complete,
+            ..Default::default()
+        };
+        if let Some(due) = &card.due {
+            match parse_date(due) {
+                Some(due_at) => task.due_at = Some(due_at),
+                None => parsed.warnings.push(format!("\"{}\": due date \"{}\" is not a date and was left out", task.title, due)),
+            }
+        }
+
+        let several = card.id_checklists.len() > 1;
+        for checklist in card.id_checklists.iter().filter_map(|id| checklists.get(id.as_str())) {
+            let mut items: Vec<&CheckItem> = checklist.check_items.iter().collect();
+            items.sort_by(|a, b| a.pos.total_cmp(&b.pos));
+            for item in items {
+                // With several checklists, items say which one they came from.
+                let text = match (several, optional(&checklist.name)) {
+                    (true, Some(name)) => format!("{}: {}", name, item.name.trim()),
+                    _ => item.name.trim().to_string(),
+                };
+                task.checklist.push(ChecklistItem { text, done: item.state == "complete" });
+            }
+        }
+        parsed.tasks.push(task);
+    }
+
+    if skipped > 0 {
+        parsed.warnings.push(format!("Skipped archived cards: {}", skipped));
+    }
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOARD: &str = r#"{
+        "lists": [
+            {"id": "l1", "name": "To do"},
+            {"id": "l2", "name": "Old", "closed": true}
+        ],
+        "cards": [
+            {
+                "name": "Launch",
+                "desc": "Go live",
+                "idList": "l1",
+                "due": "2025-05-06T07:08:09.000Z",
+                "dueComplete": true,
+                "labels": [{"name": "Urgent", "color": "red"}, {"name": "", "color": "green"}, {"color": null}],
+                "idChecklists": ["c1", "c2"]
+            },
+            {"name": "Single", "idList": "l1", "idChecklists": ["c3"]},
+            {"name": "Archived", "idList": "l1", "closed": true},
+            {"name": "In old list", "idList": "l2"}
+        ],
+        "checklists": [
+            {"id": "c1", "name": "Code", "checkItems": [
+                {"name": "Deploy", "state": "incomplete", "pos": 32768},
+                {"name": "Test", "state": "complete", "pos": 16384}
+            ]},
+            {"id": "c2", "name": "Docs", "checkItems": [{"name": "Announce", "state": "incomplete", "pos": 1}]},
+            {"id": "c3", "name": "Steps", "checkItems": [{"name": "Only step", "state": "complete", "pos": 1}]}
+        ]
+    }"#;
+
+    #[test]
+    fn skips_closed_lists_and_cards() {
+        let parsed = parse(BOARD).unwrap();
+        let titles: Vec<&str> = parsed.tasks.iter().map(|t| t.title.as_str()).collect();
+        assert_eq!(titles, vec!["Launch", "Single"]);
+        assert_eq!(parsed.warnings, vec!["Skipped archived cards: 2"]);
+    }
+
+    #[test]
+    fn reads_cards() {
+        let parsed = parse(BOARD).unwrap();
+        let launch = &parsed.tasks[0];
+        assert_eq!(launch.description.as_deref(), Some("Go live"));
+        assert_eq!(launch.column.as_deref(), Some("To do"));
+        assert_eq!(launch.due_at.unwrap().to_rfc3339(), "2025-05-06T07:08:09+00:00");
+        assert!(launch.done);
+        assert_eq!(launch.labels, vec!["Urgent", "green"]);
+    }
+
+    #[test]
+    fn merges_checklists_in_pos_order() {
+        let parsed = parse(BOARD).unwrap();
+        let items: Vec<(&str, bool)> = parsed.tasks[0].checklist.iter().map(|i| (i.text.as_str(), i.done)).collect();
+        assert_eq!(items, vec![("Code: Test", true), ("Code: Deploy", false), ("Docs: Announce", false)]);
+
+        // A single checklist's name is left out.
+        assert_eq!(parsed.tasks[1].checklist[0].text, "Only step");
+    }
+
+    #[test]
+    fn rejects_other_json() {
+        assert!(parse(r#"{"items": []}"#).is_err());
+    }
+}
@@ -0,0 +1,67 @@
+use std::{env, time::Duration as StdDuration};
+use actix_web::rt::time::interval;
+use chrono::{Duration, Utc};
+use futures::stream::TryStreamExt;
+use mongodb::{bson::{doc, oid::ObjectId, Bson}, Client};
+
+use crate::controllers::notification_controller::notify;
+use crate::models::notification_model::NotificationKind;
+use crate::models::task_model::Task;
+
+/// Every few minutes, notifies assignees of open tasks due within `DUE_SOON_HOURS` (default 24).
+pub async fn run(client: Client) {
+    let window_hours: i64 = env::var("DUE_SOON_HOURS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(24);
+
+    let mut ticker = interval(StdDuration::from_secs(5 * 60));
+    loop {
+        ticker.tick().await;
+        if let Err(err) = notify_due_soon(&client, window_hours).await {
+            eprintln!("❌ due soon notifications failed: {}", err);
+        }
+    }
+}
+
+async fn notify_due_soon(client: &Client, window_hours: i64) -> mongodb::error::Result<()> {
+    let now = Utc::now();
+    let collection = client.database("rust_backend").collection::<Task>("tasks");
+
+    let due: Vec<Task> = collection
+        .find(doc! {
+            "deleted_at": Bson::Null,
+            "completed_at": Bson::Null,
+            "due_soon_notified": { "$ne": true },
+            "due_at": { "$gte": now.to_string(), "$lte": (now + Duration::hours(window_hours)).to_string() },
+        })
+        .await?
+        .try_collect()
+        .await?;
+
+    for task in &due {
+        let Some(oid) = task.id.as_deref().and_then(|id| ObjectId::parse_str(id).ok()) else { continue };
+        let Some(due_at) = task.due_at else { continue };
+
+        // Claim the task first so a slow run never notifies twice.
+        let claimed = collection
+            .update_one(
+                doc! { "_id": oid, "completed_at": Bson::Null, "due_soon_notified": { "$ne": true } },
+                doc! { "$set": { "due_soon_notified": true } },
+            )
+            .await?;
+        if claimed.modified_count == 0 {
+            continue;
+        }
+
+        let recipients = if task.assignee_ids.is_empty() {
+            vec![task.user_id.clone()]
+        } else {
+            task.assignee_ids.clone()
+        };
+        let message = format!("\"{}\" is due {}", task.title, due_at.format("%Y-%m-%d %H:%M UTC"));
+        notify(client, &recipients, NotificationKind::DueSoon, task, None, message).await;
+    }
+
+    Ok(())
+}
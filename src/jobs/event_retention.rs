@@ -0,0 +1,29 @@
+use std::{env, time::Duration as StdDuration};
+use actix_web::rt::time::interval;
+use chrono::{Duration, Utc};
+use mongodb::{bson::doc, Client};
+
+use crate::models::event_model::TaskEvent;
+
+/// Hourly drops task events older than `TASK_EVENT_RETENTION_HOURS` (default 24);
+/// event streams can't resume from before then.
+pub async fn run(client: Client) {
+    let retention_hours: i64 = env::var("TASK_EVENT_RETENTION_HOURS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(24);
+
+    let mut ticker = interval(StdDuration::from_secs(60 * 60));
+    loop {
+        ticker.tick().await;
+        let cutoff = Utc::now() - Duration::hours(retention_hours);
+        if let Err(err) = client
+            .database("rust_backend")
+            .collection::<TaskEvent>("task_events")
+            .delete_many(doc! { "created_at": { "$lt": cutoff.to_string() } })
+            .await
+        {
+            eprintln!("❌ task event cleanup failed: {}", err);
+        }
+    }
+}
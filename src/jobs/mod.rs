@@ -0,0 +1,4 @@
+pub mod trash_purge;
+pub mod due_soon;
+pub mod webhook_delivery;
+pub mod event_retention;
@@ -0,0 +1,57 @@
+use std::{env, sync::Arc, time::Duration as StdDuration};
+use actix_web::rt::time::interval;
+use chrono::{Duration, Utc};
+use futures::stream::TryStreamExt;
+use mongodb::{bson::doc, Client};
+
+use crate::controllers::task_controller::purge_task;
+use crate::models::task_model::Task;
+use crate::storage::Storage;
+
+/// Actor recorded in the history for tasks purged by this job.
+const PURGE_ACTOR: &str = "system";
+
+/// Periodically purges tasks that have been in the trash longer than `TRASH_RETENTION_DAYS` (default 30).
+pub async fn run(client: Client, storage: Arc<dyn Storage>) {
+    let retention_days: i64 = env::var("TRASH_RETENTION_DAYS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(30);
+
+    let mut ticker = interval(StdDuration::from_secs(60 * 60));
+    loop {
+        ticker.tick().await;
+        if let Err(err) = purge_expired(&client, storage.as_ref(), retention_days).await {
+            eprintln!("❌ trash purge failed: {}", err);
+        }
+    }
+}
+
+async fn purge_expired(client: &Client, storage: &dyn Storage, retention_days: i64) -> Result<(), String> {
+    let cutoff = Utc::now() - Duration::days(retention_days);
+    let collection = client.database("rust_backend").collection::<Task>("tasks");
+
+    // `deleted_at` is stored in the same fixed textual format everywhere, so it sorts chronologically.
+    let expired: Vec<Task> = collection
+        .find(doc! { "deleted_at": { "$lt": cutoff.to_string() } })
+        .await
+        .map_err(|e| e.to_string())?
+        .try_collect()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // One task failing, e.g. on a storage error, must not keep the others in the trash.
+    let mut purged = 0;
+    for task in &expired {
+        match purge_task(client, storage, task, PURGE_ACTOR).await {
+            Ok(()) => purged += 1,
+            Err(err) => eprintln!("❌ failed to purge task {}: {}", task.id.as_deref().unwrap_or_default(), err),
+        }
+    }
+
+    if purged > 0 {
+        println!("🗑️ Purged {} tasks from the trash", purged);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,259 @@
+use std::{env, sync::Arc, time::Duration as StdDuration};
+use actix_web::rt::time::interval;
+use chrono::{DateTime, Duration, Utc};
+use hmac::{Hmac, Mac};
+use mongodb::{bson::{doc, oid::ObjectId, Bson}, options::ReturnDocument, Client};
+use reqwest::{dns::{Addrs, Name, Resolve, Resolving}, redirect::Policy, Url};
+use sha2::Sha256;
+use tokio::net::lookup_host;
+
+use crate::controllers::webhook_controller::{check_destination, is_public, private_urls_allowed};
+use crate::models::webhook_model::{Webhook, WebhookDelivery};
+
+/// Attempts per delivery before giving up on it.
+const MAX_ATTEMPTS: u32 = 6;
+/// Failed attempts in a row after which a webhook is turned off.
+const DISABLE_AFTER_FAILURES: u32 = 20;
+const MAX_BACKOFF_SECONDS: i64 = 60 * 60;
+/// How long a claimed delivery is hidden from other runs while it is being sent.
+const LEASE_SECONDS: i64 = 60;
+
+/// Every few seconds, sends the queued webhook deliveries that are due. Failed attempts
+/// are retried after `WEBHOOK_RETRY_BASE_SECONDS` (default 30), doubling each time.
+pub async fn run(client: Client) {
+    let base_seconds: i64 = env::var("WEBHOOK_RETRY_BASE_SECONDS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(30);
+    let http = http_client(private_urls_allowed());
+
+    let mut ticker = interval(StdDuration::from_secs(5));
+    loop {
+        ticker.tick().await;
+        if let Err(err) = deliver_due(&client, &http, base_seconds).await {
+            eprintln!("❌ webhook deliveries failed: {}", err);
+        }
+    }
+}
+
+/// Resolves host names like the system resolver but drops every address that is not
+/// public, so the client only ever connects to addresses `check_destination` accepts.
+/// Checking the name up front is not enough: it may resolve differently a moment later.
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let addresses: Vec<_> = lookup_host((name.as_str(), 0))
+                .await?
+                .filter(|address| is_public(address.ip()))
+                .collect();
+            if addresses.is_empty() {
+                return Err(format!("{} does not resolve to a public address", name.as_str()).into());
+            }
+            Ok(Box::new(addresses.into_iter()) as Addrs)
+        })
+    }
+}
+
+fn http_client(allow_private: bool) -> reqwest::Client {
+    let mut builder = reqwest::Client::builder()
+        .timeout(StdDuration::from_secs(10))
+        // A redirect could lead to an address `check_destination` would refuse.
+        .redirect(Policy::none());
+    if !allow_private {
+        builder = builder.dns_resolver(Arc::new(PublicResolver));
+    }
+    builder.build().expect("❌ failed to build the webhook HTTP client")
+}
+
+/// The `X-Webhook-Signature` value: the hex HMAC-SHA256 of `{timestamp}.{body}`.
+fn signature(secret: &str, timestamp: i64, body: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(format!("{}.{}", timestamp, body).as_bytes());
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+fn backoff(base_seconds: i64, attempts: u32) -> Duration {
+    let seconds = base_seconds.max(1).saturating_mul(1 << attempts.saturating_sub(1).min(20));
+    Duration::seconds(seconds.min(MAX_BACKOFF_SECONDS))
+}
+
+async fn deliver_due(client: &Client, http: &reqwest::Client, base_seconds: i64) -> mongodb::error::Result<()> {
+    let deliveries = client.database("rust_backend").collection::<WebhookDelivery>("webhook_deliveries");
+
+    loop {
+        let now = Utc::now();
+        // Claim one due delivery at a time so a slow receiver never gets it twice.
+        let claimed = deliveries
+            .find_one_and_update(
+                doc! { "status": "pending", "next_attempt_at": { "$lte": now.to_string() } },
+                doc! { "$set": { "next_attempt_at": (now + Duration::seconds(LEASE_SECONDS)).to_string() } },
+            )
+            .sort(doc! { "next_attempt_at": 1 })
+            .await?;
+        let Some(delivery) = claimed else { return Ok(()) };
+        attempt(client, http, delivery, base_seconds).await?;
+    }
+}
+
+async fn attempt(
+    client: &Client,
+    http: &reqwest::Client,
+    delivery: WebhookDelivery,
+    base_seconds: i64,
+) -> mongodb::error::Result<()> {
+    let db = client.database("rust_backend");
+    let deliveries = db.collection::<WebhookDelivery>("webhook_deliveries");
+    let webhooks = db.collection::<Webhook>("webhooks");
+    let Some(delivery_oid) = delivery.id.as_deref().and_then(|id| ObjectId::parse_str(id).ok()) else { return Ok(()) };
+    let webhook_oid = ObjectId::parse_str(&delivery.webhook_id).ok();
+
+    let webhook = match webhook_oid {
+        Some(oid) => webhooks.find_one(doc! { "_id": oid }).await?,
+        None => None,
+    };
+    let Some(webhook) = webhook.filter(|w| w.active) else {
+        deliveries
+            .update_one(
+                doc! { "_id": delivery_oid },
+                doc! { "$set": { "status": "failed", "next_attempt_at": Bson::Null, "error": "Webhook is disabled" } },
+            )
+            .await?;
+        return Ok(());
+    };
+
+    // The host may resolve to somewhere else by now.
+    let destination = match Url::parse(&webhook.url) {
+        Ok(url) => check_destination(&url).await,
+        Err(err) => Err(err.to_string()),
+    };
+
+    let timestamp = Utc::now().timestamp();
+    let result = match destination {
+        Err(err) => Err(err),
+        Ok(()) => http
+            .post(&webhook.url)
+            .header("Content-Type", "application/json")
+            .header("User-Agent", "rust-backend-webhooks")
+            .header("X-Webhook-Event", delivery.event.name())
+            .header("X-Webhook-Delivery", delivery_oid.to_hex())
+            .header("X-Webhook-Timestamp", timestamp.to_string())
+            .header("X-Webhook-Signature", signature(&webhook.secret, timestamp, &delivery.payload))
+            .body(delivery.payload.clone())
+            .send()
+            .await
+            .map_err(|err| format!("Request failed: {}", err)),
+    };
+
+    let now = Utc::now();
+    let attempts = delivery.attempts + 1;
+    let (response_status, error) = match result {
+        Ok(response) if response.status().is_success() => (Some(response.status().as_u16()), None),
+        Ok(response) => (Some(response.status().as_u16()), Some(format!("Receiver responded with {}", response.status()))),
+        Err(err) => (None, Some(err)),
+    };
+    let response_status = response_status.map(|s| Bson::Int32(s.into())).unwrap_or(Bson::Null);
+
+    let Some(error) = error else {
+        deliveries
+            .update_one(
+                doc! { "_id": delivery_oid },
+                doc! { "$set": {
+                    "status": "succeeded",
+                    "attempts": attempts,
+                    "next_attempt_at": Bson::Null,
+                    "response_status": response_status,
+                    "error": Bson::Null,
+                    "delivered_at": now.to_string(),
+                } },
+            )
+            .await?;
+        webhooks
+            .update_one(doc! { "_id": webhook_oid, "consecutive_failures": { "$gt": 0 } }, doc! { "$set": { "consecutive_failures": 0 } })
+            .await?;
+        return Ok(());
+    };
+
+    let next_attempt_at: Option<DateTime<Utc>> = (attempts < MAX_ATTEMPTS).then(|| now + backoff(base_seconds, attempts));
+    deliveries
+        .update_one(
+            doc! { "_id": delivery_oid },
+            doc! { "$set": {
+                "status": if next_attempt_at.is_some() { "pending" } else { "failed" },
+                "attempts": attempts,
+                "next_attempt_at": next_attempt_at.map(|d| Bson::String(d.to_string())).unwrap_or(Bson::Null),
+                "response_status": response_status,
+                "error": &error,
+            } },
+        )
+        .await?;
+
+    let failing = webhooks
+        .find_one_and_update(doc! { "_id": webhook_oid }, doc! { "$inc": { "consecutive_failures": 1 } })
+        .return_document(ReturnDocument::After)
+        .await?;
+    if let Some(failing) = failing
+        && failing.active
+        && failing.consecutive_failures >= DISABLE_AFTER_FAILURES
+    {
+        disable(client, &failing, &error).await?;
+    }
+    Ok(())
+}
+
+/// Turns off a webhook that keeps failing and drops its queued deliveries.
+async fn disable(client: &Client, webhook: &Webhook, last_error: &str) -> mongodb::error::Result<()> {
+    let Some(webhook_id) = webhook.id.as_deref() else { return Ok(()) };
+    let Ok(oid) = ObjectId::parse_str(webhook_id) else { return Ok(()) };
+    let db = client.database("rust_backend");
+
+    let reason = format!("Disabled after {} failed deliveries in a row; last error: {}", webhook.consecutive_failures, last_error);
+    db.collection::<Webhook>("webhooks")
+        .update_one(
+            doc! { "_id": oid },
+            doc! { "$set": { "active": false, "disabled_reason": reason, "updated_at": Utc::now().to_string() } },
+        )
+        .await?;
+    db.collection::<WebhookDelivery>("webhook_deliveries")
+        .update_many(
+            doc! { "webhook_id": webhook_id, "status": "pending" },
+            doc! { "$set": { "status": "failed", "next_attempt_at": Bson::Null, "error": "Webhook is disabled" } },
+        )
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signs_timestamp_and_body() {
+        // printf '1700000000.{"event":"ping"}' | openssl dgst -sha256 -hmac secret
+        assert_eq!(
+            signature("secret", 1_700_000_000, r#"{"event":"ping"}"#),
+            "sha256=4d39bd2442f073b6bc62e95d0297ce25475582a17389ab860abdc778fe1d9f77",
+        );
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_an_hour() {
+        assert_eq!(backoff(30, 1), Duration::seconds(30));
+        assert_eq!(backoff(30, 2), Duration::seconds(60));
+        assert_eq!(backoff(30, 5), Duration::seconds(480));
+        assert_eq!(backoff(30, 40), Duration::seconds(MAX_BACKOFF_SECONDS));
+    }
+
+    #[actix_web::test]
+    async fn refuses_host_names_resolving_to_loopback() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.set_nonblocking(true).unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let result = http_client(false).post(format!("http://localhost:{}/hooks", port)).send().await;
+        assert!(result.is_err());
+        // The receiver was never connected to.
+        assert!(listener.accept().is_err());
+    }
+}
@@ -2,7 +2,7 @@ use std::env;
 use actix_web::{App, HttpServer, Responder, get, web};
 use serde::Serialize;
 use dotenvy::dotenv;
-use crate::{db::db::init_db, routes::task_routes::task_routes, routes::user_routes::user_routes};
+use crate::{db::db::init_db, storage::storage_from_env, db::migrations::run_migrations, routes::task_routes::task_routes, routes::user_routes::user_routes, routes::workflow_routes::workflow_routes, routes::trash_routes::trash_routes, routes::project_routes::project_routes, routes::time_routes::time_routes, routes::workspace_routes::workspace_routes, routes::notification_routes::notification_routes, routes::template_routes::template_routes, routes::stats_routes::stats_routes, routes::view_routes::view_routes, routes::calendar_routes::calendar_routes, routes::caldav_routes::caldav_routes, routes::account_routes::account_routes, routes::webhook_routes::webhook_routes};
 
 mod utils;
 mod routes;
@@ -10,6 +10,9 @@ mod models;
 mod db;
 mod controllers;
 mod middlewares;
+mod storage;
+mod jobs;
+mod importers;
 
 #[derive(Serialize)]
 struct Response {
@@ -38,6 +41,14 @@ async fn main() -> std::io::Result<()> {
         .expect("❌ PORT must be a number");
 
     let client = init_db(uri).await.expect("Failed to connect to MongoDB");
+    run_migrations(&client).await.expect("Failed to run database migrations");
+
+    let storage = storage_from_env();
+
+    actix_web::rt::spawn(jobs::trash_purge::run(client.clone(), storage.clone()));
+    actix_web::rt::spawn(jobs::due_soon::run(client.clone()));
+    actix_web::rt::spawn(jobs::webhook_delivery::run(client.clone()));
+    actix_web::rt::spawn(jobs::event_retention::run(client.clone()));
 
     let host = "127.0.0.1";
 
@@ -46,8 +57,22 @@ async fn main() -> std::io::Result<()> {
     HttpServer::new(move|| {
         App::new()
             .app_data(web::Data::new(client.clone()))
+            .app_data(web::Data::from(storage.clone()))
             .configure(user_routes)
             .configure(task_routes)
+            .configure(workflow_routes)
+            .configure(trash_routes)
+            .configure(project_routes)
+            .configure(time_routes)
+            .configure(workspace_routes)
+            .configure(notification_routes)
+            .configure(template_routes)
+            .configure(stats_routes)
+            .configure(view_routes)
+            .configure(calendar_routes)
+            .configure(caldav_routes)
+            .configure(account_routes)
+            .configure(webhook_routes)
     })
     .bind((host, port))?
     .run()
@@ -52,24 +52,21 @@ where
             // Extract the Authorization header
             let auth_header = req.headers().get("Authorization");
 
-            if let Some(auth_header) = auth_header {
-                if let Ok(auth_str) = auth_header.to_str() {
-                    if auth_str.starts_with("Bearer ") {
-                        let token = &auth_str[7..]; // Remove "Bearer " prefix
-
-                        match verify_token(token) {
-                            Ok(claims) => {
-                                // Store user ID in request extensions
-                                req.extensions_mut().insert(claims.sub.clone());
-                                let res = service.call(req).await?;
-                                return Ok(res);
-                            }
-                            Err(_) => {
-                                return Ok(req.into_response(
-                                    HttpResponse::Unauthorized().body("Invalid token")
-                                ));
-                            }
-                        }
+            if let Some(auth_header) = auth_header
+                && let Ok(auth_str) = auth_header.to_str()
+                && let Some(token) = auth_str.strip_prefix("Bearer ")
+            {
+                match verify_token(token) {
+                    Ok(claims) => {
+                        // Store user ID in request extensions
+                        req.extensions_mut().insert(claims.sub.clone());
+                        let res = service.call(req).await?;
+                        return Ok(res);
+                    }
+                    Err(_) => {
+                        return Ok(req.into_response(
+                            HttpResponse::Unauthorized().body("Invalid token")
+                        ));
                     }
                 }
             }
@@ -0,0 +1,111 @@
+use actix_web::{
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    web, Error, HttpMessage, HttpResponse,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use futures_util::future::LocalBoxFuture;
+use mongodb::{bson::doc, Client};
+use std::{
+    future::{ready, Ready},
+    rc::Rc,
+};
+
+use crate::models::user_model::User;
+use crate::utils::jwt::verify_token;
+
+/// Authenticates with HTTP Basic credentials (email and password) for clients that
+/// can't send bearer tokens, such as CalDAV apps. Bearer tokens are accepted as well.
+pub struct BasicAuthMiddleware;
+
+impl<S> Transform<S, ServiceRequest> for BasicAuthMiddleware
+where
+    S: Service<ServiceRequest, Response = ServiceResponse, Error = Error> + 'static,
+    S::Future: 'static,
+{
+    type Response = ServiceResponse;
+    type Error = Error;
+    type InitError = ();
+    type Transform = BasicAuthMiddlewareMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(BasicAuthMiddlewareMiddleware {
+            service: Rc::new(service),
+        }))
+    }
+}
+
+pub struct BasicAuthMiddlewareMiddleware<S> {
+    service: Rc<S>,
+}
+
+/// The user id for Basic `credentials`, if the email and password match.
+async fn verify_credentials(client: &Client, credentials: &str) -> Option<String> {
+    let decoded = String::from_utf8(STANDARD.decode(credentials.trim()).ok()?).ok()?;
+    let (email, password) = decoded.split_once(':')?;
+
+    let user = client
+        .database("rust_backend")
+        .collection::<User>("users")
+        .find_one(doc! { "email": email })
+        .await
+        .ok()??;
+
+    // bcrypt is deliberately slow, so keep it off the async workers.
+    let (password, hashed) = (password.to_string(), user.password);
+    match web::block(move || bcrypt::verify(password, &hashed)).await {
+        Ok(Ok(true)) => user.id,
+        _ => None,
+    }
+}
+
+fn unauthorized(req: ServiceRequest) -> ServiceResponse {
+    req.into_response(
+        HttpResponse::Unauthorized()
+            .insert_header(("WWW-Authenticate", "Basic realm=\"Tasks\", charset=\"UTF-8\""))
+            .body("Authorization header missing or invalid")
+    )
+}
+
+impl<S> Service<ServiceRequest> for BasicAuthMiddlewareMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse, Error = Error> + 'static,
+    S::Future: 'static,
+{
+    type Response = ServiceResponse;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+
+        Box::pin(async move {
+            let auth_str = req
+                .headers()
+                .get("Authorization")
+                .and_then(|h| h.to_str().ok())
+                .map(str::to_string)
+                .unwrap_or_default();
+
+            let user_id = if let Some(token) = auth_str.strip_prefix("Bearer ") {
+                verify_token(token).ok().map(|claims| claims.sub)
+            } else if let Some(credentials) = auth_str.strip_prefix("Basic ")
+                && let Some(client) = req.app_data::<web::Data<Client>>()
+            {
+                verify_credentials(client, credentials).await
+            } else {
+                None
+            };
+
+            match user_id {
+                Some(user_id) => {
+                    req.extensions_mut().insert(user_id);
+                    service.call(req).await
+                }
+                None => Ok(unauthorized(req)),
+            }
+        })
+    }
+}
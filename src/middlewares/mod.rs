@@ -1 +1,2 @@
-pub mod auth_middleware;
\ No newline at end of file
+pub mod auth_middleware;
+pub mod basic_auth_middleware;
\ No newline at end of file
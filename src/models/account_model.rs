@@ -0,0 +1,104 @@
+use serde::{Serialize, Deserialize};
+use chrono::{DateTime, Utc};
+use serde_with::{serde_as, DisplayFromStr};
+use crate::models::attachment_model::AttachmentResponse;
+use crate::models::comment_model::Comment;
+use crate::models::deserialize_object_id_to_hex;
+use crate::models::project_model::Project;
+use crate::models::task_model::Task;
+use crate::models::workflow_model::Workflow;
+
+/// Bumped whenever the archive layout changes incompatibly; imports refuse newer versions.
+pub const ARCHIVE_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportStatus {
+    Pending,
+    Ready,
+    Failed,
+}
+
+/// An account export, built in the background and kept in storage until the next one.
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AccountExport {
+    #[serde(rename = "_id", deserialize_with = "deserialize_object_id_to_hex", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub user_id: String,
+    pub status: ExportStatus,
+    pub storage_key: String,
+    pub size: Option<i64>,
+    pub error: Option<String>,
+    #[serde_as(as = "DisplayFromStr")]
+    pub created_at: DateTime<Utc>,
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AccountExportResponse {
+    #[serde(rename = "_id")]
+    pub id: Option<String>,
+    pub status: ExportStatus,
+    pub size: Option<i64>,
+    pub error: Option<String>,
+    /// Set once the archive is ready.
+    pub download_url: Option<String>,
+    #[serde_as(as = "DisplayFromStr")]
+    pub created_at: DateTime<Utc>,
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+impl From<AccountExport> for AccountExportResponse {
+    fn from(export: AccountExport) -> Self {
+        let download_url = (export.status == ExportStatus::Ready)
+            .then(|| format!("/account/exports/{}/download", export.id.as_deref().unwrap_or_default()));
+        AccountExportResponse {
+            id: export.id,
+            status: export.status,
+            size: export.size,
+            error: export.error,
+            download_url,
+            created_at: export.created_at,
+            completed_at: export.completed_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ArchiveProfile {
+    pub id: String,
+    pub name: String,
+    pub email: String,
+}
+
+/// Everything an account holds. Attachments are listed without their content.
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AccountArchive {
+    pub version: u32,
+    #[serde_as(as = "DisplayFromStr")]
+    pub exported_at: DateTime<Utc>,
+    pub profile: ArchiveProfile,
+    /// Every label used on the account's tasks.
+    pub labels: Vec<String>,
+    pub workflows: Vec<Workflow>,
+    pub projects: Vec<Project>,
+    pub tasks: Vec<Task>,
+    /// Comments on the account's tasks, whoever wrote them.
+    pub comments: Vec<Comment>,
+    pub attachments: Vec<AttachmentResponse>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct AccountImportReport {
+    pub workflows: usize,
+    pub projects: usize,
+    pub tasks: usize,
+    pub comments: usize,
+    /// Attachments whose content isn't in the archive, so they weren't restored.
+    pub attachments_skipped: usize,
+}
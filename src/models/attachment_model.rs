@@ -0,0 +1,45 @@
+use serde::{Serialize, Deserialize};
+use chrono::{DateTime, Utc};
+use serde_with::{serde_as, DisplayFromStr};
+use crate::models::deserialize_object_id_to_hex;
+
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Attachment {
+    #[serde(rename = "_id", deserialize_with = "deserialize_object_id_to_hex", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub task_id: String,
+    pub user_id: String,
+    pub filename: String,
+    pub content_type: String,
+    pub size: i64,
+    pub storage_key: String,
+    #[serde_as(as = "DisplayFromStr")]
+    pub created_at: DateTime<Utc>,
+}
+
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AttachmentResponse {
+    #[serde(rename = "_id")]
+    pub id: Option<String>,
+    pub task_id: String,
+    pub filename: String,
+    pub content_type: String,
+    pub size: i64,
+    #[serde_as(as = "DisplayFromStr")]
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<Attachment> for AttachmentResponse {
+    fn from(attachment: Attachment) -> Self {
+        AttachmentResponse {
+            id: attachment.id,
+            task_id: attachment.task_id,
+            filename: attachment.filename,
+            content_type: attachment.content_type,
+            size: attachment.size,
+            created_at: attachment.created_at,
+        }
+    }
+}
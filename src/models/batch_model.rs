@@ -0,0 +1,59 @@
+use serde::{Serialize, Deserialize};
+use crate::models::task_model::{NewTask, UpdateTask};
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum BatchOperation {
+    Create { task: NewTask },
+    Update { id: String, changes: UpdateTask },
+    Delete { id: String },
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BatchRequest {
+    pub operations: Vec<BatchOperation>,
+    /// Run every operation in one transaction; nothing is written if any of them fails.
+    #[serde(default)]
+    pub atomic: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchItemResult {
+    pub index: usize,
+    pub status: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchResponse {
+    pub committed: bool,
+    pub results: Vec<BatchItemResult>,
+}
+
+/// Selects the caller's tasks; every given criterion must match.
+#[derive(Debug, Deserialize)]
+pub struct TaskFilter {
+    pub status: Option<String>,
+    pub workflow_id: Option<String>,
+    /// Tasks must carry all of these labels.
+    pub labels: Option<Vec<String>>,
+    pub project_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BulkUpdateRequest {
+    pub filter: TaskFilter,
+    pub changes: UpdateTask,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BulkUpdateResponse {
+    pub matched: u64,
+    pub modified: u64,
+    /// Ids of matched tasks that changed between being read and written; they are left
+    /// as they are.
+    pub conflicts: Vec<String>,
+}
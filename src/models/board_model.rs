@@ -0,0 +1,32 @@
+use serde::{Serialize, Deserialize};
+use crate::models::task_model::TaskResponse;
+use crate::models::workflow_model::StatusCategory;
+
+#[derive(Debug, Deserialize)]
+pub struct BoardQuery {
+    /// Defaults to the caller's default workflow.
+    pub workflow_id: Option<String>,
+    pub project_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BoardColumn {
+    pub status: String,
+    pub category: StatusCategory,
+    pub wip_limit: Option<u32>,
+    pub tasks: Vec<TaskResponse>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BoardResponse {
+    pub workflow_id: String,
+    pub columns: Vec<BoardColumn>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MoveTask {
+    /// Target column; defaults to the task's current status.
+    pub status: Option<String>,
+    /// Zero-based index in the target column; defaults to the end.
+    pub position: Option<usize>,
+}
@@ -0,0 +1,32 @@
+use serde::{Serialize, Deserialize};
+use chrono::{DateTime, Utc};
+use serde_with::{serde_as, DisplayFromStr};
+use crate::models::deserialize_object_id_to_hex;
+
+/// A user's calendar subscription. Only a hash of the secret token is stored.
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarFeed {
+    #[serde(rename = "_id", deserialize_with = "deserialize_object_id_to_hex", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub user_id: String,
+    pub token_hash: String,
+    #[serde_as(as = "DisplayFromStr")]
+    pub created_at: DateTime<Utc>,
+}
+
+/// Returned once, when the token is (re)generated.
+#[serde_as]
+#[derive(Debug, Serialize)]
+pub struct CalendarFeedResponse {
+    pub url: String,
+    pub token: String,
+    #[serde_as(as = "DisplayFromStr")]
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CalendarQuery {
+    /// Also add an all-day event on each task's due date.
+    pub events: Option<bool>,
+}
@@ -0,0 +1,72 @@
+use serde::{Serialize, Deserialize};
+use chrono::{DateTime, Utc};
+use serde_with::{serde_as, DisplayFromStr};
+use crate::models::deserialize_object_id_to_hex;
+
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Comment {
+    #[serde(rename = "_id", deserialize_with = "deserialize_object_id_to_hex", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub task_id: String,
+    pub author_id: String,
+    pub parent_id: Option<String>,
+    /// Ids from the thread root down to the direct parent; empty for top-level comments.
+    pub ancestors: Vec<String>,
+    pub body: String,
+    pub edited: bool,
+    #[serde_as(as = "DisplayFromStr")]
+    pub created_at: DateTime<Utc>,
+    #[serde_as(as = "DisplayFromStr")]
+    pub updated_at: DateTime<Utc>,
+}
+
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CommentResponse {
+    #[serde(rename = "_id")]
+    pub id: Option<String>,
+    pub task_id: String,
+    pub author_id: String,
+    pub parent_id: Option<String>,
+    pub body: String,
+    pub edited: bool,
+    #[serde_as(as = "DisplayFromStr")]
+    pub created_at: DateTime<Utc>,
+    #[serde_as(as = "DisplayFromStr")]
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<Comment> for CommentResponse {
+    fn from(comment: Comment) -> Self {
+        CommentResponse {
+            id: comment.id,
+            task_id: comment.task_id,
+            author_id: comment.author_id,
+            parent_id: comment.parent_id,
+            body: comment.body,
+            edited: comment.edited,
+            created_at: comment.created_at,
+            updated_at: comment.updated_at,
+        }
+    }
+}
+
+/// A top-level comment together with every reply below it, oldest first.
+#[derive(Debug, Serialize)]
+pub struct CommentThread {
+    #[serde(flatten)]
+    pub comment: CommentResponse,
+    pub replies: Vec<CommentResponse>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NewComment {
+    pub body: String,
+    pub parent_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateComment {
+    pub body: String,
+}
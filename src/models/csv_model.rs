@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+use serde::{Serialize, Deserialize};
+use crate::models::task_model::TaskScope;
+
+/// A task column of a CSV export or import. Dates are RFC 3339; list columns hold
+/// comma-separated values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CsvColumn {
+    Id,
+    Title,
+    Description,
+    Status,
+    WorkflowId,
+    Labels,
+    Priority,
+    ProjectId,
+    ParentId,
+    WorkspaceId,
+    AssigneeIds,
+    DueAt,
+    CreatedAt,
+    UpdatedAt,
+    CompletedAt,
+}
+
+impl CsvColumn {
+    pub const ALL: [CsvColumn; 15] = [
+        CsvColumn::Id,
+        CsvColumn::Title,
+        CsvColumn::Description,
+        CsvColumn::Status,
+        CsvColumn::WorkflowId,
+        CsvColumn::Labels,
+        CsvColumn::Priority,
+        CsvColumn::ProjectId,
+        CsvColumn::ParentId,
+        CsvColumn::WorkspaceId,
+        CsvColumn::AssigneeIds,
+        CsvColumn::DueAt,
+        CsvColumn::CreatedAt,
+        CsvColumn::UpdatedAt,
+        CsvColumn::CompletedAt,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            CsvColumn::Id => "id",
+            CsvColumn::Title => "title",
+            CsvColumn::Description => "description",
+            CsvColumn::Status => "status",
+            CsvColumn::WorkflowId => "workflow_id",
+            CsvColumn::Labels => "labels",
+            CsvColumn::Priority => "priority",
+            CsvColumn::ProjectId => "project_id",
+            CsvColumn::ParentId => "parent_id",
+            CsvColumn::WorkspaceId => "workspace_id",
+            CsvColumn::AssigneeIds => "assignee_ids",
+            CsvColumn::DueAt => "due_at",
+            CsvColumn::CreatedAt => "created_at",
+            CsvColumn::UpdatedAt => "updated_at",
+            CsvColumn::CompletedAt => "completed_at",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<CsvColumn> {
+        CsvColumn::ALL.into_iter().find(|c| c.name().eq_ignore_ascii_case(name.trim()))
+    }
+
+    /// Columns set by the server, which imports ignore.
+    pub fn is_read_only(self) -> bool {
+        matches!(self, CsvColumn::Id | CsvColumn::CreatedAt | CsvColumn::UpdatedAt | CsvColumn::CompletedAt)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExportQuery {
+    /// Comma-separated column names; all columns by default.
+    pub columns: Option<String>,
+    #[serde(default)]
+    pub scope: TaskScope,
+    pub project_id: Option<String>,
+    pub status: Option<String>,
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImportRequest {
+    /// The CSV document, with a header row.
+    pub csv: String,
+    /// Maps CSV headers to columns. Headers not mapped are matched by column name,
+    /// and ignored if there is none.
+    #[serde(default)]
+    pub mapping: HashMap<String, CsvColumn>,
+    /// Validate and report without creating tasks.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// `row` is the CSV line, so the header is line 1.
+#[derive(Debug, Serialize)]
+pub struct ImportRowError {
+    pub row: u64,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportReport {
+    pub dry_run: bool,
+    /// Tasks created, or the valid rows of a dry run.
+    pub imported: usize,
+    /// Rows skipped because a task with the same title, project and due date exists.
+    pub duplicates: Vec<u64>,
+    /// Nothing is imported while any row has errors.
+    pub errors: Vec<ImportRowError>,
+    pub task_ids: Vec<String>,
+}
@@ -0,0 +1,60 @@
+use serde::{Serialize, Deserialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldType {
+    Text,
+    Number,
+    /// A `YYYY-MM-DD` day.
+    Date,
+    SingleSelect,
+    MultiSelect,
+    Checkbox,
+    /// The id of a user.
+    User,
+}
+
+impl FieldType {
+    pub fn has_options(self) -> bool {
+        matches!(self, FieldType::SingleSelect | FieldType::MultiSelect)
+    }
+}
+
+/// A field defined by a project for its tasks.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomField {
+    pub id: String,
+    pub name: String,
+    pub field_type: FieldType,
+    /// The choices of select fields; empty for other types.
+    #[serde(default)]
+    pub options: Vec<String>,
+}
+
+/// A custom field value as stored on a task. Which variant is valid depends on the
+/// field's type: text, date, single select and user fields hold `Text`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum FieldValue {
+    Bool(bool),
+    Number(f64),
+    Text(String),
+    List(Vec<String>),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NewCustomField {
+    pub name: String,
+    pub field_type: FieldType,
+    #[serde(default)]
+    pub options: Vec<String>,
+}
+
+/// Changing the type or options converts existing values where possible and removes
+/// the rest.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateCustomField {
+    pub name: Option<String>,
+    pub field_type: Option<FieldType>,
+    pub options: Option<Vec<String>>,
+}
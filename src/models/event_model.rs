@@ -0,0 +1,80 @@
+use serde::{Serialize, Deserialize};
+use chrono::{DateTime, Utc};
+use serde_with::{serde_as, DisplayFromStr};
+use crate::models::deserialize_object_id_to_hex;
+use crate::models::history_model::FieldChange;
+use crate::models::task_model::TaskResponse;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TaskEventKind {
+    /// Also sent when a task is restored from the trash.
+    #[serde(rename = "task.created")]
+    Created,
+    #[serde(rename = "task.updated")]
+    Updated,
+    /// Sent when a task is moved to the trash.
+    #[serde(rename = "task.deleted")]
+    Deleted,
+}
+
+impl TaskEventKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            TaskEventKind::Created => "task.created",
+            TaskEventKind::Updated => "task.updated",
+            TaskEventKind::Deleted => "task.deleted",
+        }
+    }
+}
+
+/// A task change pushed to event streams. `seq` comes from a counter and only grows,
+/// so clients resume after the last one they saw.
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TaskEvent {
+    #[serde(rename = "_id", deserialize_with = "deserialize_object_id_to_hex", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub seq: i64,
+    pub event: TaskEventKind,
+    /// The task's owner, workspace and assignees decide who receives the event.
+    pub user_id: String,
+    pub workspace_id: Option<String>,
+    pub assignee_ids: Vec<String>,
+    pub actor_id: String,
+    pub task: TaskResponse,
+    pub changes: Vec<FieldChange>,
+    #[serde_as(as = "DisplayFromStr")]
+    pub created_at: DateTime<Utc>,
+}
+
+#[serde_as]
+#[derive(Debug, Serialize)]
+pub struct TaskEventResponse {
+    /// The event's `seq`.
+    pub id: String,
+    pub event: TaskEventKind,
+    pub actor_id: String,
+    pub task: TaskResponse,
+    pub changes: Vec<FieldChange>,
+    #[serde_as(as = "DisplayFromStr")]
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<TaskEvent> for TaskEventResponse {
+    fn from(event: TaskEvent) -> Self {
+        TaskEventResponse {
+            id: event.seq.to_string(),
+            event: event.event,
+            actor_id: event.actor_id,
+            task: event.task,
+            changes: event.changes,
+            created_at: event.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EventStreamQuery {
+    /// Resume after this event; Server-Sent Events clients can send `Last-Event-ID` instead.
+    pub last_event_id: Option<String>,
+}
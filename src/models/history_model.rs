@@ -0,0 +1,62 @@
+use serde::{Serialize, Deserialize};
+use chrono::{DateTime, Utc};
+use serde_with::{serde_as, DisplayFromStr};
+use crate::models::deserialize_object_id_to_hex;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum HistoryAction {
+    Created,
+    Updated,
+    Trashed,
+    Restored,
+    Deleted,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub from: serde_json::Value,
+    pub to: serde_json::Value,
+}
+
+/// Compares two `Task::tracked_fields` snapshots; fields missing on one side count as null.
+pub fn diff_fields(
+    before: &[(&'static str, serde_json::Value)],
+    after: &[(&'static str, serde_json::Value)],
+) -> Vec<FieldChange> {
+    let lookup = |fields: &[(&'static str, serde_json::Value)], name: &str| {
+        fields
+            .iter()
+            .find(|(field, _)| *field == name)
+            .map(|(_, value)| value.clone())
+            .unwrap_or(serde_json::Value::Null)
+    };
+
+    let mut names: Vec<&'static str> = after.iter().map(|(field, _)| *field).collect();
+    names.extend(before.iter().map(|(field, _)| *field).filter(|field| !after.iter().any(|(f, _)| f == field)));
+
+    names
+        .into_iter()
+        .filter_map(|name| {
+            let from = lookup(before, name);
+            let to = lookup(after, name);
+            (from != to).then(|| FieldChange { field: name.to_string(), from, to })
+        })
+        .collect()
+}
+
+/// One change made to a task. Entries are kept after the task is deleted.
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TaskHistory {
+    #[serde(rename = "_id", deserialize_with = "deserialize_object_id_to_hex", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub task_id: String,
+    /// Owner of the task, used to scope who can read the history.
+    pub user_id: String,
+    pub actor_id: String,
+    pub action: HistoryAction,
+    pub changes: Vec<FieldChange>,
+    #[serde_as(as = "DisplayFromStr")]
+    pub created_at: DateTime<Utc>,
+}
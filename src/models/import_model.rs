@@ -0,0 +1,70 @@
+use serde::{Serialize, Deserialize};
+use chrono::{DateTime, Utc};
+use crate::models::csv_model::ImportRowError;
+use crate::models::task_model::Priority;
+
+/// Exports of other tools that tasks can be imported from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ImportFormat {
+    /// A Todoist project exported as CSV.
+    TodoistCsv,
+    /// A Todoist backup or API dump with `items`, `projects` and `sections`.
+    TodoistJson,
+    /// A Trello board exported as JSON.
+    Trello,
+    TodoTxt,
+}
+
+/// What the lists of a Trello board, or the sections of a Todoist project, become.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColumnMapping {
+    /// The workflow status with the same name, or its first status if there is none.
+    #[default]
+    Status,
+    /// A project with the same name, created if needed.
+    Project,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FormatImportRequest {
+    /// The exported file.
+    pub data: String,
+    #[serde(default)]
+    pub columns: ColumnMapping,
+    /// Workflow of the imported tasks; the default one otherwise.
+    pub workflow_id: Option<String>,
+    /// Project for tasks that don't name one.
+    pub project_id: Option<String>,
+    /// Report what would be created without creating anything.
+    #[serde(default)]
+    pub preview: bool,
+}
+
+/// A task as it will be created.
+#[derive(Debug, Serialize)]
+pub struct ImportedTaskPreview {
+    pub title: String,
+    pub status: String,
+    /// The project's name.
+    pub project: Option<String>,
+    pub labels: Vec<String>,
+    pub priority: Priority,
+    pub due_at: Option<DateTime<Utc>>,
+    pub checklist_items: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FormatImportReport {
+    pub preview: bool,
+    pub tasks: Vec<ImportedTaskPreview>,
+    /// Projects that are, or would be, created for the import.
+    pub new_projects: Vec<String>,
+    /// Things that couldn't be imported as they were, such as unknown lists or dates.
+    pub warnings: Vec<String>,
+    /// Tasks that failed validation, by position in the file starting at 1. Nothing
+    /// is imported while there are any.
+    pub errors: Vec<ImportRowError>,
+    pub task_ids: Vec<String>,
+}
@@ -1,2 +1,33 @@
+use serde::{Deserialize, Deserializer};
+use mongodb::bson::oid::ObjectId;
+
 pub mod user_model;
-pub mod task_model;
\ No newline at end of file
+pub mod task_model;
+pub mod workflow_model;
+pub mod comment_model;
+pub mod attachment_model;
+pub mod history_model;
+pub mod batch_model;
+pub mod project_model;
+
+pub(crate) fn deserialize_object_id_to_hex<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let oid = Option::<ObjectId>::deserialize(deserializer)?;
+    Ok(oid.map(|o| o.to_hex()))
+}
+pub mod board_model;
+pub mod time_entry_model;
+pub mod workspace_model;
+pub mod notification_model;
+pub mod template_model;
+pub mod stats_model;
+pub mod view_model;
+pub mod custom_field_model;
+pub mod csv_model;
+pub mod calendar_model;
+pub mod account_model;
+pub mod import_model;
+pub mod webhook_model;
+pub mod event_model;
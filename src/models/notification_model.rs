@@ -0,0 +1,107 @@
+use serde::{Serialize, Deserialize};
+use chrono::{DateTime, Utc};
+use serde_with::{serde_as, DisplayFromStr};
+use crate::models::deserialize_object_id_to_hex;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationKind {
+    Assigned,
+    Comment,
+    DueSoon,
+    StatusChanged,
+}
+
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Notification {
+    #[serde(rename = "_id", deserialize_with = "deserialize_object_id_to_hex", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// The recipient.
+    pub user_id: String,
+    pub kind: NotificationKind,
+    pub task_id: String,
+    /// Who caused the notification; `None` for system events such as due dates.
+    pub actor_id: Option<String>,
+    pub message: String,
+    pub read: bool,
+    #[serde_as(as = "DisplayFromStr")]
+    pub created_at: DateTime<Utc>,
+}
+
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NotificationResponse {
+    #[serde(rename = "_id")]
+    pub id: Option<String>,
+    pub kind: NotificationKind,
+    pub task_id: String,
+    pub actor_id: Option<String>,
+    pub message: String,
+    pub read: bool,
+    #[serde_as(as = "DisplayFromStr")]
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<Notification> for NotificationResponse {
+    fn from(notification: Notification) -> Self {
+        NotificationResponse {
+            id: notification.id,
+            kind: notification.kind,
+            task_id: notification.task_id,
+            actor_id: notification.actor_id,
+            message: notification.message,
+            read: notification.read,
+            created_at: notification.created_at,
+        }
+    }
+}
+
+fn enabled() -> bool {
+    true
+}
+
+/// Which kinds of notifications a user receives; everything is on by default.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationPreferences {
+    #[serde(default = "enabled")]
+    pub assigned: bool,
+    #[serde(default = "enabled")]
+    pub comment: bool,
+    #[serde(default = "enabled")]
+    pub due_soon: bool,
+    #[serde(default = "enabled")]
+    pub status_changed: bool,
+}
+
+impl Default for NotificationPreferences {
+    fn default() -> Self {
+        NotificationPreferences { assigned: true, comment: true, due_soon: true, status_changed: true }
+    }
+}
+
+impl NotificationPreferences {
+    pub fn allows(&self, kind: NotificationKind) -> bool {
+        match kind {
+            NotificationKind::Assigned => self.assigned,
+            NotificationKind::Comment => self.comment,
+            NotificationKind::DueSoon => self.due_soon,
+            NotificationKind::StatusChanged => self.status_changed,
+        }
+    }
+}
+
+/// Stored preferences, one document per user.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UserNotificationPreferences {
+    pub user_id: String,
+    #[serde(flatten)]
+    pub preferences: NotificationPreferences,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NotificationQuery {
+    pub page: Option<u64>,
+    pub limit: Option<u64>,
+    pub unread_only: Option<bool>,
+}
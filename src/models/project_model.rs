@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+use serde::{Serialize, Deserialize};
+use chrono::{DateTime, Utc};
+use serde_with::{serde_as, DisplayFromStr};
+use crate::models::custom_field_model::CustomField;
+use crate::models::deserialize_object_id_to_hex;
+
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Project {
+    #[serde(rename = "_id", deserialize_with = "deserialize_object_id_to_hex", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// The creator; personal projects are only visible to them.
+    pub user_id: String,
+    /// Projects in a workspace are shared by all its members.
+    #[serde(default)]
+    pub workspace_id: Option<String>,
+    pub name: String,
+    pub description: Option<String>,
+    pub color: Option<String>,
+    /// Archived projects and their tasks are hidden from default listings.
+    pub archived: bool,
+    /// Fields the project's tasks can fill in.
+    #[serde(default)]
+    pub custom_fields: Vec<CustomField>,
+    #[serde_as(as = "DisplayFromStr")]
+    pub created_at: DateTime<Utc>,
+    #[serde_as(as = "DisplayFromStr")]
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Project {
+    pub fn custom_field(&self, field_id: &str) -> Option<&CustomField> {
+        self.custom_fields.iter().find(|f| f.id == field_id)
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TaskCounts {
+    pub total: u64,
+    pub by_status: HashMap<String, u64>,
+}
+
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProjectResponse {
+    #[serde(rename = "_id")]
+    pub id: Option<String>,
+    pub workspace_id: Option<String>,
+    pub name: String,
+    pub description: Option<String>,
+    pub color: Option<String>,
+    pub archived: bool,
+    pub custom_fields: Vec<CustomField>,
+    pub task_counts: TaskCounts,
+    #[serde_as(as = "DisplayFromStr")]
+    pub created_at: DateTime<Utc>,
+    #[serde_as(as = "DisplayFromStr")]
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ProjectResponse {
+    pub fn new(project: Project, task_counts: TaskCounts) -> Self {
+        ProjectResponse {
+            id: project.id,
+            workspace_id: project.workspace_id,
+            name: project.name,
+            description: project.description,
+            color: project.color,
+            archived: project.archived,
+            custom_fields: project.custom_fields,
+            task_counts,
+            created_at: project.created_at,
+            updated_at: project.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NewProject {
+    pub name: String,
+    /// Share the project with a workspace you are a member of.
+    #[serde(default)]
+    pub workspace_id: Option<String>,
+    pub description: Option<String>,
+    pub color: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateProject {
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "::serde_with::rust::double_option")]
+    pub description: Option<Option<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "::serde_with::rust::double_option")]
+    pub color: Option<Option<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProjectListQuery {
+    pub include_archived: Option<bool>,
+}
+
+/// Accepts `#rgb` and `#rrggbb` hex colors.
+pub fn is_valid_color(color: &str) -> bool {
+    color
+        .strip_prefix('#')
+        .is_some_and(|hex| (hex.len() == 3 || hex.len() == 6) && hex.chars().all(|c| c.is_ascii_hexdigit()))
+}
@@ -0,0 +1,52 @@
+use serde::{Serialize, Deserialize};
+use chrono::NaiveDate;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StatsInterval {
+    #[default]
+    Day,
+    Week,
+}
+
+/// `from` and `to` are inclusive UTC days and default to the last 30 days.
+#[derive(Debug, Deserialize)]
+pub struct StatsQuery {
+    pub project_id: Option<String>,
+    pub label: Option<String>,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    #[serde(default)]
+    pub interval: StatsInterval,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StatusCount {
+    pub status: String,
+    pub count: u64,
+}
+
+/// Tasks created and completed in one day or week (keyed by its Monday).
+#[derive(Debug, Default, Serialize)]
+pub struct PeriodCounts {
+    pub period: String,
+    pub created: u64,
+    pub completed: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TaskStats {
+    /// Current tasks, whenever they were created.
+    pub total: u64,
+    pub completed: u64,
+    /// `completed / total`, or 0 without tasks.
+    pub completion_rate: f64,
+    pub overdue: u64,
+    pub by_status: Vec<StatusCount>,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub interval: StatsInterval,
+    pub series: Vec<PeriodCounts>,
+    /// Mean time from creation to completion of the tasks completed in the range.
+    pub average_cycle_time_seconds: Option<f64>,
+}
@@ -1,36 +1,105 @@
-use serde::{Serialize, Deserialize, Deserializer};
-use mongodb::bson::oid::ObjectId;
+use std::collections::BTreeMap;
+use serde::{Serialize, Deserialize};
+use serde_json::json;
 use chrono::{DateTime, Utc};
 use serde_with::{serde_as, DisplayFromStr};
+use crate::models::custom_field_model::FieldValue;
+use crate::models::deserialize_object_id_to_hex;
+use crate::models::view_model::SortDirection;
+use crate::models::workflow_model::DEFAULT_WORKFLOW_ID;
+use crate::utils::markdown::{checklist, linked_task_ids, render_html, Checklist};
 
-fn deserialize_object_id_to_hex<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
-where
-    D: Deserializer<'de>,
-{
-    let oid = Option::<ObjectId>::deserialize(deserializer)?;
-    Ok(oid.map(|o| o.to_hex()))
+fn default_workflow_id() -> String {
+    DEFAULT_WORKFLOW_ID.to_string()
+}
+
+/// Ordered from lowest to highest.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Priority {
+    Low,
+    #[default]
+    Medium,
+    High,
+    Urgent,
 }
 
 #[serde_as]
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Task {
     #[serde(rename = "_id", deserialize_with = "deserialize_object_id_to_hex", skip_serializing_if = "Option::is_none")]
     pub id: Option<String>,
     pub title: String,
     pub description: Option<String>,
-    pub status: TaskStatus,
+    pub status: String,
+    #[serde(default = "default_workflow_id")]
+    pub workflow_id: String,
+    #[serde(default)]
+    pub labels: Vec<String>,
+    #[serde(default)]
+    pub priority: Priority,
+    #[serde(default)]
+    pub project_id: Option<String>,
+    /// Set on subtasks.
+    #[serde(default)]
+    pub parent_id: Option<String>,
+    /// Fractional rank ordering the task within its board column.
+    #[serde(default)]
+    pub rank: String,
+    /// Owner of the task; every read and write is scoped to it.
     pub user_id: String,
+    /// Who created the task.
+    #[serde(default)]
+    pub creator_id: String,
+    /// Who works on the task: the owner or, for workspace tasks, workspace members.
+    #[serde(default)]
+    pub assignee_ids: Vec<String>,
+    #[serde(default)]
+    pub workspace_id: Option<String>,
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub due_at: Option<DateTime<Utc>>,
+    /// Values of the project's custom fields, keyed by field id.
+    #[serde(default)]
+    pub custom_fields: BTreeMap<String, FieldValue>,
+    /// Set once assignees were told the task is due soon; cleared when `due_at` changes.
+    #[serde(default)]
+    pub due_soon_notified: bool,
+    /// Incremented on every write; exposed as the task's `ETag`.
+    #[serde(default)]
+    pub version: i64,
+    /// The iCalendar UID a CalDAV client gave the task when it created it.
+    #[serde(default)]
+    pub ical_uid: Option<String>,
+    /// The CalDAV resource name a client created the task under; `{id}.ics` otherwise.
+    #[serde(default)]
+    pub caldav_name: Option<String>,
     #[serde_as(as = "DisplayFromStr")]
     pub created_at: DateTime<Utc>,
     #[serde_as(as = "DisplayFromStr")]
     pub updated_at: DateTime<Utc>,
+    /// When the task entered a `Done` status; cleared when it is reopened.
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub completed_at: Option<DateTime<Utc>>,
+    /// Set while the task is in the trash.
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub deleted_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum TaskStatus {
-    Pending,
-    InProgress,
-    Completed,
+impl Task {
+    /// Fields whose changes are recorded in the task history.
+    pub fn tracked_fields(&self) -> Vec<(&'static str, serde_json::Value)> {
+        vec![
+            ("title", json!(self.title)),
+            ("description", json!(self.description)),
+            ("status", json!(self.status)),
+            ("workflow_id", json!(self.workflow_id)),
+            ("labels", json!(self.labels)),
+            ("priority", json!(self.priority)),
+            ("project_id", json!(self.project_id)),
+            ("assignee_ids", json!(self.assignee_ids)),
+            ("due_at", json!(self.due_at)),
+            ("custom_fields", json!(self.custom_fields)),
+        ]
+    }
 }
 
 #[serde_as]
@@ -39,26 +108,212 @@ pub struct TaskResponse {
     #[serde(rename = "_id")]
     pub id: Option<String>,
     pub title: String,
+    /// CommonMark source.
     pub description: Option<String>,
-    pub status: TaskStatus,
+    /// The description rendered to sanitized HTML; only included when asked for.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description_html: Option<String>,
+    /// Progress of the description's checklist items.
+    #[serde(default)]
+    pub checklist: Checklist,
+    /// Tasks the description links to.
+    #[serde(default)]
+    pub linked_task_ids: Vec<String>,
+    pub status: String,
+    pub workflow_id: String,
+    pub labels: Vec<String>,
+    pub priority: Priority,
+    pub project_id: Option<String>,
+    pub parent_id: Option<String>,
+    pub rank: String,
     pub user_id: String,
+    pub creator_id: String,
+    pub assignee_ids: Vec<String>,
+    pub workspace_id: Option<String>,
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub due_at: Option<DateTime<Utc>>,
+    pub custom_fields: BTreeMap<String, FieldValue>,
+    pub version: i64,
     #[serde_as(as = "DisplayFromStr")]
     pub created_at: DateTime<Utc>,
     #[serde_as(as = "DisplayFromStr")]
     pub updated_at: DateTime<Utc>,
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub completed_at: Option<DateTime<Utc>>,
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+impl TaskResponse {
+    /// Adds the rendered description.
+    pub fn with_html(mut self) -> Self {
+        self.description_html = self.description.as_deref().map(render_html);
+        self
+    }
 }
 
+impl From<Task> for TaskResponse {
+    fn from(task: Task) -> Self {
+        let source = task.description.as_deref().unwrap_or_default();
+        TaskResponse {
+            id: task.id,
+            title: task.title,
+            checklist: checklist(source),
+            linked_task_ids: linked_task_ids(source),
+            description_html: None,
+            description: task.description,
+            status: task.status,
+            workflow_id: task.workflow_id,
+            labels: task.labels,
+            priority: task.priority,
+            project_id: task.project_id,
+            parent_id: task.parent_id,
+            rank: task.rank,
+            user_id: task.user_id,
+            creator_id: task.creator_id,
+            assignee_ids: task.assignee_ids,
+            workspace_id: task.workspace_id,
+            due_at: task.due_at,
+            custom_fields: task.custom_fields,
+            version: task.version,
+            created_at: task.created_at,
+            updated_at: task.updated_at,
+            completed_at: task.completed_at,
+            deleted_at: task.deleted_at,
+        }
+    }
+}
+
+/// The fields of a task a PATCH document may change; anything else is rejected.
 #[derive(Debug, Serialize, Deserialize)]
-pub struct NewTask {
+#[serde(deny_unknown_fields)]
+pub struct PatchableTask {
     pub title: String,
     pub description: Option<String>,
-    pub status: Option<TaskStatus>,
-    pub user_id: String,
+    pub status: String,
+    pub labels: Vec<String>,
+    pub priority: Priority,
+    pub project_id: Option<String>,
+    pub assignee_ids: Vec<String>,
+    pub due_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub custom_fields: BTreeMap<String, FieldValue>,
+}
+
+impl From<&Task> for PatchableTask {
+    fn from(task: &Task) -> Self {
+        PatchableTask {
+            title: task.title.clone(),
+            description: task.description.clone(),
+            status: task.status.clone(),
+            labels: task.labels.clone(),
+            priority: task.priority,
+            project_id: task.project_id.clone(),
+            assignee_ids: task.assignee_ids.clone(),
+            due_at: task.due_at,
+            custom_fields: task.custom_fields.clone(),
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+pub struct NewTask {
+    pub title: String,
+    pub description: Option<String>,
+    pub status: Option<String>,
+    pub workflow_id: Option<String>,
+    pub labels: Option<Vec<String>>,
+    pub priority: Option<Priority>,
+    pub project_id: Option<String>,
+    /// Makes the new task a subtask of this one.
+    pub parent_id: Option<String>,
+    /// A workspace the caller belongs to; its members can then be assigned.
+    pub workspace_id: Option<String>,
+    /// For workspace tasks, the member who will own the task; defaults to the caller,
+    /// who is recorded as its creator either way.
+    pub owner_id: Option<String>,
+    /// Defaults to the caller.
+    pub assignee_ids: Option<Vec<String>>,
+    pub due_at: Option<DateTime<Utc>>,
+    /// Values for the custom fields of the task's project, keyed by field id.
+    pub custom_fields: Option<BTreeMap<String, FieldValue>>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct UpdateTask {
     pub title: Option<String>,
-    pub description: Option<String>,
-    pub status: Option<TaskStatus>,
-}
\ No newline at end of file
+    /// `None` leaves the description alone, `Some(None)` (an explicit `null`) clears it.
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "::serde_with::rust::double_option")]
+    pub description: Option<Option<String>>,
+    pub status: Option<String>,
+    pub labels: Option<Vec<String>>,
+    pub priority: Option<Priority>,
+    /// `Some(None)` (an explicit `null`) removes the task from its project.
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "::serde_with::rust::double_option")]
+    pub project_id: Option<Option<String>>,
+    pub assignee_ids: Option<Vec<String>>,
+    /// `Some(None)` (an explicit `null`) clears the due date.
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "::serde_with::rust::double_option")]
+    pub due_at: Option<Option<DateTime<Utc>>>,
+    /// Sets the given custom fields, keyed by field id; `null` removes a value.
+    pub custom_fields: Option<BTreeMap<String, Option<FieldValue>>>,
+}
+
+impl UpdateTask {
+    /// Whether the update leaves every field alone.
+    pub fn is_empty(&self) -> bool {
+        self.title.is_none()
+            && self.description.is_none()
+            && self.status.is_none()
+            && self.labels.is_none()
+            && self.priority.is_none()
+            && self.project_id.is_none()
+            && self.assignee_ids.is_none()
+            && self.due_at.is_none()
+            && self.custom_fields.is_none()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateTaskQuery {
+    /// Stop the task's running timer when the update completes the task.
+    pub stop_timer: Option<bool>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskScope {
+    /// Tasks the caller owns.
+    #[default]
+    Owned,
+    /// Tasks assigned to the caller, whoever owns them.
+    Assigned,
+    /// Tasks the caller created.
+    Created,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TaskListQuery {
+    #[serde(default)]
+    pub scope: TaskScope,
+    pub project_id: Option<String>,
+    /// Also list tasks of archived projects.
+    pub include_archived: Option<bool>,
+    /// With `custom_value`, only tasks whose value of this custom field of
+    /// `project_id` matches.
+    pub custom_field: Option<String>,
+    pub custom_value: Option<String>,
+    /// Orders by this custom field of `project_id`; tasks without a value come first.
+    pub sort_by_field: Option<String>,
+    #[serde(default)]
+    pub order: SortDirection,
+    /// Include `description_html`.
+    pub html: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TaskQuery {
+    /// Include `description_html`.
+    pub html: Option<bool>,
+}
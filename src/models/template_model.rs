@@ -0,0 +1,68 @@
+use std::collections::HashMap;
+use serde::{Serialize, Deserialize};
+use chrono::{DateTime, Utc};
+use serde_with::{serde_as, DisplayFromStr};
+use crate::models::deserialize_object_id_to_hex;
+use crate::models::task_model::{Priority, TaskResponse};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateSubtask {
+    pub title: String,
+    pub description: Option<String>,
+}
+
+/// A reusable task. Text fields may contain `{{variable}}` placeholders that are filled
+/// in when the template is instantiated.
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskTemplate {
+    #[serde(rename = "_id", deserialize_with = "deserialize_object_id_to_hex", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub user_id: String,
+    pub name: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub labels: Vec<String>,
+    pub priority: Priority,
+    pub subtasks: Vec<TemplateSubtask>,
+    #[serde_as(as = "DisplayFromStr")]
+    pub created_at: DateTime<Utc>,
+    #[serde_as(as = "DisplayFromStr")]
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NewTemplate {
+    pub name: String,
+    pub title: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub labels: Vec<String>,
+    #[serde(default)]
+    pub priority: Priority,
+    #[serde(default)]
+    pub subtasks: Vec<TemplateSubtask>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TemplateFromTask {
+    pub name: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct InstantiateTemplate {
+    /// Values for custom placeholders; they override the built-in ones.
+    #[serde(default)]
+    pub variables: HashMap<String, String>,
+    pub workflow_id: Option<String>,
+    pub project_id: Option<String>,
+    pub workspace_id: Option<String>,
+    pub due_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TaskTree {
+    #[serde(flatten)]
+    pub task: TaskResponse,
+    pub subtasks: Vec<TaskResponse>,
+}
@@ -0,0 +1,104 @@
+use serde::{Serialize, Deserialize};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde_with::{serde_as, DisplayFromStr};
+use crate::models::deserialize_object_id_to_hex;
+
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimeEntry {
+    #[serde(rename = "_id", deserialize_with = "deserialize_object_id_to_hex", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub task_id: String,
+    pub user_id: String,
+    pub note: Option<String>,
+    #[serde_as(as = "DisplayFromStr")]
+    pub started_at: DateTime<Utc>,
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub ended_at: Option<DateTime<Utc>>,
+    /// True for a running timer; a unique index allows one per user.
+    pub running: bool,
+    #[serde_as(as = "DisplayFromStr")]
+    pub created_at: DateTime<Utc>,
+}
+
+impl TimeEntry {
+    /// Tracked time in seconds, counting a running timer up to `now`.
+    pub fn duration_seconds(&self, now: DateTime<Utc>) -> i64 {
+        (self.ended_at.unwrap_or(now) - self.started_at).num_seconds().max(0)
+    }
+}
+
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TimeEntryResponse {
+    #[serde(rename = "_id")]
+    pub id: Option<String>,
+    pub task_id: String,
+    pub note: Option<String>,
+    #[serde_as(as = "DisplayFromStr")]
+    pub started_at: DateTime<Utc>,
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub ended_at: Option<DateTime<Utc>>,
+    pub running: bool,
+    pub duration_seconds: i64,
+}
+
+impl From<TimeEntry> for TimeEntryResponse {
+    fn from(entry: TimeEntry) -> Self {
+        TimeEntryResponse {
+            duration_seconds: entry.duration_seconds(Utc::now()),
+            id: entry.id,
+            task_id: entry.task_id,
+            note: entry.note,
+            started_at: entry.started_at,
+            ended_at: entry.ended_at,
+            running: entry.running,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StartTimer {
+    pub note: Option<String>,
+}
+
+/// A manually logged entry; give either `ended_at` or `duration_seconds`.
+#[derive(Debug, Deserialize)]
+pub struct NewTimeEntry {
+    pub started_at: DateTime<Utc>,
+    pub ended_at: Option<DateTime<Utc>>,
+    pub duration_seconds: Option<i64>,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeGrouping {
+    Task,
+    Project,
+    Day,
+}
+
+/// Filters time entries; `from` and `to` are inclusive UTC days matched against `started_at`.
+#[derive(Debug, Deserialize)]
+pub struct TimeQuery {
+    pub task_id: Option<String>,
+    pub project_id: Option<String>,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub group_by: Option<TimeGrouping>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TimeTotal {
+    /// Task id, project id (`null` for tasks without a project) or `YYYY-MM-DD` day.
+    pub key: Option<String>,
+    pub seconds: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TimeSummary {
+    pub group_by: TimeGrouping,
+    pub total_seconds: i64,
+    pub totals: Vec<TimeTotal>,
+}
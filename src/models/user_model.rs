@@ -1,13 +1,5 @@
-use serde::{Serialize, Deserialize, Deserializer};
-use mongodb::bson::oid::ObjectId;
-
-fn deserialize_object_id_to_hex<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
-where
-    D: Deserializer<'de>,
-{
-    let oid = Option::<ObjectId>::deserialize(deserializer)?;
-    Ok(oid.map(|o| o.to_hex()))
-}
+use serde::{Serialize, Deserialize};
+use crate::models::deserialize_object_id_to_hex;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct User {
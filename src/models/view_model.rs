@@ -0,0 +1,145 @@
+use serde::{Serialize, Deserialize};
+use chrono::{DateTime, Utc};
+use serde_with::{serde_as, DisplayFromStr};
+use crate::models::deserialize_object_id_to_hex;
+use crate::models::task_model::{Priority, TaskResponse, TaskScope};
+
+/// Task criteria of a view. Every set criterion must match; list criteria match any
+/// of their values. Date criteria are in days relative to the current UTC day, so a
+/// saved view keeps meaning the same thing over time.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ViewFilter {
+    pub scope: TaskScope,
+    pub statuses: Vec<String>,
+    pub labels: Vec<String>,
+    pub priorities: Vec<Priority>,
+    pub project_id: Option<String>,
+    pub assignee_id: Option<String>,
+    /// Due on or after today plus this many days.
+    pub due_from_days: Option<i64>,
+    /// Due on or before today plus this many days.
+    pub due_to_days: Option<i64>,
+    /// Not updated in this many days.
+    pub stale_days: Option<i64>,
+    /// Only completed (`true`) or only open (`false`) tasks.
+    pub completed: Option<bool>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortField {
+    /// Board order.
+    #[default]
+    Rank,
+    Title,
+    Priority,
+    DueAt,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortDirection {
+    #[default]
+    Asc,
+    Desc,
+}
+
+/// Tasks without a value for the sort field come last in either direction.
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ViewSort {
+    pub field: SortField,
+    pub direction: SortDirection,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GroupBy {
+    Status,
+    Priority,
+    Project,
+    /// A task with several labels appears in each of their groups.
+    Label,
+    /// A task with several assignees appears in each of their groups.
+    Assignee,
+}
+
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedView {
+    #[serde(rename = "_id", deserialize_with = "deserialize_object_id_to_hex", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub user_id: String,
+    /// Shared with the members of this workspace, who can use but not change the view.
+    pub workspace_id: Option<String>,
+    pub name: String,
+    pub filter: ViewFilter,
+    pub sort: ViewSort,
+    pub group_by: Option<GroupBy>,
+    #[serde_as(as = "DisplayFromStr")]
+    pub created_at: DateTime<Utc>,
+    #[serde_as(as = "DisplayFromStr")]
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NewView {
+    pub name: String,
+    pub workspace_id: Option<String>,
+    #[serde(default)]
+    pub filter: ViewFilter,
+    #[serde(default)]
+    pub sort: ViewSort,
+    pub group_by: Option<GroupBy>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ViewResponse {
+    #[serde(rename = "_id")]
+    pub id: String,
+    pub name: String,
+    /// Smart views available to everyone; they cannot be changed or deleted.
+    pub builtin: bool,
+    pub user_id: Option<String>,
+    pub workspace_id: Option<String>,
+    pub filter: ViewFilter,
+    pub sort: ViewSort,
+    pub group_by: Option<GroupBy>,
+}
+
+impl From<SavedView> for ViewResponse {
+    fn from(view: SavedView) -> Self {
+        ViewResponse {
+            id: view.id.unwrap_or_default(),
+            name: view.name,
+            builtin: false,
+            user_id: Some(view.user_id),
+            workspace_id: view.workspace_id,
+            filter: view.filter,
+            sort: view.sort,
+            group_by: view.group_by,
+        }
+    }
+}
+
+/// Tasks sharing a group value; `key` is `null` for tasks without one, and for the
+/// single group of an ungrouped view.
+#[derive(Debug, Serialize)]
+pub struct TaskGroup {
+    pub key: Option<String>,
+    pub tasks: Vec<TaskResponse>,
+}
+
+/// One page of a view's tasks, grouped. `total` counts matching tasks, not group
+/// entries: a task with several labels or assignees counts once.
+#[derive(Debug, Serialize)]
+pub struct ViewTasks {
+    pub view: ViewResponse,
+    pub groups: Vec<TaskGroup>,
+    pub page: u64,
+    pub limit: u64,
+    pub total: u64,
+}
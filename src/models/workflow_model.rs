@@ -0,0 +1,206 @@
+use std::collections::HashSet;
+use serde::{Serialize, Deserialize};
+use crate::models::deserialize_object_id_to_hex;
+
+/// Id of the built-in workflow that mirrors the original Pending/InProgress/Completed statuses.
+pub const DEFAULT_WORKFLOW_ID: &str = "default";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StatusCategory {
+    Open,
+    Done,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowStatus {
+    pub name: String,
+    pub category: StatusCategory,
+    pub order: i32,
+    /// Maximum number of tasks in this board column; writes that would exceed it are refused.
+    #[serde(default)]
+    pub wip_limit: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowTransition {
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Workflow {
+    #[serde(rename = "_id", deserialize_with = "deserialize_object_id_to_hex", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub user_id: String,
+    /// Shared with the members of this workspace, who can use but not change the workflow.
+    #[serde(default)]
+    pub workspace_id: Option<String>,
+    pub name: String,
+    pub statuses: Vec<WorkflowStatus>,
+    /// `None` allows moving between any two statuses.
+    pub transitions: Option<Vec<WorkflowTransition>>,
+    pub is_default: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NewWorkflow {
+    pub name: String,
+    pub workspace_id: Option<String>,
+    pub statuses: Vec<WorkflowStatus>,
+    pub transitions: Option<Vec<WorkflowTransition>>,
+    pub is_default: Option<bool>,
+}
+
+impl Workflow {
+    pub fn builtin_default() -> Self {
+        let status = |name: &str, category, order| WorkflowStatus {
+            name: name.to_string(),
+            category,
+            order,
+            wip_limit: None,
+        };
+
+        Workflow {
+            id: Some(DEFAULT_WORKFLOW_ID.to_string()),
+            user_id: String::new(),
+            workspace_id: None,
+            name: "Default".to_string(),
+            statuses: vec![
+                status("Pending", StatusCategory::Open, 0),
+                status("InProgress", StatusCategory::Open, 1),
+                status("Completed", StatusCategory::Done, 2),
+            ],
+            transitions: None,
+            is_default: false,
+        }
+    }
+
+    pub fn status(&self, name: &str) -> Option<&WorkflowStatus> {
+        self.statuses.iter().find(|s| s.name == name)
+    }
+
+    /// The status new tasks start in: the one with the lowest `order`.
+    pub fn initial_status(&self) -> Option<&WorkflowStatus> {
+        self.statuses.iter().min_by_key(|s| s.order)
+    }
+
+    pub fn can_transition(&self, from: &str, to: &str) -> bool {
+        if from == to {
+            return true;
+        }
+        match &self.transitions {
+            Some(transitions) => transitions.iter().any(|t| t.from == from && t.to == to),
+            None => true,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Workflow name is required".to_string());
+        }
+        if self.statuses.is_empty() {
+            return Err("Workflow needs at least one status".to_string());
+        }
+
+        let mut names = HashSet::new();
+        for status in &self.statuses {
+            if status.name.trim().is_empty() {
+                return Err("Status name is required".to_string());
+            }
+            if !names.insert(status.name.as_str()) {
+                return Err(format!("Duplicate status: {}", status.name));
+            }
+            if status.wip_limit == Some(0) {
+                return Err(format!("WIP limit of {} must be at least 1", status.name));
+            }
+        }
+
+        if let Some(transitions) = &self.transitions {
+            for t in transitions {
+                if !names.contains(t.from.as_str()) || !names.contains(t.to.as_str()) {
+                    return Err(format!("Transition {} -> {} references an unknown status", t.from, t.to));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workflow(transitions: Option<&[(&str, &str)]>) -> Workflow {
+        let mut workflow = Workflow::builtin_default();
+        workflow.name = "Review flow".to_string();
+        workflow.transitions = transitions.map(|pairs| {
+            pairs
+                .iter()
+                .map(|(from, to)| WorkflowTransition { from: from.to_string(), to: to.to_string() })
+                .collect()
+        });
+        workflow
+    }
+
+    #[test]
+    fn any_change_is_allowed_without_transitions() {
+        let workflow = workflow(None);
+        assert!(workflow.can_transition("Pending", "Completed"));
+        assert!(workflow.can_transition("Completed", "Pending"));
+    }
+
+    #[test]
+    fn only_listed_transitions_are_allowed() {
+        let workflow = workflow(Some(&[("Pending", "InProgress"), ("InProgress", "Completed")]));
+        assert!(workflow.can_transition("Pending", "InProgress"));
+        assert!(workflow.can_transition("InProgress", "Completed"));
+        assert!(!workflow.can_transition("Pending", "Completed"));
+        // Transitions are one-way.
+        assert!(!workflow.can_transition("InProgress", "Pending"));
+        // Staying put is always allowed.
+        assert!(workflow.can_transition("Completed", "Completed"));
+
+        assert!(!Workflow { transitions: Some(Vec::new()), ..workflow }.can_transition("Pending", "InProgress"));
+    }
+
+    #[test]
+    fn accepts_the_built_in_workflow() {
+        assert_eq!(Workflow::builtin_default().validate(), Ok(()));
+        assert_eq!(workflow(Some(&[("Pending", "Completed")])).validate(), Ok(()));
+    }
+
+    #[test]
+    fn requires_a_name_and_statuses() {
+        let mut unnamed = workflow(None);
+        unnamed.name = "  ".to_string();
+        assert_eq!(unnamed.validate().unwrap_err(), "Workflow name is required");
+
+        let mut empty = workflow(None);
+        empty.statuses.clear();
+        assert_eq!(empty.validate().unwrap_err(), "Workflow needs at least one status");
+    }
+
+    #[test]
+    fn rejects_bad_statuses() {
+        let mut blank = workflow(None);
+        blank.statuses[1].name = " ".to_string();
+        assert_eq!(blank.validate().unwrap_err(), "Status name is required");
+
+        let mut duplicate = workflow(None);
+        duplicate.statuses[2].name = "Pending".to_string();
+        assert_eq!(duplicate.validate().unwrap_err(), "Duplicate status: Pending");
+
+        let mut zero = workflow(None);
+        zero.statuses[1].wip_limit = Some(0);
+        assert_eq!(zero.validate().unwrap_err(), "WIP limit of InProgress must be at least 1");
+        zero.statuses[1].wip_limit = Some(1);
+        assert_eq!(zero.validate(), Ok(()));
+    }
+
+    #[test]
+    fn rejects_transitions_to_unknown_statuses() {
+        let workflow = workflow(Some(&[("Pending", "Blocked")]));
+        assert_eq!(workflow.validate().unwrap_err(), "Transition Pending -> Blocked references an unknown status");
+    }
+}
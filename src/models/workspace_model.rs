@@ -0,0 +1,33 @@
+use serde::{Serialize, Deserialize};
+use chrono::{DateTime, Utc};
+use serde_with::{serde_as, DisplayFromStr};
+use crate::models::deserialize_object_id_to_hex;
+
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Workspace {
+    #[serde(rename = "_id", deserialize_with = "deserialize_object_id_to_hex", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub name: String,
+    pub owner_id: String,
+    /// Everyone in the workspace, including the owner.
+    pub member_ids: Vec<String>,
+    #[serde_as(as = "DisplayFromStr")]
+    pub created_at: DateTime<Utc>,
+}
+
+impl Workspace {
+    pub fn is_member(&self, user_id: &str) -> bool {
+        self.member_ids.iter().any(|m| m == user_id)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NewWorkspace {
+    pub name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NewMember {
+    pub user_id: String,
+}
@@ -0,0 +1,19 @@
+use actix_web::web;
+
+use crate::controllers::account_controller::{create_export, download_export, get_export, import_account};
+use crate::middlewares::auth_middleware::AuthMiddleware;
+
+pub fn account_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/account")
+        .wrap(AuthMiddleware)
+        .route("/exports", web::post().to(create_export))
+        .route("/exports/{export_id}", web::get().to(get_export))
+        .route("/exports/{export_id}/download", web::get().to(download_export))
+        .service(
+            web::resource("/import")
+                .app_data(web::JsonConfig::default().limit(64 * 1024 * 1024))
+                .route(web::post().to(import_account))
+        )
+    );
+}
@@ -0,0 +1,30 @@
+use actix_web::{http::Method, web, Route};
+
+use crate::controllers::caldav_controller::{
+    dav_options, delete_task_resource, get_task_resource, propfind_collection, propfind_home, propfind_principal,
+    propfind_root, propfind_task, put_task_resource, report_collection, well_known_caldav,
+};
+use crate::middlewares::basic_auth_middleware::BasicAuthMiddleware;
+
+fn dav_method(name: &str) -> Route {
+    web::method(Method::from_bytes(name.as_bytes()).expect("valid HTTP method"))
+}
+
+pub fn caldav_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("/.well-known/caldav").to(well_known_caldav));
+    cfg.service(
+        web::scope("/caldav")
+        .wrap(BasicAuthMiddleware)
+        .route("", dav_method("PROPFIND").to(propfind_root))
+        .route("/", dav_method("PROPFIND").to(propfind_root))
+        .route("/principal/", dav_method("PROPFIND").to(propfind_principal))
+        .route("/calendars/", dav_method("PROPFIND").to(propfind_home))
+        .route("/calendars/tasks/", dav_method("PROPFIND").to(propfind_collection))
+        .route("/calendars/tasks/", dav_method("REPORT").to(report_collection))
+        .route("/calendars/tasks/{name}", dav_method("PROPFIND").to(propfind_task))
+        .route("/calendars/tasks/{name}", web::get().to(get_task_resource))
+        .route("/calendars/tasks/{name}", web::put().to(put_task_resource))
+        .route("/calendars/tasks/{name}", web::delete().to(delete_task_resource))
+        .route("/{tail:.*}", web::method(Method::OPTIONS).to(dav_options))
+    );
+}
@@ -0,0 +1,17 @@
+use actix_web::web;
+
+use crate::controllers::calendar_controller::{delete_feed_token, get_calendar_feed, regenerate_feed_token};
+use crate::middlewares::auth_middleware::AuthMiddleware;
+
+pub fn calendar_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/calendar")
+        .service(
+            web::scope("/feed")
+            .wrap(AuthMiddleware)
+            .route("", web::post().to(regenerate_feed_token))
+            .route("", web::delete().to(delete_feed_token))
+        )
+        .route("/{token}.ics", web::get().to(get_calendar_feed))
+    );
+}
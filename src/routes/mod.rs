@@ -1,2 +1,15 @@
 pub mod user_routes;
-pub mod task_routes;
\ No newline at end of file
+pub mod task_routes;
+pub mod workflow_routes;
+pub mod trash_routes;
+pub mod project_routes;
+pub mod time_routes;
+pub mod workspace_routes;
+pub mod notification_routes;
+pub mod template_routes;
+pub mod stats_routes;
+pub mod view_routes;
+pub mod calendar_routes;
+pub mod caldav_routes;
+pub mod account_routes;
+pub mod webhook_routes;
\ No newline at end of file
@@ -0,0 +1,19 @@
+use actix_web::web;
+
+use crate::controllers::notification_controller::{
+    get_notifications, get_preferences, mark_all_read, mark_read, mark_unread, update_preferences,
+};
+use crate::middlewares::auth_middleware::AuthMiddleware;
+
+pub fn notification_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/notifications")
+            .wrap(AuthMiddleware)
+            .route("", web::get().to(get_notifications))
+            .route("/read_all", web::post().to(mark_all_read))
+            .route("/preferences", web::get().to(get_preferences))
+            .route("/preferences", web::put().to(update_preferences))
+            .route("/{notification_id}/read", web::post().to(mark_read))
+            .route("/{notification_id}/unread", web::post().to(mark_unread))
+    );
+}
@@ -0,0 +1,26 @@
+use actix_web::web;
+
+use crate::controllers::custom_field_controller::{create_custom_field, delete_custom_field, update_custom_field};
+use crate::controllers::project_controller::{
+    archive_project, create_project, delete_project, get_project, get_project_tasks, get_projects, unarchive_project,
+    update_project,
+};
+use crate::middlewares::auth_middleware::AuthMiddleware;
+
+pub fn project_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/projects")
+            .wrap(AuthMiddleware)
+            .route("", web::post().to(create_project))
+            .route("", web::get().to(get_projects))
+            .route("/{project_id}", web::get().to(get_project))
+            .route("/{project_id}", web::put().to(update_project))
+            .route("/{project_id}", web::delete().to(delete_project))
+            .route("/{project_id}/archive", web::post().to(archive_project))
+            .route("/{project_id}/unarchive", web::post().to(unarchive_project))
+            .route("/{project_id}/tasks", web::get().to(get_project_tasks))
+            .route("/{project_id}/fields", web::post().to(create_custom_field))
+            .route("/{project_id}/fields/{field_id}", web::put().to(update_custom_field))
+            .route("/{project_id}/fields/{field_id}", web::delete().to(delete_custom_field))
+    );
+}
@@ -0,0 +1,12 @@
+use actix_web::web;
+
+use crate::controllers::stats_controller::get_task_stats;
+use crate::middlewares::auth_middleware::AuthMiddleware;
+
+pub fn stats_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/stats")
+            .wrap(AuthMiddleware)
+            .route("/tasks", web::get().to(get_task_stats))
+    );
+}
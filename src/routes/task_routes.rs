@@ -1,6 +1,15 @@
 use actix_web::web;
 
-use crate::controllers::task_controller::{create_task, delete_task, get_task, get_tasks, update_task};
+use crate::controllers::attachment_controller::{delete_attachment, download_attachment, get_attachments, upload_attachment};
+use crate::controllers::board_controller::{get_board, move_task};
+use crate::controllers::batch_controller::{batch_tasks, bulk_update_tasks};
+use crate::controllers::csv_controller::{export_tasks, import_tasks};
+use crate::controllers::event_controller::{stream_task_events, task_events_socket};
+use crate::controllers::comment_controller::{create_comment, delete_comment, get_comments, update_comment};
+use crate::controllers::history_controller::get_task_history;
+use crate::controllers::import_controller::import_from;
+use crate::controllers::time_controller::{add_time_entry, get_task_time_entries, start_timer, stop_task_timer};
+use crate::controllers::task_controller::{create_task, delete_task, get_task, get_tasks, patch_task, update_task};
 use crate::middlewares::auth_middleware::AuthMiddleware;
 
 pub fn task_routes(cfg: &mut web::ServiceConfig) {
@@ -9,8 +18,43 @@ pub fn task_routes(cfg: &mut web::ServiceConfig) {
             .wrap(AuthMiddleware)
             .route("", web::post().to(create_task))
             .route("", web::get().to(get_tasks))
+            .service(
+                web::resource("/batch")
+                    .app_data(web::JsonConfig::default().limit(16 * 1024 * 1024))
+                    .route(web::post().to(batch_tasks))
+            )
+            .route("/bulk_update", web::post().to(bulk_update_tasks))
+            .route("/export", web::get().to(export_tasks))
+            .route("/events", web::get().to(stream_task_events))
+            .route("/events/ws", web::get().to(task_events_socket))
+            .service(
+                web::resource("/import")
+                    .app_data(web::JsonConfig::default().limit(16 * 1024 * 1024))
+                    .route(web::post().to(import_tasks))
+            )
+            .service(
+                web::resource("/import/{format}")
+                    .app_data(web::JsonConfig::default().limit(16 * 1024 * 1024))
+                    .route(web::post().to(import_from))
+            )
+            .route("/board", web::get().to(get_board))
             .route("/{task_id}", web::get().to(get_task))
             .route("/{task_id}", web::put().to(update_task))
+            .route("/{task_id}", web::patch().to(patch_task))
             .route("/{task_id}", web::delete().to(delete_task))
+            .route("/{task_id}/move", web::post().to(move_task))
+            .route("/{task_id}/timer/start", web::post().to(start_timer))
+            .route("/{task_id}/timer/stop", web::post().to(stop_task_timer))
+            .route("/{task_id}/time_entries", web::post().to(add_time_entry))
+            .route("/{task_id}/time_entries", web::get().to(get_task_time_entries))
+            .route("/{task_id}/history", web::get().to(get_task_history))
+            .route("/{task_id}/comments", web::post().to(create_comment))
+            .route("/{task_id}/comments", web::get().to(get_comments))
+            .route("/{task_id}/comments/{comment_id}", web::put().to(update_comment))
+            .route("/{task_id}/comments/{comment_id}", web::delete().to(delete_comment))
+            .route("/{task_id}/attachments", web::post().to(upload_attachment))
+            .route("/{task_id}/attachments", web::get().to(get_attachments))
+            .route("/{task_id}/attachments/{attachment_id}", web::get().to(download_attachment))
+            .route("/{task_id}/attachments/{attachment_id}", web::delete().to(delete_attachment))
     );
-}
\ No newline at end of file
+}
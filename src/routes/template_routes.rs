@@ -0,0 +1,21 @@
+use actix_web::web;
+
+use crate::controllers::template_controller::{
+    create_template, create_template_from_task, delete_template, get_template, get_templates, instantiate_template,
+    update_template,
+};
+use crate::middlewares::auth_middleware::AuthMiddleware;
+
+pub fn template_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/templates")
+            .wrap(AuthMiddleware)
+            .route("", web::post().to(create_template))
+            .route("", web::get().to(get_templates))
+            .route("/from_task/{task_id}", web::post().to(create_template_from_task))
+            .route("/{template_id}", web::get().to(get_template))
+            .route("/{template_id}", web::put().to(update_template))
+            .route("/{template_id}", web::delete().to(delete_template))
+            .route("/{template_id}/instantiate", web::post().to(instantiate_template))
+    );
+}
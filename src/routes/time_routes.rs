@@ -0,0 +1,16 @@
+use actix_web::web;
+
+use crate::controllers::time_controller::{delete_time_entry, get_time_entries, get_time_summary, get_timer, stop_timer};
+use crate::middlewares::auth_middleware::AuthMiddleware;
+
+pub fn time_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/time")
+            .wrap(AuthMiddleware)
+            .route("/timer", web::get().to(get_timer))
+            .route("/timer/stop", web::post().to(stop_timer))
+            .route("/entries", web::get().to(get_time_entries))
+            .route("/entries/{entry_id}", web::delete().to(delete_time_entry))
+            .route("/summary", web::get().to(get_time_summary))
+    );
+}
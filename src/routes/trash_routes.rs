@@ -0,0 +1,14 @@
+use actix_web::web;
+
+use crate::controllers::trash_controller::{delete_task_permanently, get_trash, restore_task};
+use crate::middlewares::auth_middleware::AuthMiddleware;
+
+pub fn trash_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/trash")
+            .wrap(AuthMiddleware)
+            .route("", web::get().to(get_trash))
+            .route("/{task_id}/restore", web::post().to(restore_task))
+            .route("/{task_id}", web::delete().to(delete_task_permanently))
+    );
+}
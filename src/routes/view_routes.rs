@@ -0,0 +1,19 @@
+use actix_web::web;
+
+use crate::controllers::view_controller::{
+    create_view, delete_view, get_view, get_view_tasks, get_views, update_view,
+};
+use crate::middlewares::auth_middleware::AuthMiddleware;
+
+pub fn view_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/views")
+            .wrap(AuthMiddleware)
+            .route("", web::post().to(create_view))
+            .route("", web::get().to(get_views))
+            .route("/{view_id}", web::get().to(get_view))
+            .route("/{view_id}", web::put().to(update_view))
+            .route("/{view_id}", web::delete().to(delete_view))
+            .route("/{view_id}/tasks", web::get().to(get_view_tasks))
+    );
+}
@@ -0,0 +1,20 @@
+use actix_web::web;
+
+use crate::controllers::webhook_controller::{
+    create_webhook, delete_webhook, get_deliveries, get_webhook, get_webhooks, test_webhook, update_webhook,
+};
+use crate::middlewares::auth_middleware::AuthMiddleware;
+
+pub fn webhook_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/webhooks")
+            .wrap(AuthMiddleware)
+            .route("", web::post().to(create_webhook))
+            .route("", web::get().to(get_webhooks))
+            .route("/{webhook_id}", web::get().to(get_webhook))
+            .route("/{webhook_id}", web::put().to(update_webhook))
+            .route("/{webhook_id}", web::delete().to(delete_webhook))
+            .route("/{webhook_id}/test", web::post().to(test_webhook))
+            .route("/{webhook_id}/deliveries", web::get().to(get_deliveries))
+    );
+}
@@ -0,0 +1,16 @@
+use actix_web::web;
+
+use crate::controllers::workflow_controller::{create_workflow, delete_workflow, get_workflow, get_workflows, update_workflow};
+use crate::middlewares::auth_middleware::AuthMiddleware;
+
+pub fn workflow_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/workflows")
+            .wrap(AuthMiddleware)
+            .route("", web::post().to(create_workflow))
+            .route("", web::get().to(get_workflows))
+            .route("/{workflow_id}", web::get().to(get_workflow))
+            .route("/{workflow_id}", web::put().to(update_workflow))
+            .route("/{workflow_id}", web::delete().to(delete_workflow))
+    );
+}
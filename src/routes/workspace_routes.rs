@@ -0,0 +1,19 @@
+use actix_web::web;
+
+use crate::controllers::workspace_controller::{
+    add_member, create_workspace, delete_workspace, get_workspace, get_workspaces, remove_member,
+};
+use crate::middlewares::auth_middleware::AuthMiddleware;
+
+pub fn workspace_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/workspaces")
+            .wrap(AuthMiddleware)
+            .route("", web::post().to(create_workspace))
+            .route("", web::get().to(get_workspaces))
+            .route("/{workspace_id}", web::get().to(get_workspace))
+            .route("/{workspace_id}", web::delete().to(delete_workspace))
+            .route("/{workspace_id}/members", web::post().to(add_member))
+            .route("/{workspace_id}/members/{user_id}", web::delete().to(remove_member))
+    );
+}
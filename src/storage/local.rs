@@ -0,0 +1,90 @@
+use std::{io, path::{Component, Path, PathBuf}};
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::TryStreamExt;
+use tokio::fs;
+use tokio_util::io::ReaderStream;
+
+use super::{ByteStream, Storage, StorageError};
+
+pub struct LocalStorage {
+    root: PathBuf,
+}
+
+impl LocalStorage {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        LocalStorage { root: root.into() }
+    }
+
+    fn path_for(&self, key: &str) -> Result<PathBuf, StorageError> {
+        let relative = Path::new(key);
+        if !relative.components().all(|c| matches!(c, Component::Normal(_))) {
+            return Err(StorageError::Backend(format!("invalid storage key: {}", key)));
+        }
+        Ok(self.root.join(relative))
+    }
+}
+
+fn map_io(err: io::Error) -> StorageError {
+    match err.kind() {
+        io::ErrorKind::NotFound => StorageError::NotFound,
+        _ => StorageError::Backend(err.to_string()),
+    }
+}
+
+#[async_trait]
+impl Storage for LocalStorage {
+    async fn put(&self, key: &str, data: Bytes, _content_type: &str) -> Result<(), StorageError> {
+        let path = self.path_for(key)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await.map_err(map_io)?;
+        }
+        fs::write(path, data).await.map_err(map_io)
+    }
+
+    async fn get(&self, key: &str) -> Result<ByteStream, StorageError> {
+        let file = fs::File::open(self.path_for(key)?).await.map_err(map_io)?;
+        Ok(Box::pin(ReaderStream::new(file).map_err(map_io)))
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), StorageError> {
+        match fs::remove_file(self.path_for(key)?).await {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(map_io(err)),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn read_all(storage: &LocalStorage, key: &str) -> Result<Vec<u8>, StorageError> {
+        let chunks: Vec<Bytes> = storage.get(key).await?.try_collect().await?;
+        Ok(chunks.concat())
+    }
+
+    #[actix_web::test]
+    async fn round_trip() {
+        let root = std::env::temp_dir().join(format!("local-storage-{}", uuid::Uuid::new_v4().simple()));
+        let storage = LocalStorage::new(&root);
+
+        storage.put("tasks/1/notes.txt", Bytes::from_static(b"hello"), "text/plain").await.unwrap();
+        assert_eq!(read_all(&storage, "tasks/1/notes.txt").await.unwrap(), b"hello");
+
+        storage.delete("tasks/1/notes.txt").await.unwrap();
+        assert!(matches!(read_all(&storage, "tasks/1/notes.txt").await, Err(StorageError::NotFound)));
+        // Deleting a missing object is not an error.
+        storage.delete("tasks/1/notes.txt").await.unwrap();
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[actix_web::test]
+    async fn rejects_keys_outside_the_root() {
+        let storage = LocalStorage::new(std::env::temp_dir());
+        for key in ["../escape", "/etc/passwd", "a/../../b"] {
+            assert!(matches!(storage.put(key, Bytes::new(), "text/plain").await, Err(StorageError::Backend(_))));
+        }
+    }
+}
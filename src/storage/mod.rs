@@ -0,0 +1,46 @@
+use std::{env, fmt, sync::Arc};
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::stream::BoxStream;
+
+pub mod local;
+pub mod s3;
+
+pub type ByteStream = BoxStream<'static, Result<Bytes, StorageError>>;
+
+#[derive(Debug)]
+pub enum StorageError {
+    NotFound,
+    Backend(String),
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorageError::NotFound => write!(f, "object not found"),
+            StorageError::Backend(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl std::error::Error for StorageError {}
+
+/// Where attachment blobs live. Keys are `/`-separated and generated by the server.
+#[async_trait]
+pub trait Storage: Send + Sync {
+    async fn put(&self, key: &str, data: Bytes, content_type: &str) -> Result<(), StorageError>;
+    async fn get(&self, key: &str) -> Result<ByteStream, StorageError>;
+    async fn delete(&self, key: &str) -> Result<(), StorageError>;
+}
+
+/// Builds the backend selected by `STORAGE_BACKEND` (`local` by default, or `s3`).
+pub fn storage_from_env() -> Arc<dyn Storage> {
+    match env::var("STORAGE_BACKEND").unwrap_or_else(|_| "local".to_string()).as_str() {
+        "s3" => Arc::new(s3::S3Storage::from_env()),
+        "local" => {
+            let root = env::var("STORAGE_LOCAL_PATH").unwrap_or_else(|_| "uploads".to_string());
+            Arc::new(local::LocalStorage::new(root))
+        }
+        other => panic!("❌ unknown STORAGE_BACKEND: {}", other),
+    }
+}
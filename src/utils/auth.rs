@@ -0,0 +1,20 @@
+use actix_web::{dev::Payload, error::ErrorUnauthorized, Error, FromRequest, HttpMessage, HttpRequest};
+use std::future::{ready, Ready};
+
+/// The user id that `AuthMiddleware` stored in the request extensions.
+pub struct AuthenticatedUser(pub String);
+
+impl FromRequest for AuthenticatedUser {
+    type Error = Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(
+            req.extensions()
+                .get::<String>()
+                .cloned()
+                .map(AuthenticatedUser)
+                .ok_or_else(|| ErrorUnauthorized("Authorization header missing or invalid")),
+        )
+    }
+}
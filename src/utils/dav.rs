@@ -0,0 +1,236 @@
+//! The parts of WebDAV (RFC 4918) and CalDAV (RFC 4791) the task calendar needs:
+//! reading PROPFIND and REPORT bodies and writing multistatus responses.
+
+use quick_xml::escape::escape;
+use quick_xml::events::Event;
+use quick_xml::name::ResolveResult;
+use quick_xml::NsReader;
+
+pub const DAV: &str = "DAV:";
+pub const CALDAV: &str = "urn:ietf:params:xml:ns:caldav";
+pub const CALENDARSERVER: &str = "http://calendarserver.org/ns/";
+
+/// An element name qualified by its namespace, such as a property name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PropName {
+    pub namespace: String,
+    pub name: String,
+}
+
+impl PropName {
+    pub fn new(namespace: &str, name: &str) -> Self {
+        PropName { namespace: namespace.to_string(), name: name.to_string() }
+    }
+
+    pub fn is(&self, namespace: &str, name: &str) -> bool {
+        self.namespace == namespace && self.name == name
+    }
+
+    /// The element with `content`, which must already be XML.
+    fn element(&self, content: &str) -> String {
+        let (prefix, declaration) = match self.namespace.as_str() {
+            DAV => ("D", String::new()),
+            CALDAV => ("C", String::new()),
+            CALENDARSERVER => ("CS", String::new()),
+            other => ("X", format!(" xmlns:X=\"{}\"", escape(other))),
+        };
+        if content.is_empty() {
+            format!("<{0}:{1}{2}/>", prefix, self.name, declaration)
+        } else {
+            format!("<{0}:{1}{2}>{3}</{0}:{1}>", prefix, self.name, declaration, content)
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct DavRequest {
+    /// Local name of the root element, e.g. `propfind` or `calendar-multiget`; empty
+    /// for an empty body.
+    pub root: String,
+    /// The properties asked for; empty for `allprop` or an empty body.
+    pub props: Vec<PropName>,
+    /// Resources named in a `calendar-multiget`.
+    pub hrefs: Vec<String>,
+    /// Component names of the `comp-filter`s in a `calendar-query`.
+    pub components: Vec<String>,
+}
+
+pub fn parse_request(body: &str) -> Result<DavRequest, String> {
+    let mut request = DavRequest::default();
+    let mut reader = NsReader::from_str(body);
+    reader.config_mut().trim_text(true);
+
+    // The open elements, outermost first.
+    let mut open: Vec<PropName> = Vec::new();
+    loop {
+        let (namespace, event) = reader.read_resolved_event().map_err(|e| e.to_string())?;
+        let namespace = match namespace {
+            ResolveResult::Bound(ns) => ns.as_ref().to_string(),
+            _ => String::new(),
+        };
+
+        match event {
+            Event::Start(ref e) | Event::Empty(ref e) => {
+                let element = PropName::new(&namespace, e.local_name().as_ref());
+                if open.is_empty() {
+                    request.root = element.name.clone();
+                }
+                if open.last().is_some_and(|parent| parent.is(DAV, "prop")) {
+                    request.props.push(element.clone());
+                }
+                if element.is(CALDAV, "comp-filter")
+                    && let Some(name) = e.try_get_attribute("name").map_err(|e| e.to_string())?
+                {
+                    request.components.push(name.value.to_string());
+                }
+                if matches!(event, Event::Start(_)) {
+                    open.push(element);
+                }
+            }
+            Event::End(_) => {
+                open.pop();
+            }
+            Event::Text(text) if open.last().is_some_and(|e| e.is(DAV, "href")) => {
+                request.hrefs.push(text.xml10_content().into_owned());
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(request)
+}
+
+/// Builds a `207 Multi-Status` body response by response.
+#[derive(Debug, Default)]
+pub struct Multistatus {
+    out: String,
+}
+
+impl Multistatus {
+    /// Adds a resource with the values (already XML) of the properties it has and the
+    /// names of those it lacks.
+    pub fn response(&mut self, href: &str, found: &[(PropName, String)], missing: &[PropName]) -> &mut Self {
+        self.out.push_str(&format!("<D:response><D:href>{}</D:href>", escape(href)));
+        if !found.is_empty() {
+            let props: String = found.iter().map(|(prop, value)| prop.element(value)).collect();
+            self.out.push_str(&format!(
+                "<D:propstat><D:prop>{}</D:prop><D:status>HTTP/1.1 200 OK</D:status></D:propstat>",
+                props
+            ));
+        }
+        if !missing.is_empty() {
+            let props: String = missing.iter().map(|prop| prop.element("")).collect();
+            self.out.push_str(&format!(
+                "<D:propstat><D:prop>{}</D:prop><D:status>HTTP/1.1 404 Not Found</D:status></D:propstat>",
+                props
+            ));
+        }
+        self.out.push_str("</D:response>");
+        self
+    }
+
+    pub fn not_found(&mut self, href: &str) -> &mut Self {
+        self.out.push_str(&format!(
+            "<D:response><D:href>{}</D:href><D:status>HTTP/1.1 404 Not Found</D:status></D:response>",
+            escape(href)
+        ));
+        self
+    }
+
+    pub fn finish(self) -> String {
+        format!(
+            "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<D:multistatus xmlns:D=\"{}\" xmlns:C=\"{}\" xmlns:CS=\"{}\">{}</D:multistatus>",
+            DAV, CALDAV, CALENDARSERVER, self.out
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_allprop_and_empty_bodies() {
+        let request = parse_request(r#"<?xml version="1.0"?><D:propfind xmlns:D="DAV:"><D:allprop/></D:propfind>"#).unwrap();
+        assert_eq!(request.root, "propfind");
+        assert!(request.props.is_empty());
+
+        let request = parse_request("").unwrap();
+        assert_eq!(request.root, "");
+        assert!(request.props.is_empty());
+    }
+
+    #[test]
+    fn reads_requested_properties_with_their_namespaces() {
+        let body = r#"<propfind xmlns="DAV:" xmlns:C="urn:ietf:params:xml:ns:caldav" xmlns:CS="http://calendarserver.org/ns/">
+            <prop>
+                <displayname/>
+                <resourcetype></resourcetype>
+                <C:supported-calendar-component-set/>
+                <CS:getctag/>
+                <X:color xmlns:X="http://apple.com/ns/ical/"/>
+            </prop>
+        </propfind>"#;
+        let request = parse_request(body).unwrap();
+        assert_eq!(
+            request.props,
+            vec![
+                PropName::new(DAV, "displayname"),
+                PropName::new(DAV, "resourcetype"),
+                PropName::new(CALDAV, "supported-calendar-component-set"),
+                PropName::new(CALENDARSERVER, "getctag"),
+                PropName::new("http://apple.com/ns/ical/", "color"),
+            ]
+        );
+    }
+
+    #[test]
+    fn reads_multiget_hrefs() {
+        let body = r#"<C:calendar-multiget xmlns:D="DAV:" xmlns:C="urn:ietf:params:xml:ns:caldav">
+            <D:prop><D:getetag/><C:calendar-data/></D:prop>
+            <D:href>/caldav/tasks/a.ics</D:href>
+            <D:href>/caldav/tasks/b%20c.ics</D:href>
+        </C:calendar-multiget>"#;
+        let request = parse_request(body).unwrap();
+        assert_eq!(request.root, "calendar-multiget");
+        assert_eq!(request.props, vec![PropName::new(DAV, "getetag"), PropName::new(CALDAV, "calendar-data")]);
+        assert_eq!(request.hrefs, vec!["/caldav/tasks/a.ics", "/caldav/tasks/b%20c.ics"]);
+    }
+
+    #[test]
+    fn reads_comp_filter_names() {
+        let body = r#"<C:calendar-query xmlns:D="DAV:" xmlns:C="urn:ietf:params:xml:ns:caldav">
+            <D:prop><D:getetag/></D:prop>
+            <C:filter>
+                <C:comp-filter name="VCALENDAR"><C:comp-filter name="VTODO"/></C:comp-filter>
+            </C:filter>
+        </C:calendar-query>"#;
+        let request = parse_request(body).unwrap();
+        assert_eq!(request.root, "calendar-query");
+        assert_eq!(request.components, vec!["VCALENDAR", "VTODO"]);
+        assert!(request.hrefs.is_empty());
+    }
+
+    #[test]
+    fn rejects_malformed_xml() {
+        assert!(parse_request("<D:propfind xmlns:D=\"DAV:\"><D:prop></D:propfind>").is_err());
+    }
+
+    #[test]
+    fn writes_found_and_missing_properties() {
+        let mut multistatus = Multistatus::default();
+        multistatus
+            .response(
+                "/caldav/tasks/a&b.ics",
+                &[(PropName::new(DAV, "getetag"), "\"1\"".to_string())],
+                &[PropName::new("urn:x", "color")],
+            )
+            .not_found("/caldav/tasks/gone.ics");
+        let out = multistatus.finish();
+
+        assert!(out.contains("<D:href>/caldav/tasks/a&amp;b.ics</D:href>"));
+        assert!(out.contains("<D:prop><D:getetag>\"1\"</D:getetag></D:prop><D:status>HTTP/1.1 200 OK</D:status>"));
+        assert!(out.contains("<X:color xmlns:X=\"urn:x\"/>"));
+        assert!(out.contains("<D:href>/caldav/tasks/gone.ics</D:href><D:status>HTTP/1.1 404 Not Found</D:status>"));
+    }
+}
@@ -0,0 +1,256 @@
+//! Minimal iCalendar (RFC 5545) support: writing escaped text values, folded content
+//! lines and CRLF line endings, and reading the properties of a component.
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+
+/// Content lines longer than this many octets are folded.
+const MAX_LINE_OCTETS: usize = 75;
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+pub fn format_date_time(date: &DateTime<Utc>) -> String {
+    date.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+pub fn format_date(date: &NaiveDate) -> String {
+    date.format("%Y%m%d").to_string()
+}
+
+/// Builds an iCalendar document line by line.
+#[derive(Debug, Default)]
+pub struct Calendar {
+    out: String,
+}
+
+impl Calendar {
+    /// Adds a property whose value is already in iCalendar format, e.g. a date.
+    pub fn raw(&mut self, name: &str, value: &str) -> &mut Self {
+        let line = format!("{}:{}", name, value);
+        let mut octets = 0;
+        for c in line.chars() {
+            if octets + c.len_utf8() > MAX_LINE_OCTETS {
+                self.out.push_str("\r\n ");
+                octets = 1;
+            }
+            self.out.push(c);
+            octets += c.len_utf8();
+        }
+        self.out.push_str("\r\n");
+        self
+    }
+
+    /// Adds a text property, escaping its value.
+    pub fn text(&mut self, name: &str, value: &str) -> &mut Self {
+        self.raw(name, &escape(value))
+    }
+
+    /// Adds a property listing several text values.
+    pub fn list(&mut self, name: &str, values: &[String]) -> &mut Self {
+        let joined: Vec<String> = values.iter().map(|v| escape(v)).collect();
+        self.raw(name, &joined.join(","))
+    }
+
+    pub fn finish(self) -> String {
+        self.out
+    }
+}
+
+/// A content line of a parsed component. `value` is kept escaped.
+#[derive(Debug, Clone)]
+pub struct Property {
+    pub name: String,
+    pub params: Vec<(String, String)>,
+    pub value: String,
+}
+
+impl Property {
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params.iter().find(|(n, _)| n.eq_ignore_ascii_case(name)).map(|(_, v)| v.as_str())
+    }
+
+    pub fn text(&self) -> String {
+        unescape(&self.value)
+    }
+
+    /// The values of a comma-separated list property such as CATEGORIES.
+    pub fn texts(&self) -> Vec<String> {
+        let mut values = Vec::new();
+        let mut current = String::new();
+        let mut escaped = false;
+        for c in self.value.chars() {
+            match c {
+                ',' if !escaped => values.push(std::mem::take(&mut current)),
+                c => current.push(c),
+            }
+            escaped = c == '\\' && !escaped;
+        }
+        values.push(current);
+        values.iter().map(|v| unescape(v).trim().to_string()).filter(|v| !v.is_empty()).collect()
+    }
+
+    /// A DATE-TIME or DATE value. Times with a TZID are resolved in that IANA time zone;
+    /// zones only defined by a VTIMEZONE component are not supported. Floating times are
+    /// read as UTC, and dates as midnight UTC.
+    pub fn date_time(&self) -> Result<DateTime<Utc>, String> {
+        let value = self.value.trim();
+        if self.param("VALUE").is_some_and(|v| v.eq_ignore_ascii_case("DATE")) || value.len() == 8 {
+            return NaiveDate::parse_from_str(value, "%Y%m%d")
+                .ok()
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+                .map(|d| d.and_utc())
+                .ok_or_else(|| format!("{} is not a date", value));
+        }
+
+        let local = NaiveDateTime::parse_from_str(value.trim_end_matches('Z'), "%Y%m%dT%H%M%S")
+            .map_err(|_| format!("{} is not a date-time", value))?;
+        let Some(tzid) = self.param("TZID").filter(|_| !value.ends_with('Z')) else { return Ok(local.and_utc()) };
+        let zone: Tz = tzid.trim_start_matches('/').parse().map_err(|_| format!("Unknown time zone {}", tzid))?;
+        zone.from_local_datetime(&local)
+            .earliest()
+            .map(|d| d.with_timezone(&Utc))
+            .ok_or_else(|| format!("{} does not exist in {}", value, tzid))
+    }
+}
+
+fn unescape(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => unescaped.push('\n'),
+            Some(other) => unescaped.push(other),
+            None => {}
+        }
+    }
+    unescaped
+}
+
+fn parse_line(line: &str) -> Option<Property> {
+    // The value starts at the first colon outside a quoted parameter value.
+    let mut quoted = false;
+    let split = line.char_indices().find_map(|(i, c)| match c {
+        '"' => {
+            quoted = !quoted;
+            None
+        }
+        ':' if !quoted => Some(i),
+        _ => None,
+    })?;
+    let (head, value) = (&line[..split], &line[split + 1..]);
+
+    let mut parts = head.split(';');
+    let name = parts.next()?.trim().to_ascii_uppercase();
+    let params = parts
+        .filter_map(|p| p.split_once('='))
+        .map(|(k, v)| (k.trim().to_ascii_uppercase(), v.trim_matches('"').to_string()))
+        .collect();
+    Some(Property { name, params, value: value.to_string() })
+}
+
+/// The properties of the first `component` (e.g. `VTODO`) in `source`, without those
+/// of components nested in it such as alarms.
+pub fn parse_component(source: &str, component: &str) -> Option<Vec<Property>> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in source.split('\n').map(|l| l.strip_suffix('\r').unwrap_or(l)) {
+        match (line.strip_prefix(' ').or_else(|| line.strip_prefix('\t')), lines.last_mut()) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ => lines.push(line.to_string()),
+        }
+    }
+
+    let mut properties = Vec::new();
+    let mut depth = 0;
+    for property in lines.iter().filter_map(|l| parse_line(l)) {
+        let value = property.value.trim();
+        match property.name.as_str() {
+            "BEGIN" if depth == 0 && value.eq_ignore_ascii_case(component) => depth = 1,
+            "BEGIN" if depth > 0 => depth += 1,
+            "END" if depth == 1 && value.eq_ignore_ascii_case(component) => return Some(properties),
+            "END" if depth > 0 => depth -= 1,
+            _ if depth == 1 => properties.push(property),
+            _ => {}
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_and_folds_long_lines() {
+        let mut calendar = Calendar::default();
+        calendar.text("SUMMARY", "Buy milk, eggs; bread\nand \\ more");
+        calendar.text("DESCRIPTION", &"é".repeat(60));
+        let out = calendar.finish();
+
+        assert!(out.starts_with("SUMMARY:Buy milk\\, eggs\\; bread\\nand \\\\ more\r\n"));
+        for line in out.split("\r\n") {
+            assert!(line.len() <= MAX_LINE_OCTETS, "{:?} is too long", line);
+        }
+    }
+
+    #[test]
+    fn parses_what_it_writes() {
+        let mut calendar = Calendar::default();
+        calendar.raw("BEGIN", "VTODO");
+        calendar.text("SUMMARY", &format!("A, long; title\n{}", "x".repeat(100)));
+        calendar.list("CATEGORIES", &["home".to_string(), "a,b".to_string()]);
+        calendar.raw("BEGIN", "VALARM");
+        calendar.raw("ACTION", "DISPLAY");
+        calendar.raw("END", "VALARM");
+        calendar.raw("END", "VTODO");
+
+        let properties = parse_component(&calendar.finish(), "VTODO").unwrap();
+        let find = |name: &str| properties.iter().find(|p| p.name == name);
+        assert_eq!(find("SUMMARY").unwrap().text(), format!("A, long; title\n{}", "x".repeat(100)));
+        assert_eq!(find("CATEGORIES").unwrap().texts(), vec!["home", "a,b"]);
+        // Properties of nested components are left out.
+        assert!(find("ACTION").is_none());
+    }
+
+    #[test]
+    fn parses_parameters_and_dates() {
+        let source = "BEGIN:VTODO\r\nDUE;VALUE=DATE:20250102\r\nDTSTAMP:20250102T030405Z\r\nX-URL;LABEL=\"a:b\":https://x\r\nEND:VTODO\r\n";
+        let properties = parse_component(source, "vtodo").unwrap();
+
+        assert_eq!(properties[0].param("value"), Some("DATE"));
+        assert_eq!(properties[0].date_time().unwrap().to_string(), "2025-01-02 00:00:00 UTC");
+        assert_eq!(properties[1].date_time().unwrap().to_string(), "2025-01-02 03:04:05 UTC");
+        assert_eq!(properties[2].param("LABEL"), Some("a:b"));
+        assert_eq!(properties[2].value, "https://x");
+    }
+
+    #[test]
+    fn resolves_time_zones() {
+        let source = "BEGIN:VTODO\r\nDUE;TZID=Europe/Berlin:20250702T120000\r\nDTSTART;TZID=Mars/Olympus:20250702T120000\r\nEND:VTODO\r\n";
+        let properties = parse_component(source, "VTODO").unwrap();
+
+        assert_eq!(properties[0].date_time().unwrap().to_string(), "2025-07-02 10:00:00 UTC");
+        assert!(properties[1].date_time().is_err());
+    }
+
+    #[test]
+    fn missing_component() {
+        assert!(parse_component("BEGIN:VEVENT\r\nEND:VEVENT\r\n", "VTODO").is_none());
+    }
+}
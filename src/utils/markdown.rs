@@ -0,0 +1,110 @@
+//! Task descriptions are CommonMark. The source is stored as written; HTML is rendered
+//! on request and sanitized, since descriptions are user input.
+
+use std::collections::HashSet;
+use pulldown_cmark::{html, Event, Options, Parser, Tag};
+use serde::{Deserialize, Serialize};
+
+/// Longest description accepted, in bytes.
+pub const MAX_DESCRIPTION_LEN: usize = 100_000;
+
+fn parser(source: &str) -> Parser<'_> {
+    Parser::new_ext(source, Options::ENABLE_TASKLISTS | Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH)
+}
+
+/// Renders `source` to HTML without scripts, event handlers or unsafe URLs.
+/// Checklist items keep their (disabled) checkboxes.
+pub fn render_html(source: &str) -> String {
+    let mut unsafe_html = String::new();
+    html::push_html(&mut unsafe_html, parser(source));
+
+    ammonia::Builder::default()
+        .add_tags(["input"])
+        .add_tag_attribute_values("input", "type", ["checkbox"])
+        .add_tag_attributes("input", ["checked", "disabled"])
+        .clean(&unsafe_html)
+        .to_string()
+}
+
+/// Progress of the `- [ ]` / `- [x]` items in a description.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Checklist {
+    pub total: u32,
+    pub completed: u32,
+}
+
+pub fn checklist(source: &str) -> Checklist {
+    let mut checklist = Checklist::default();
+    for event in parser(source) {
+        if let Event::TaskListMarker(checked) = event {
+            checklist.total += 1;
+            if checked {
+                checklist.completed += 1;
+            }
+        }
+    }
+    checklist
+}
+
+/// The task id in a link to `/tasks/{id}`, whether relative or absolute.
+fn linked_task_id(url: &str) -> Option<String> {
+    let (_, rest) = url.split_once("/tasks/")?;
+    let id: String = rest.chars().take_while(char::is_ascii_hexdigit).collect();
+    (id.len() == 24 && !rest[24..].starts_with(|c: char| c.is_ascii_alphanumeric())).then(|| id.to_lowercase())
+}
+
+/// Ids of the tasks `source` links to, in order of first appearance.
+pub fn linked_task_ids(source: &str) -> Vec<String> {
+    let mut seen = HashSet::new();
+    parser(source)
+        .flat_map(|event| match event {
+            Event::Start(Tag::Link { dest_url, .. }) => linked_task_id(&dest_url).into_iter().collect(),
+            Event::Text(text) => text.split_whitespace().filter_map(linked_task_id).collect(),
+            _ => Vec::new(),
+        })
+        .filter(|id| seen.insert(id.clone()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_scripts_handlers_and_unsafe_urls() {
+        let html = render_html(
+            "Hi <script>alert(1)</script>\n\n<img src=\"x.png\" onerror=\"alert(2)\">\n\n[click](javascript:alert(3))",
+        );
+        assert!(!html.contains("<script"));
+        assert!(!html.contains("alert(1)"));
+        assert!(!html.contains("onerror"));
+        assert!(!html.contains("javascript:"));
+        assert!(html.contains("<img src=\"x.png\""));
+    }
+
+    #[test]
+    fn keeps_checklist_boxes() {
+        let html = render_html("- [x] done\n- [ ] todo");
+        assert!(html.contains("<input"));
+        assert!(html.contains("checked"));
+        assert!(html.contains("disabled"));
+    }
+
+    #[test]
+    fn counts_checked_and_unchecked_items() {
+        let source = "- [x] one\n- [ ] two\n- [X] three\n- plain item\n\n1. [ ] numbered";
+        assert_eq!(checklist(source), Checklist { total: 4, completed: 2 });
+        assert_eq!(checklist("No checklist here"), Checklist::default());
+    }
+
+    #[test]
+    fn extracts_linked_task_ids() {
+        let first = "64b7f0c2a1b2c3d4e5f60718";
+        let second = "64B7F0C2A1B2C3D4E5F60719";
+        let source = format!(
+            "See [this](/tasks/{first}), https://example.com/tasks/{second}/comments and [again](/tasks/{first}).\n\n\
+             Not [a task](/tasks/{first}extra) nor [a project](/projects/{first}) nor `/tasks/{second}` code."
+        );
+        assert_eq!(linked_task_ids(&source), vec![first.to_string(), second.to_lowercase()]);
+    }
+}
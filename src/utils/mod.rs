@@ -1 +1,7 @@
-pub mod jwt;
\ No newline at end of file
+pub mod jwt;
+pub mod auth;
+pub mod pagination;
+pub mod rank;
+pub mod markdown;
+pub mod ical;
+pub mod dav;
@@ -0,0 +1,53 @@
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_LIMIT: u64 = 20;
+const MAX_LIMIT: u64 = 100;
+
+/// `?page=&limit=` query parameters; pages start at 1.
+#[derive(Debug, Deserialize)]
+pub struct PageQuery {
+    pub page: Option<u64>,
+    pub limit: Option<u64>,
+}
+
+impl PageQuery {
+    pub fn page(&self) -> u64 {
+        self.page.unwrap_or(1).max(1)
+    }
+
+    pub fn limit(&self) -> u64 {
+        self.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT)
+    }
+
+    /// Capped at `i64::MAX`, the largest skip MongoDB accepts; pages that far out are
+    /// simply empty.
+    pub fn skip(&self) -> u64 {
+        (self.page() - 1).saturating_mul(self.limit()).min(i64::MAX as u64)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub page: u64,
+    pub limit: u64,
+    pub total: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skips_whole_pages() {
+        let query = PageQuery { page: Some(3), limit: Some(10) };
+        assert_eq!(query.skip(), 20);
+        assert_eq!(PageQuery { page: Some(0), limit: None }.skip(), 0);
+    }
+
+    #[test]
+    fn caps_the_skip_for_huge_pages() {
+        let query = PageQuery { page: Some(u64::MAX), limit: Some(MAX_LIMIT) };
+        assert_eq!(query.skip(), i64::MAX as u64);
+    }
+}
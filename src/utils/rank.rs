@@ -0,0 +1,142 @@
+//! Fractional ranks: base-36 strings that sort lexicographically, so a task can be
+//! moved between two others by writing only its own rank.
+//!
+//! Ranks never end in `'0'`, which guarantees there is always room before any rank.
+
+const DIGITS: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+const BASE: u8 = 36;
+
+fn digit_value(c: u8) -> u8 {
+    DIGITS.iter().position(|&d| d == c).unwrap_or(0) as u8
+}
+
+fn encode(values: &[u8]) -> String {
+    values.iter().map(|&v| DIGITS[v as usize] as char).collect()
+}
+
+fn decode(rank: &str) -> Vec<u8> {
+    rank.bytes().map(digit_value).collect()
+}
+
+/// A rank strictly between `a` and `b`, where `b` may be open-ended.
+fn midpoint(a: &[u8], b: Option<&[u8]>) -> Vec<u8> {
+    if let Some(b) = b {
+        // Copy the common prefix, treating a missing digit of `a` as zero.
+        let mut n = 0;
+        while n < b.len() && a.get(n).copied().unwrap_or(0) == b[n] {
+            n += 1;
+        }
+        if n > 0 {
+            let mut out = b[..n].to_vec();
+            out.extend(midpoint(a.get(n..).unwrap_or_default(), Some(&b[n..])));
+            return out;
+        }
+    }
+
+    let low = a.first().copied().unwrap_or(0);
+    let high = b.map_or(BASE, |b| b[0]);
+    if high - low > 1 {
+        return vec![(low + high) / 2];
+    }
+    if let Some(b) = b
+        && b.len() > 1
+    {
+        return vec![b[0]];
+    }
+
+    let mut out = vec![low];
+    out.extend(midpoint(a.get(1..).unwrap_or_default(), None));
+    out
+}
+
+/// A rank after `a`, bumping its first digit that can grow so ranks stay short
+/// when tasks are repeatedly added to the end of a column.
+fn after(a: &[u8]) -> Vec<u8> {
+    match a.iter().position(|&d| d < BASE - 1) {
+        Some(i) => {
+            let mut out = a[..i].to_vec();
+            out.push(a[i] + 1);
+            out
+        }
+        None => midpoint(a, None),
+    }
+}
+
+/// Returns a rank that sorts after `before` and before `after`; either may be
+/// `None` for the start or end of a column.
+pub fn rank_between(before: Option<&str>, after_rank: Option<&str>) -> Result<String, String> {
+    match (before, after_rank) {
+        (None, None) => Ok(encode(&midpoint(&[], None))),
+        (Some(a), None) => Ok(encode(&after(&decode(a)))),
+        (a, Some(b)) => {
+            let a = a.unwrap_or_default();
+            if b.is_empty() || a >= b {
+                return Err(format!("Cannot rank between {:?} and {:?}", a, b));
+            }
+            Ok(encode(&midpoint(&decode(a), Some(&decode(b)))))
+        }
+    }
+}
+
+/// `count` evenly spaced, increasing ranks of equal length, for ranking existing tasks.
+pub fn rank_sequence(count: usize) -> Vec<String> {
+    let mut width = 1;
+    while (BASE as usize).pow(width) <= count {
+        width += 1;
+    }
+
+    (1..=count)
+        .map(|mut n| {
+            let mut values = vec![0; width as usize];
+            for slot in values.iter_mut().rev() {
+                *slot = (n % BASE as usize) as u8;
+                n /= BASE as usize;
+            }
+            // A trailing midpoint digit keeps ranks from ending in '0'.
+            values.push(BASE / 2);
+            encode(&values)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ranks_sort_between_their_neighbours() {
+        let first = rank_between(None, None).unwrap();
+        let last = rank_between(Some(&first), None).unwrap();
+        assert!(first < last);
+
+        let middle = rank_between(Some(&first), Some(&last)).unwrap();
+        assert!(first < middle && middle < last);
+
+        let before_first = rank_between(None, Some(&first)).unwrap();
+        assert!(before_first < first);
+    }
+
+    #[test]
+    fn repeated_inserts_keep_order_and_never_end_in_zero() {
+        let (mut low, high) = ("h".to_string(), "i".to_string());
+        for _ in 0..200 {
+            let next = rank_between(Some(&low), Some(&high)).unwrap();
+            assert!(low < next && next < high);
+            assert!(!next.ends_with('0'));
+            low = next;
+        }
+    }
+
+    #[test]
+    fn rejects_out_of_order_bounds() {
+        assert!(rank_between(Some("b"), Some("a")).is_err());
+        assert!(rank_between(Some("a"), Some("a")).is_err());
+    }
+
+    #[test]
+    fn sequences_increase_with_equal_length() {
+        let ranks = rank_sequence(100);
+        assert_eq!(ranks.len(), 100);
+        assert!(ranks.windows(2).all(|w| w[0] < w[1] && w[0].len() == w[1].len()));
+    }
+}